@@ -37,4 +37,6 @@ impl ConsumerChainPosContract {
     pub fn change_key(&self, staker_id: AccountId, key: Key) -> bool {
         self.should_change_key_success
     }
+
+    pub fn transfer_position(&self, staker_id: AccountId, new_staker_id: AccountId) {}
 }
@@ -0,0 +1,107 @@
+#![allow(unused)]
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, Balance, PanicOnDefault, Promise};
+
+/// The staking pool whose balances can be reduced to simulate losing funds.
+#[near_bindgen]
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault)]
+pub struct MockStakingPoolContract {
+    pub staked_balances: LookupMap<AccountId, Balance>,
+    pub unstaked_balances: LookupMap<AccountId, Balance>,
+}
+
+#[near_bindgen]
+impl MockStakingPoolContract {
+    #[init]
+    pub fn new() -> Self {
+        MockStakingPoolContract {
+            staked_balances: LookupMap::new(b"s".to_vec()),
+            unstaked_balances: LookupMap::new(b"u".to_vec()),
+        }
+    }
+
+    /// Reduce the staked balance of account, as if it's slashed.
+    pub fn lose_staked_balance(&mut self, account_id: AccountId, amount: U128) {
+        let staked_balance = self.get_staked(&account_id);
+        self.staked_balances
+            .insert(&account_id, &(staked_balance - amount.0));
+    }
+
+    pub fn ping(&mut self) {}
+
+    #[payable]
+    pub fn deposit(&mut self) {
+        let account_id = env::predecessor_account_id();
+        let unstaked_balance = self.get_unstaked(&account_id);
+        self.unstaked_balances
+            .insert(&account_id, &(unstaked_balance + env::attached_deposit()));
+    }
+
+    #[payable]
+    pub fn deposit_and_stake(&mut self) {
+        let account_id = env::predecessor_account_id();
+        let staked_balance = self.get_staked(&account_id);
+        self.staked_balances
+            .insert(&account_id, &(staked_balance + env::attached_deposit()));
+    }
+
+    pub fn withdraw(&mut self, amount: U128) {
+        let account_id = env::predecessor_account_id();
+        let unstaked_balance = self.get_unstaked(&account_id);
+        assert!(unstaked_balance >= amount.0, "Not enough unstaked balance.");
+        self.unstaked_balances
+            .insert(&account_id, &(unstaked_balance - amount.0));
+        Promise::new(account_id).transfer(amount.0);
+    }
+
+    pub fn stake(&mut self, amount: U128) {
+        let account_id = env::predecessor_account_id();
+        let unstaked_balance = self.get_unstaked(&account_id);
+        assert!(unstaked_balance >= amount.0, "Not enough unstaked balance.");
+        self.unstaked_balances
+            .insert(&account_id, &(unstaked_balance - amount.0));
+        let staked_balance = self.get_staked(&account_id);
+        self.staked_balances
+            .insert(&account_id, &(staked_balance + amount.0));
+    }
+
+    pub fn unstake(&mut self, amount: U128) {
+        let account_id = env::predecessor_account_id();
+        let staked_balance = self.get_staked(&account_id);
+        assert!(staked_balance >= amount.0, "Not enough staked balance.");
+        self.staked_balances
+            .insert(&account_id, &(staked_balance - amount.0));
+        let unstaked_balance = self.get_unstaked(&account_id);
+        self.unstaked_balances
+            .insert(&account_id, &(unstaked_balance + amount.0));
+    }
+
+    pub fn unstake_all(&mut self) {
+        let account_id = env::predecessor_account_id();
+        let staked_balance = self.get_staked(&account_id);
+        self.unstake(staked_balance.into());
+    }
+
+    pub fn get_account_staked_balance(&self, account_id: AccountId) -> U128 {
+        self.get_staked(&account_id).into()
+    }
+
+    pub fn get_account_unstaked_balance(&self, account_id: AccountId) -> U128 {
+        self.get_unstaked(&account_id).into()
+    }
+
+    pub fn get_account_total_balance(&self, account_id: AccountId) -> U128 {
+        (self.get_staked(&account_id) + self.get_unstaked(&account_id)).into()
+    }
+
+    fn get_staked(&self, account_id: &AccountId) -> Balance {
+        self.staked_balances.get(account_id).unwrap_or(0)
+    }
+
+    fn get_unstaked(&self, account_id: &AccountId) -> Balance {
+        self.unstaked_balances.get(account_id).unwrap_or(0)
+    }
+}
@@ -1,9 +1,59 @@
-use near_sdk::{Balance, EpochHeight};
+use near_sdk::{Balance, BlockHeight, EpochHeight};
 use near_units::parse_near;
 
 pub mod gas_constants;
 
-pub const NUM_EPOCHS_TO_UNLOCK: EpochHeight = 4;
-pub const STORAGE_FEE: Balance = parse_near!("0.01 near");
+pub const DEFAULT_NUM_EPOCHS_TO_UNLOCK: EpochHeight = 4;
+pub const MAX_NUM_EPOCHS_TO_UNLOCK: EpochHeight = 16;
+/// The estimated duration of one epoch, it's only used to estimate the time in views.
+pub const ESTIMATED_EPOCH_DURATION_SECONDS: u64 = 12 * 60 * 60;
+pub const DEFAULT_STORAGE_FEE: Balance = parse_near!("0.01 near");
 
-pub const REGISTER_STORAGE_FEE: Balance = parse_near!("0.02 near");
+pub const DEFAULT_REGISTER_STORAGE_FEE: Balance = parse_near!("0.02 near");
+/// The storage fees in protocol config should not be greater than it.
+pub const MAX_STORAGE_FEE: Balance = parse_near!("1 near");
+
+/// A staking pool is paused by reconciling if its shortfall is greater than 1% by default.
+pub const DEFAULT_SHORTFALL_PAUSE_THRESHOLD_BASIS_POINTS: u32 = 100;
+
+/// The staking pool lock is stale after this number of blocks, then anyone can clear it.
+pub const STAKING_POOL_LOCK_EXPIRY_BLOCKS: BlockHeight = 200;
+
+/// The staking pool contract rounds the balances by its share price, so the balances
+/// observed after an operation may differ from the expected ones by a few yocto.
+pub const STAKING_POOL_ROUNDING_TOLERANCE: Balance = 10;
+
+/// The keeper is rewarded only for the unstake batches not less than it, otherwise
+/// keeper fund can be drained by submitting and withdrawing tiny batches.
+pub const MIN_REWARDED_UNSTAKE_BATCH_AMOUNT: Balance = parse_near!("10 near");
+
+/// The sensitive owner actions are executed at least 2 days after scheduled by default.
+pub const DEFAULT_TIMELOCK_DELAY_SECONDS: u64 = 2 * 24 * 60 * 60;
+
+/// The timelock delay can't be shorter than it, so that pending actions can be reviewed before executed.
+pub const MIN_TIMELOCK_DELAY_SECONDS: u64 = 24 * 60 * 60;
+
+/// The register fee pays for the storage of consumer chain, so at most half of it
+/// can be cut as protocol fee.
+pub const MAX_REGISTER_FEE_BASIS_POINTS: u32 = 5_000;
+
+/// The default page size of list views if `limit` is not given.
+pub const DEFAULT_VIEW_LIMIT: u32 = 100;
+
+/// The max number of pending withdrawals paid out by one `withdraw_all`,
+/// so that the transfers and their callbacks fit in the prepaid gas.
+pub const MAX_WITHDRAW_ALL_LIMIT: u32 = 20;
+
+/// The max number of pending withdrawals scanned by one `withdraw_all`,
+/// so that its gas is bounded however many pending withdrawals an account has.
+pub const MAX_WITHDRAW_ALL_SCAN: u32 = 100;
+
+/// The max number of pending withdrawals settled by one `settle_liquidity_pool`.
+pub const MAX_SETTLE_LIQUIDITY_POOL_LIMIT: u32 = 50;
+
+/// The max number of pending withdrawals scanned by one `settle_liquidity_pool`,
+/// so that its gas is bounded however many pending withdrawals are taken over.
+pub const MAX_SETTLE_LIQUIDITY_POOL_SCAN: u32 = 100;
+
+/// The schema version of state which is migrated by `migrate_state` and `migrate_accounts`.
+pub const CURRENT_SCHEMA_VERSION: u32 = 4;
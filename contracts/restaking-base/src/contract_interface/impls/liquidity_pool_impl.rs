@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use crate::{
+    constants::{MAX_SETTLE_LIQUIDITY_POOL_LIMIT, MAX_SETTLE_LIQUIDITY_POOL_SCAN},
+    *,
+};
+
+#[near_bindgen]
+impl LiquidityPoolAction for RestakingBaseContract {
+    #[payable]
+    fn add_liquidity(&mut self) -> U128 {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Stake);
+        assert_attached_near();
+        let account_id = env::predecessor_account_id();
+        assert!(
+            self.accounts.contains_key(&account_id),
+            "Should register by storage_deposit first."
+        );
+
+        let amount = env::attached_deposit();
+        let initial_storage_usage = env::storage_usage();
+        let increase_shares = self.liquidity_pool.add_liquidity(&account_id, amount);
+        self.internal_charge_storage_usage(&account_id, initial_storage_usage);
+
+        Event::AddLiquidity {
+            account_id: &account_id,
+            amount: &amount.into(),
+            increase_shares: &increase_shares.into(),
+        }
+        .emit();
+        increase_shares.into()
+    }
+
+    #[payable]
+    fn remove_liquidity(&mut self, shares: U128) -> U128 {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Withdraw);
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+
+        let initial_storage_usage = env::storage_usage();
+        let amount = self.liquidity_pool.remove_liquidity(&account_id, shares.0);
+        self.internal_refund_released_storage(&account_id, initial_storage_usage);
+        self.transfer_near(account_id.clone(), amount);
+
+        Event::RemoveLiquidity {
+            account_id: &account_id,
+            amount: &amount.into(),
+            decrease_shares: &shares,
+        }
+        .emit();
+        amount.into()
+    }
+
+    #[payable]
+    fn instant_withdraw(&mut self, id: WithdrawalCertificate) -> U128 {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Withdraw);
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_not_slashable(&account_id);
+
+        let initial_storage_usage = env::storage_usage();
+        let pending_withdrawal = self.internal_use_account(&account_id, |account| {
+            account
+                .pending_withdrawals
+                .remove(&id)
+                .unwrap_or_else(|| panic!("Failed to get pending withdrawal by {}", id.0))
+        });
+        self.internal_refund_released_storage(&account_id, initial_storage_usage);
+        assert!(
+            pending_withdrawal.unstake_batch_id.is_some(),
+            "Failed to instant withdraw, the pending withdrawal doesn't belong to any unstake batch."
+        );
+        assert!(
+            pending_withdrawal.is_withdrawable(),
+            "Failed to instant withdraw, unlock timestamp:{}, current timestamp:{}",
+            pending_withdrawal.unlock_time,
+            env::block_timestamp()
+        );
+
+        let beneficiary = pending_withdrawal.beneficiary.clone();
+        let amount = pending_withdrawal.amount;
+        let pay_amount = self.liquidity_pool.take_over(pending_withdrawal);
+        self.transfer_near(beneficiary, pay_amount);
+
+        Event::InstantWithdraw {
+            account_id: &account_id,
+            withdrawal_certificate: &id,
+            amount: &amount.into(),
+            pay_amount: &pay_amount.into(),
+        }
+        .emit();
+        pay_amount.into()
+    }
+
+    fn settle_liquidity_pool(&mut self, limit: u32) -> U128 {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::BatchProcessing);
+        // The scan is bounded, so that the gas doesn't grow with the taken over pending withdrawals.
+        let limit = min(limit, MAX_SETTLE_LIQUIDITY_POOL_LIMIT) as usize;
+        let mut staking_pools: HashMap<PoolId, StakingPool> = HashMap::new();
+        let mut withdrawable_pending_withdrawals = vec![];
+        for pending_withdrawal in self
+            .liquidity_pool
+            .pending_withdrawals
+            .values()
+            .take(MAX_SETTLE_LIQUIDITY_POOL_SCAN as usize)
+        {
+            if withdrawable_pending_withdrawals.len() == limit {
+                break;
+            }
+            let staking_pool = staking_pools
+                .entry(pending_withdrawal.pool_id.clone())
+                .or_insert_with(|| {
+                    self.internal_get_staking_pool_or_panic(&pending_withdrawal.pool_id)
+                });
+            if self.internal_is_withdrawable(staking_pool, &pending_withdrawal) {
+                withdrawable_pending_withdrawals.push(pending_withdrawal);
+            }
+        }
+
+        let mut settled_amount: Balance = 0;
+        for pending_withdrawal in withdrawable_pending_withdrawals {
+            self.internal_use_staking_pool_or_panic(&pending_withdrawal.pool_id, |staking_pool| {
+                staking_pool.withdraw_from_unstake_batch(
+                    pending_withdrawal.amount,
+                    pending_withdrawal.unstake_batch_id.unwrap(),
+                )
+            });
+            self.liquidity_pool.settle(&pending_withdrawal);
+            settled_amount += pending_withdrawal.amount;
+            Event::Withdraw {
+                withdrawal_certificate: &pending_withdrawal.withdrawal_certificate,
+            }
+            .emit();
+        }
+        settled_amount.into()
+    }
+}
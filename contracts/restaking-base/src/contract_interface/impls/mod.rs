@@ -1,3 +1,4 @@
+pub mod liquidity_pool_impl;
 pub mod owner_impl;
 pub mod restaking_impl;
 pub mod staking_impl;
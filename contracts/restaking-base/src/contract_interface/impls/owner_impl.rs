@@ -1,4 +1,4 @@
-use crate::{constants::STORAGE_FEE, contract_interface::owner::OwnerAction, *};
+use crate::{constants::MIN_TIMELOCK_DELAY_SECONDS, contract_interface::owner::OwnerAction, *};
 
 #[near_bindgen]
 impl OwnerAction for RestakingBaseContract {
@@ -6,35 +6,132 @@ impl OwnerAction for RestakingBaseContract {
     fn set_new_owner(&mut self, new_owner: AccountId) {
         assert_one_yocto();
         self.assert_owner();
-        self.owner = new_owner;
+        self.pending_owner = Some(new_owner.clone());
+        Event::ProposeOwner {
+            owner: &self.owner,
+            pending_owner: &new_owner,
+        }
+        .emit();
     }
 
     #[payable]
-    fn set_cc_register_fee(&mut self, new_cc_register_fee: U128) {
+    fn accept_ownership(&mut self) {
+        assert_one_yocto();
+        let new_owner = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_owner.as_ref(),
+            Some(&new_owner),
+            "Predecessor should be pending owner!"
+        );
+        self.pending_owner = None;
+        let old_owner = std::mem::replace(&mut self.owner, new_owner);
+        Event::AcceptOwnership {
+            old_owner: &old_owner,
+            new_owner: &self.owner,
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn cancel_ownership_transfer(&mut self) {
+        assert_one_yocto();
+        self.assert_owner();
+        let pending_owner = self
+            .pending_owner
+            .take()
+            .expect("There is no pending owner.");
+        Event::CancelOwnershipTransfer {
+            owner: &self.owner,
+            pending_owner: &pending_owner,
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn grant_role(&mut self, account_id: AccountId, role: Role) {
         assert_one_yocto();
         self.assert_owner();
+        if self.internal_grant_role(&account_id, role) {
+            Event::GrantRole {
+                account_id: &account_id,
+                role: &role,
+                operator: &env::predecessor_account_id(),
+            }
+            .emit();
+        }
+    }
+
+    #[payable]
+    fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        assert_one_yocto();
+        self.assert_owner();
+        if self.internal_revoke_role(&account_id, role) {
+            Event::RevokeRole {
+                account_id: &account_id,
+                role: &role,
+                operator: &env::predecessor_account_id(),
+            }
+            .emit();
+        }
+    }
+
+    #[payable]
+    fn set_cc_register_fee(&mut self, new_cc_register_fee: U128) {
+        assert_one_yocto();
+        self.assert_role(Role::FeeManager);
         self.cc_register_fee = new_cc_register_fee.into();
     }
 
     #[payable]
     fn set_slash_guarantee(&mut self, new_slash_guarantee: U128) {
         assert_one_yocto();
-        self.assert_owner();
-        self.slash_guarantee = new_slash_guarantee.into();
+        self.internal_schedule_action(TimelockAction::SetSlashGuarantee {
+            new_slash_guarantee,
+        });
+    }
+
+    #[payable]
+    fn set_keeper_reward(&mut self, new_keeper_reward: U128) {
+        assert_one_yocto();
+        self.assert_role(Role::FeeManager);
+        self.keeper_reward = new_keeper_reward.into();
+    }
+
+    #[payable]
+    fn set_liquidity_pool_fee(&mut self, fee_basis_points: u32) {
+        assert_one_yocto();
+        self.assert_role(Role::FeeManager);
+        assert!(
+            fee_basis_points <= FULL_BASIS_POINTS,
+            "The fee basis points should not greater than {}.",
+            FULL_BASIS_POINTS
+        );
+        self.liquidity_pool.fee_basis_points = fee_basis_points;
     }
 
     #[payable]
     fn set_contract_running(&mut self) {
         assert_one_yocto();
-        self.assert_owner();
-        self.is_contract_running = true;
+        self.assert_role(Role::PauseGuardian);
+        for category in PauseCategory::ALL {
+            self.internal_set_paused(category, false);
+        }
     }
 
     #[payable]
     fn set_contract_pause(&mut self) {
         assert_one_yocto();
-        self.assert_owner();
-        self.is_contract_running = false;
+        self.assert_role(Role::PauseGuardian);
+        for category in PauseCategory::ALL {
+            self.internal_set_paused(category, true);
+        }
+    }
+
+    #[payable]
+    fn set_paused(&mut self, category: PauseCategory, paused: bool) {
+        assert_one_yocto();
+        self.assert_role(Role::PauseGuardian);
+        self.internal_set_paused(category, paused);
     }
 
     #[payable]
@@ -45,35 +142,191 @@ impl OwnerAction for RestakingBaseContract {
         new_beneficiary: AccountId,
     ) {
         assert_one_yocto();
-        self.assert_owner();
-        self.internal_use_account(&account_id, |account| {
-            let mut pending_withdrawal = account
-                .pending_withdrawals
-                .get(&withdraw_certificate)
-                .unwrap();
-            pending_withdrawal.beneficiary = new_beneficiary.clone();
-            account
-                .pending_withdrawals
-                .insert(&withdraw_certificate, &pending_withdrawal);
-        })
+        self.internal_get_account_or_panic(&account_id)
+            .pending_withdrawals
+            .get(&withdraw_certificate)
+            .expect("The pending withdrawal is not found.");
+        self.internal_schedule_action(TimelockAction::SetWithdrawalBeneficiary {
+            account_id,
+            withdraw_certificate,
+            new_beneficiary,
+        });
     }
 
     #[payable]
     fn set_staker_unbonding_unlock_time_as_current_time(&mut self, staker_id: AccountId) {
         assert_one_yocto();
-        self.assert_owner();
-        self.internal_use_staker_or_panic(&staker_id, |staker| {
-            staker.unbonding_unlock_time = env::block_timestamp();
+        self.internal_get_staker_or_panic(&staker_id);
+        self.internal_schedule_action(TimelockAction::SetStakerUnbondingUnlockTimeAsCurrentTime {
+            staker_id,
         });
     }
 
     #[payable]
-    fn set_staking_pool_unlock(&mut self, pool_id: PoolId) {
+    fn set_staking_pool_unlock(&mut self, pool_id: PoolId) -> Promise {
         assert_one_yocto();
-        self.assert_owner();
+        self.assert_role(Role::PoolOperator);
+        self.internal_clear_lock(pool_id)
+    }
+
+    #[payable]
+    fn resolve_stale_operation(&mut self, pool_id: PoolId, is_done: bool) {
+        assert_one_yocto();
+        self.assert_role(Role::PoolOperator);
+        let mut staking_pool = self.internal_get_staking_pool_or_panic(&pool_id);
+        assert!(
+            staking_pool.is_lock_stale(),
+            "The lock of staking pool({}) is not stale.",
+            pool_id
+        );
+        let stale_lock = staking_pool.lock.take().unwrap();
+        let new_total_staked_balance = match &stale_lock.operation {
+            StakingPoolOperation::Stake { amount, .. }
+            | StakingPoolOperation::IncreaseStake { amount, .. } => {
+                staking_pool.total_staked_balance + amount.0
+            }
+            _ => staking_pool.total_staked_balance,
+        };
+        self.internal_finalize_stale_operation(
+            &mut staking_pool,
+            &stale_lock,
+            is_done,
+            new_total_staked_balance,
+        );
+        self.internal_save_staking_pool(&staking_pool);
+        Event::ResolveStaleOperation {
+            pool_id: &pool_id,
+            lock: &stale_lock,
+            is_done: &is_done,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn set_shortfall_pause_threshold(&mut self, basis_points: u32) {
+        assert_one_yocto();
+        self.assert_role(Role::PoolOperator);
+        assert!(
+            basis_points <= FULL_BASIS_POINTS,
+            "The threshold basis points should not greater than {}.",
+            FULL_BASIS_POINTS
+        );
+        self.shortfall_pause_threshold_basis_points = basis_points;
+    }
+
+    #[payable]
+    fn resume_staking_pool(&mut self, pool_id: PoolId) {
+        assert_one_yocto();
+        self.assert_role(Role::PoolOperator);
         self.internal_use_staking_pool_or_panic(&pool_id, |pool| {
-            pool.unlock();
+            pool.paused = false;
         });
+        Event::ResumeStakingPool { pool_id: &pool_id }.emit();
+    }
+
+    #[payable]
+    fn set_timelock_delay(&mut self, delay_seconds: DurationOfSeconds) {
+        assert_one_yocto();
+        assert!(
+            delay_seconds >= MIN_TIMELOCK_DELAY_SECONDS,
+            "The timelock delay should not less than {} seconds.",
+            MIN_TIMELOCK_DELAY_SECONDS
+        );
+        self.internal_schedule_action(TimelockAction::SetTimelockDelay { delay_seconds });
+    }
+
+    #[payable]
+    fn execute_pending_action(&mut self, id: U64) {
+        assert_one_yocto();
+        let pending_action = self.internal_take_pending_action_or_panic(id);
+        assert!(
+            env::block_timestamp() >= pending_action.executable_at,
+            "The pending action({}) can't be executed until {}.",
+            id.0,
+            pending_action.executable_at
+        );
+        if !self.internal_execute_action(pending_action.action.clone()) {
+            Event::DropAction {
+                pending_action: &pending_action,
+                operator: &env::predecessor_account_id(),
+            }
+            .emit();
+            return;
+        }
+        Event::ExecuteAction {
+            pending_action: &pending_action,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn cancel_pending_action(&mut self, id: U64) {
+        assert_one_yocto();
+        let pending_action = self.internal_take_pending_action_or_panic(id);
+        self.assert_role(pending_action.action.required_role());
+        Event::CancelAction {
+            pending_action: &pending_action,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn update_config(&mut self, config: ProtocolConfig) {
+        assert_one_yocto();
+        self.assert_role(Role::ConfigManager);
+        self.internal_update_config(config);
+    }
+
+    #[payable]
+    fn set_protocol_fee(&mut self, protocol_fee: ProtocolFee) {
+        assert_one_yocto();
+        self.assert_role(Role::FeeManager);
+        protocol_fee.assert_valid();
+        self.protocol_fee = protocol_fee;
+        Event::SetProtocolFee {
+            protocol_fee: &self.protocol_fee,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn set_protocol_treasury_account(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+        Event::SetProtocolTreasuryAccount {
+            old_treasury_account: &self.protocol_treasury_account,
+            new_treasury_account: &account_id,
+        }
+        .emit();
+        self.protocol_treasury_account = account_id;
+    }
+
+    #[payable]
+    fn withdraw_protocol_fee(&mut self, amount: Option<U128>) -> U128 {
+        assert_one_yocto();
+        self.assert_protocol_treasury();
+        let amount = amount
+            .map(|amount| amount.0)
+            .unwrap_or(self.protocol_fee_balance);
+        assert!(
+            amount <= self.protocol_fee_balance,
+            "The amount({}) is greater than the accrued protocol fee({}).",
+            amount,
+            self.protocol_fee_balance
+        );
+        self.protocol_fee_balance -= amount;
+        self.transfer_near(self.protocol_treasury_account.clone(), amount);
+        Event::WithdrawProtocolFee {
+            treasury_account: &self.protocol_treasury_account,
+            amount: &amount.into(),
+            protocol_fee_balance: &self.protocol_fee_balance.into(),
+        }
+        .emit();
+        amount.into()
     }
 }
 
@@ -85,17 +338,4 @@ impl RestakingBaseContract {
             "Predecessor should be owner!"
         );
     }
-
-    pub(crate) fn assert_contract_is_running(&self) {
-        assert!(self.is_contract_running, "The contract is pause.");
-    }
-
-    pub(crate) fn assert_attached_storage_fee(&self) {
-        assert_eq!(
-            env::attached_deposit(),
-            STORAGE_FEE,
-            "Should attach {} near as storage fee.",
-            STORAGE_FEE
-        );
-    }
 }
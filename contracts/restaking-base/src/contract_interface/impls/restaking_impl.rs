@@ -1,10 +1,14 @@
-use crate::{contract_interface::view::RestakingView, types::ValidatorSetInSequence, *};
+use crate::{
+    constants::DEFAULT_VIEW_LIMIT, contract_interface::view::RestakingView,
+    types::ValidatorSetInSequence, *,
+};
 
 #[near_bindgen]
 impl ConsumerChainAction for RestakingBaseContract {
     #[payable]
     fn blackout(&mut self, consumer_chain_id: ConsumerChainId, staker_id: StakerId) {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Slash);
         self.internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
             consumer_chain.assert_cc_pos_account();
             consumer_chain.blacklist.insert(&staker_id);
@@ -18,7 +22,8 @@ impl ConsumerChainAction for RestakingBaseContract {
         slash_items: Vec<(AccountId, U128)>,
         evidence_sha256_hash: String,
     ) -> SlashId {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Slash);
         assert_eq!(
             env::attached_deposit(),
             self.slash_guarantee,
@@ -39,6 +44,9 @@ impl ConsumerChainAction for RestakingBaseContract {
         }
 
         let slash_id = U64(self.next_uuid());
+        self.internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
+            consumer_chain.pending_slash_count += 1
+        });
 
         Event::RequestSlash {
             consumer_chain_id: &consumer_chain_id,
@@ -48,7 +56,7 @@ impl ConsumerChainAction for RestakingBaseContract {
         .emit();
 
         // needn't check storage, the slash guarantee should able to cover storage.
-        self.slashes.insert(
+        self.internal_save_slash(
             &slash_id,
             &Slash {
                 consumer_chain_id,
@@ -60,13 +68,45 @@ impl ConsumerChainAction for RestakingBaseContract {
 
         slash_id
     }
+
+    #[payable]
+    fn deposit_reward(&mut self, consumer_chain_id: ConsumerChainId, staker_id: StakerId) -> U128 {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Bond);
+        assert_attached_near();
+        let consumer_chain = self.internal_get_consumer_chain_or_panic(&consumer_chain_id);
+        consumer_chain.assert_cc_pos_account();
+        let staker = self.internal_get_staker_or_panic(&staker_id);
+        assert!(
+            staker.allow_slash(&consumer_chain_id),
+            "Failed to deposit reward, {} is not bonding in {}.",
+            staker_id,
+            consumer_chain_id
+        );
+
+        let amount = env::attached_deposit();
+        let protocol_fee = self.internal_accrue_protocol_fee(ProtocolFeeSource::Reward, amount);
+        let reward = amount - protocol_fee;
+        if reward > 0 {
+            self.transfer_near(staker_id.clone(), reward);
+        }
+        Event::DepositReward {
+            consumer_chain_id: &consumer_chain_id,
+            staker_id: &staker_id,
+            amount: &amount.into(),
+            protocol_fee: &protocol_fee.into(),
+        }
+        .emit();
+        reward.into()
+    }
 }
 
 #[near_bindgen]
 impl GovernanceAction for RestakingBaseContract {
     #[payable]
     fn register_consumer_chain(&mut self, register_param: ConsumerChainRegisterParam) {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::ConsumerChainRegistration);
         // check register_fee eq env::attached_deposit
         assert_eq!(
             env::attached_deposit(),
@@ -92,8 +132,16 @@ impl GovernanceAction for RestakingBaseContract {
         );
 
         // needn't check storage, the register fee should able to cover storage.
+        let initial_storage_usage = env::storage_usage();
         self.consumer_chains
             .insert(&consumer_chain.consumer_chain_id, &consumer_chain);
+        // The protocol fee is only cut from the part of register fee not used by storage.
+        let storage_cost =
+            Balance::from(env::storage_usage() - initial_storage_usage) * env::storage_byte_cost();
+        self.internal_accrue_protocol_fee(
+            ProtocolFeeSource::RegisterFee,
+            self.cc_register_fee.saturating_sub(storage_cost),
+        );
 
         Event::RegisterConsumerChain {
             consumer_chain_info: &consumer_chain.into(),
@@ -104,7 +152,8 @@ impl GovernanceAction for RestakingBaseContract {
 
     #[payable]
     fn deregister_consumer_chain(&mut self, consumer_chain_id: ConsumerChainId) {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::ConsumerChainRegistration);
         assert_one_yocto();
 
         let mut consumer_chain = self.internal_get_consumer_chain_or_panic(&consumer_chain_id);
@@ -123,7 +172,8 @@ impl GovernanceAction for RestakingBaseContract {
         consumer_chain_id: ConsumerChainId,
         update_param: ConsumerChainUpdateParam,
     ) {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::ConsumerChainRegistration);
         assert_one_yocto();
         let mut consumer_chain = self.consumer_chains.get(&consumer_chain_id).expect(
             format!(
@@ -151,6 +201,14 @@ impl GovernanceAction for RestakingBaseContract {
         self.consumer_chains
             .insert(&consumer_chain_id, &consumer_chain);
 
+        if let Some(pending_governance) = &update_param.governance {
+            Event::ProposeConsumerChainGovernance {
+                consumer_chain_id: &consumer_chain_id,
+                governance: &consumer_chain.governance,
+                pending_governance,
+            }
+            .emit();
+        }
         Event::UpdateConsumerChain {
             consumer_chain_info: &consumer_chain.into(),
             consumer_chain_update_param: &update_param,
@@ -158,11 +216,46 @@ impl GovernanceAction for RestakingBaseContract {
         .emit();
     }
 
+    #[payable]
+    fn accept_consumer_chain_governance(&mut self, consumer_chain_id: ConsumerChainId) {
+        assert_one_yocto();
+        let old_governance = self
+            .internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
+                consumer_chain.accept_governance()
+            });
+        Event::AcceptConsumerChainGovernance {
+            consumer_chain_id: &consumer_chain_id,
+            old_governance: &old_governance,
+            new_governance: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn cancel_consumer_chain_governance_transfer(&mut self, consumer_chain_id: ConsumerChainId) {
+        assert_one_yocto();
+        let pending_governance =
+            self.internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
+                consumer_chain.assert_cc_gov();
+                consumer_chain
+                    .pending_governance
+                    .take()
+                    .expect("There is no pending governance.")
+            });
+        Event::CancelConsumerChainGovernanceTransfer {
+            consumer_chain_id: &consumer_chain_id,
+            governance: &env::predecessor_account_id(),
+            pending_governance: &pending_governance,
+        }
+        .emit();
+    }
+
     #[allow(unused)]
     #[payable]
     fn slash(&mut self, consumer_chain_id: ConsumerChainId, slash_id: SlashId, is_approve: bool) {
         unreachable!();
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Slash);
         // todo if slash item too much, need finish slash by multi transaction.
         assert_one_yocto();
 
@@ -194,16 +287,48 @@ impl GovernanceAction for RestakingBaseContract {
 
         self.internal_remove_slash(&slash_id);
     }
+
+    #[payable]
+    fn reject_slash(&mut self, consumer_chain_id: ConsumerChainId, slash_id: SlashId) {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Slash);
+        assert_one_yocto();
+
+        let slash = self.get_slash_or_panic(&slash_id);
+        assert_eq!(
+            slash.consumer_chain_id, consumer_chain_id,
+            "The slash is not belong to {}.",
+            consumer_chain_id
+        );
+        let consumer_chain = self.internal_get_consumer_chain_or_panic(&consumer_chain_id);
+        if !self.internal_has_role(&env::predecessor_account_id(), Role::SlashArbiter) {
+            consumer_chain.assert_cc_gov();
+        }
+
+        self.internal_remove_slash(&slash_id);
+        Event::RejectSlash {
+            consumer_chain_id: &consumer_chain_id,
+            slash_id: &slash_id,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
 }
 
 #[near_bindgen]
 impl StakerRestakingAction for RestakingBaseContract {
     #[payable]
-    fn bond(&mut self, consumer_chain_id: ConsumerChainId, key: String) -> PromiseOrValue<bool> {
-        self.assert_contract_is_running();
-        self.assert_attached_storage_fee();
+    fn bond(
+        &mut self,
+        consumer_chain_id: ConsumerChainId,
+        key: String,
+        staker_id: Option<StakerId>,
+    ) -> PromiseOrValue<bool> {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Bond);
 
-        let staker_id = env::predecessor_account_id();
+        let staker_id = self.internal_get_restaking_staker_id(staker_id, &consumer_chain_id);
+        self.internal_assert_storage_prepaid(&staker_id);
         let consumer_chain = self.internal_get_consumer_chain_or_panic(&consumer_chain_id);
 
         consumer_chain.assert_chain_active();
@@ -224,27 +349,41 @@ impl StakerRestakingAction for RestakingBaseContract {
             env::block_timestamp()
         );
 
-        self.ping(Option::None)
+        let pool_id = self.internal_get_staker_selected_pool_or_panic(&staker_id);
+        self.ping(Some(pool_id))
             .then(
                 ext_consumer_chain_pos::ext(consumer_chain.pos_account_id)
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_BOND))
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.bond))
                     .bond(staker_id.clone(), key.clone()),
             )
             .then(
                 Self::ext(env::current_account_id())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_BOND_CALLBACK))
-                    .bond_callback(consumer_chain_id, key, staker_id),
+                    .with_attached_deposit(env::attached_deposit())
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.bond_callback))
+                    .bond_callback(
+                        consumer_chain_id,
+                        key,
+                        staker_id,
+                        env::predecessor_account_id(),
+                    ),
             )
             .into()
     }
 
     #[payable]
-    fn change_key(&mut self, consumer_chain_id: ConsumerChainId, new_key: String) {
-        self.assert_contract_is_running();
+    fn change_key(
+        &mut self,
+        consumer_chain_id: ConsumerChainId,
+        new_key: String,
+        staker_id: Option<StakerId>,
+    ) {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Bond);
         assert_attached_near();
 
         // 1. check if bonding
-        let staker = self.internal_get_staker_or_panic(&env::predecessor_account_id());
+        let staker_id = self.internal_get_restaking_staker_id(staker_id, &consumer_chain_id);
+        let staker = self.internal_get_staker_or_panic(&staker_id);
         let consumer_chain = self.internal_get_consumer_chain_or_panic(&consumer_chain_id);
         assert!(
             staker
@@ -257,52 +396,130 @@ impl StakerRestakingAction for RestakingBaseContract {
         );
 
         ext_consumer_chain_pos::ext(consumer_chain.pos_account_id)
-            .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_CHANGE_KEY))
+            .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.change_key))
             .change_key(staker.staker_id, new_key);
     }
 
     #[payable]
-    fn unbond(&mut self, consumer_chain_id: ConsumerChainId) {
-        self.assert_contract_is_running();
+    fn unbond(&mut self, consumer_chain_id: ConsumerChainId, staker_id: Option<StakerId>) {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Bond);
         assert_one_yocto();
-        let staker_id = env::predecessor_account_id();
-        self.internal_use_staker_or_panic(&staker_id, |staker| staker.unbond(&consumer_chain_id));
+        let staker_id = self.internal_get_restaking_staker_id(staker_id, &consumer_chain_id);
+        let initial_storage_usage = env::storage_usage();
+        let staker = self.internal_use_staker_or_panic(&staker_id, |staker| {
+            staker.unbond(&consumer_chain_id);
+            (staker.select_staking_pool.clone(), staker.shares)
+        });
         self.internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
-            consumer_chain.unbond(&staker_id)
+            if let (Some(pool_id), shares) = &staker {
+                consumer_chain.unbond(&staker_id, pool_id, *shares);
+            }
         });
+        self.internal_refund_released_storage(&staker_id, initial_storage_usage);
         Event::StakerUnbond {
             staker_id: &staker_id,
             consumer_chain_id: &consumer_chain_id,
         }
         .emit();
     }
+
+    #[payable]
+    fn authorize_operator(
+        &mut self,
+        operator_id: AccountId,
+        consumer_chain_id: Option<ConsumerChainId>,
+    ) {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Bond);
+        assert_one_yocto();
+        let staker_id = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+        self.internal_use_staker_or_panic(&staker_id, |staker| match &consumer_chain_id {
+            Some(consumer_chain_id) => {
+                staker
+                    .consumer_chain_operators
+                    .insert(consumer_chain_id, &operator_id);
+            }
+            None => staker.global_operator = Some(operator_id.clone()),
+        });
+        self.internal_charge_storage_usage(&staker_id, initial_storage_usage);
+        Event::AuthorizeOperator {
+            staker_id: &staker_id,
+            operator_id: &operator_id,
+            consumer_chain_id: &consumer_chain_id,
+        }
+        .emit();
+    }
+
+    #[payable]
+    fn revoke_operator(&mut self, consumer_chain_id: Option<ConsumerChainId>) {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Bond);
+        assert_one_yocto();
+        let staker_id = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+        let operator_id =
+            self.internal_use_staker_or_panic(&staker_id, |staker| match &consumer_chain_id {
+                Some(consumer_chain_id) => {
+                    staker.consumer_chain_operators.remove(consumer_chain_id)
+                }
+                None => staker.global_operator.take(),
+            });
+        let operator_id = operator_id.expect("Failed to revoke, the operator is not found.");
+        self.internal_refund_released_storage(&staker_id, initial_storage_usage);
+        Event::RevokeOperator {
+            staker_id: &staker_id,
+            operator_id: &operator_id,
+            consumer_chain_id: &consumer_chain_id,
+        }
+        .emit();
+    }
 }
 
 #[near_bindgen]
 impl RestakingCallback for RestakingBaseContract {
+    #[payable]
     #[private]
     fn bond_callback(
         &mut self,
         consumer_chain_id: ConsumerChainId,
         key: String,
         staker_id: AccountId,
+        payer_id: AccountId,
     ) -> PromiseOrValue<bool> {
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Failed => {
+                // The storage deposit is optional since storage may be prepaid.
+                if env::attached_deposit() > 0 {
+                    self.transfer_near(payer_id, env::attached_deposit());
+                }
                 emit_callback_failed_event();
                 PromiseOrValue::Value(false)
             }
             PromiseResult::Successful(_) => {
+                self.internal_add_storage_balance(&staker_id, env::attached_deposit());
+                let initial_storage_usage = env::storage_usage();
                 let mut staker = self.internal_get_staker_or_panic(&staker_id);
                 let mut consumer_chain =
                     self.internal_get_consumer_chain_or_panic(&consumer_chain_id);
 
                 staker.bond(&consumer_chain_id, consumer_chain.unbonding_period);
-                consumer_chain.bond(&staker_id);
+                consumer_chain.bond(
+                    &staker_id,
+                    &self.internal_get_staker_selected_pool_or_panic(&staker_id),
+                    staker.shares,
+                );
 
                 self.internal_save_staker(&staker_id, &staker);
                 self.internal_save_consumer_chain(&consumer_chain_id, &consumer_chain);
+                self.internal_record_storage_usage(&staker_id, initial_storage_usage);
+                self.internal_refund_unused_storage_deposit(
+                    &staker_id,
+                    payer_id,
+                    env::attached_deposit(),
+                );
 
                 Event::StakerBond {
                     staker_id: &staker_id,
@@ -346,13 +563,35 @@ impl RestakingView for RestakingBaseContract {
             .map(ConsumerChainInfo::from)
     }
 
-    fn get_consumer_chains(&self) -> Vec<ConsumerChainInfo> {
+    fn get_consumer_chains(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<ConsumerChainInfo> {
         self.consumer_chains
             .values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
             .map(ConsumerChainInfo::from)
             .collect_vec()
     }
 
+    fn get_consumer_chain_stats(
+        &self,
+        consumer_chain_id: ConsumerChainId,
+    ) -> Option<ConsumerChainStats> {
+        self.consumer_chains
+            .get(&consumer_chain_id)
+            .map(|consumer_chain| self.internal_get_consumer_chain_stats(&consumer_chain))
+    }
+
+    fn get_protocol_stats(&self, from_index: Option<u32>, limit: Option<u32>) -> ProtocolStats {
+        self.internal_get_protocol_stats(
+            from_index.unwrap_or(0),
+            limit.unwrap_or(DEFAULT_VIEW_LIMIT),
+        )
+    }
+
     fn get_validator_set(
         &self,
         consumer_chain_id: ConsumerChainId,
@@ -390,8 +629,61 @@ impl RestakingView for RestakingBaseContract {
         self.owner.clone()
     }
 
+    fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
+    fn get_timelock_delay(&self) -> DurationOfSeconds {
+        self.timelock_delay
+    }
+
+    fn get_pending_actions(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<PendingAction> {
+        self.pending_actions
+            .values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .collect_vec()
+    }
+
+    fn get_roles(&self, from_index: Option<u32>, limit: Option<u32>) -> Vec<AccountRoles> {
+        self.roles
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .map(|(account_id, roles)| AccountRoles { account_id, roles })
+            .collect_vec()
+    }
+
     fn is_contract_running(&self) -> bool {
-        self.is_contract_running
+        self.schema_version == CURRENT_SCHEMA_VERSION && !self.pause_matrix.is_any_paused()
+    }
+
+    fn get_pause_matrix(&self) -> PauseMatrix {
+        self.pause_matrix.clone()
+    }
+
+    fn get_state_migration_info(&self) -> StateMigrationInfo {
+        StateMigrationInfo {
+            schema_version: self.schema_version,
+            current_schema_version: CURRENT_SCHEMA_VERSION,
+            migration_index: U64(self.migration_index),
+        }
+    }
+
+    fn get_config(&self) -> ProtocolConfig {
+        self.config.clone()
+    }
+
+    fn get_protocol_fee_info(&self) -> ProtocolFeeInfo {
+        ProtocolFeeInfo {
+            protocol_fee: self.protocol_fee.clone(),
+            treasury_account: self.protocol_treasury_account.clone(),
+            accrued_fee: self.protocol_fee_balance.into(),
+        }
     }
 }
 
@@ -485,6 +777,7 @@ impl RestakingBaseContract {
             staking_pool.calculate_decrease_shares(min(staker_total_staked_balance, slash_amount));
         decrease_shares = min(decrease_shares, staker.shares);
         staker.shares = staker.shares - decrease_shares;
+        self.internal_decrease_bonded_shares(&staker, decrease_shares);
 
         staking_pool.decrease_stake(decrease_shares);
 
@@ -498,7 +791,9 @@ impl RestakingBaseContract {
             self.next_uuid().into(),
             pool_id,
             receive_amount,
-            env::epoch_height() + NUM_EPOCHS_TO_UNLOCK,
+            staking_pool
+                .get_unstake_batch_unlock_epoch(&unstake_batch_id, self.config.num_epochs_to_unlock)
+                .unwrap(),
             staker.get_unlock_time(),
             treasury_id.clone(),
             true,
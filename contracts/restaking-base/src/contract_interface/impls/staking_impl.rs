@@ -1,21 +1,50 @@
+use std::collections::HashMap;
+
 use near_sdk::env::current_account_id;
 
-use crate::{types::Sequence, *};
+use crate::{
+    constants::{
+        DEFAULT_VIEW_LIMIT, MAX_WITHDRAW_ALL_LIMIT, MAX_WITHDRAW_ALL_SCAN,
+        MIN_REWARDED_UNSTAKE_BATCH_AMOUNT,
+    },
+    types::Sequence,
+    *,
+};
 
 #[near_bindgen]
 impl StakerAction for RestakingBaseContract {
     #[payable]
-    fn stake(&mut self, pool_id: PoolId) -> PromiseOrValue<Option<StakingChangeResult>> {
-        self.assert_contract_is_running();
+    fn stake(
+        &mut self,
+        pool_id: PoolId,
+        staker_id: Option<StakerId>,
+    ) -> PromiseOrValue<Option<StakingChangeResult>> {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Stake);
         assert_attached_near();
 
-        let staker_id = env::predecessor_account_id();
+        // The funder only pays for staking, the shares are credited to staker.
+        let funder_id = env::predecessor_account_id();
+        let staker_id = staker_id.unwrap_or(funder_id.clone());
 
         assert!(
             self.accounts.get(&staker_id).is_some(),
             "Should register by storage_deposit first."
         );
 
+        // The staking pool is chosen by staker only, others can only fund the selected one.
+        if staker_id != funder_id {
+            let selected_pool_id = self.internal_get_staker_selected_pool_or_panic(&staker_id);
+            assert_eq!(
+                selected_pool_id, pool_id,
+                "Failed to stake for staker({}), it has selected another pool.",
+                staker_id
+            );
+            return self
+                .internal_increase_stake(staker_id, funder_id, env::attached_deposit())
+                .into();
+        }
+
         let staker = self
             .stakers
             .get(&staker_id)
@@ -29,22 +58,17 @@ impl StakerAction for RestakingBaseContract {
             staker.select_staking_pool
         );
 
+        let initial_storage_usage = env::storage_usage();
         self.internal_save_staker(&staker_id, &staker);
+        self.internal_charge_storage_usage(&staker_id, initial_storage_usage);
 
-        return ext_whitelist::ext(self.staking_pool_whitelist_account.clone())
-            .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_IS_WHITELISTED))
-            .is_whitelisted(pool_id.clone())
-            .then(
-                Self::ext(env::current_account_id())
-                    .with_attached_deposit(env::attached_deposit())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_SELECT_POOL_AFTER_CHECK_WHITELIST))
-                    .stake_after_check_whitelisted(staker_id.clone(), pool_id.clone()),
-            )
-            .into();
+        self.internal_stake(staker_id, pool_id, funder_id, env::attached_deposit())
+            .into()
     }
 
     fn ping(&mut self, pool_id: Option<PoolId>) -> Promise {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::BatchProcessing);
         let ping_pool_id = pool_id.unwrap_or_else(|| {
             self.stakers
                 .get(&env::predecessor_account_id())
@@ -53,7 +77,7 @@ impl StakerAction for RestakingBaseContract {
         });
 
         ext_staking_pool::ext(ping_pool_id.clone())
-            .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_PING))
+            .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.ping))
             .with_unused_gas_weight(0)
             .ping()
             .function_call(
@@ -62,35 +86,29 @@ impl StakerAction for RestakingBaseContract {
                     .to_string()
                     .into_bytes(),
                 NO_DEPOSIT,
-                Gas::ONE_TERA.mul(TGAS_FOR_GET_ACCOUNT_STAKED_BALANCE),
+                Gas::ONE_TERA.mul(self.config.gas.get_account_staked_balance),
             )
             .then(
                 Self::ext(env::current_account_id())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_PING_CALLBACK))
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.ping_callback))
                     .with_unused_gas_weight(0)
                     .ping_callback(ping_pool_id),
             )
     }
 
     #[payable]
-    fn increase_stake(&mut self) -> PromiseOrValue<Option<StakingChangeResult>> {
-        self.assert_contract_is_running();
+    fn increase_stake(
+        &mut self,
+        staker_id: Option<StakerId>,
+    ) -> PromiseOrValue<Option<StakingChangeResult>> {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Stake);
         assert_attached_near();
 
-        let staker_id = env::predecessor_account_id();
-        self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
-            staking_pool.lock()
-        });
-
-        return self
-            .ping(Option::None)
-            .then(
-                Self::ext(env::current_account_id())
-                    .with_attached_deposit(env::attached_deposit())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_INCREASE_STAKE_AFTER_PING))
-                    .increase_stake_after_ping(staker_id),
-            )
-            .into();
+        let funder_id = env::predecessor_account_id();
+        let staker_id = staker_id.unwrap_or(funder_id.clone());
+        self.internal_increase_stake(staker_id, funder_id, env::attached_deposit())
+            .into()
     }
 
     #[payable]
@@ -99,25 +117,34 @@ impl StakerAction for RestakingBaseContract {
         decrease_amount: U128,
         beneficiary: Option<AccountId>,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
-        self.assert_contract_is_running();
-        self.assert_attached_storage_fee();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Unstake);
         assert!(decrease_amount.0 > 0, "The decrease amount should gt 0");
 
         let staker_id = env::predecessor_account_id();
+        self.internal_assert_storage_prepaid(&staker_id);
 
+        let nonce = self.next_uuid();
         self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
-            staking_pool.lock()
+            staking_pool.assert_not_paused();
+            staking_pool.lock(
+                staker_id.clone(),
+                StakingPoolOperation::DecreaseStake,
+                nonce,
+            )
         });
 
         return self
             .ping(Option::None)
             .then(
                 Self::ext(env::current_account_id())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_DECREASE_STAKE_AFTER_PING))
+                    .with_attached_deposit(env::attached_deposit())
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.decrease_stake_after_ping()))
                     .decrease_stake_after_ping(
                         staker_id,
                         decrease_amount,
                         beneficiary.unwrap_or(env::predecessor_account_id()),
+                        nonce,
                     ),
             )
             .into();
@@ -129,36 +156,47 @@ impl StakerAction for RestakingBaseContract {
         beneficiary: Option<AccountId>,
         withdraw_by_anyone: Option<bool>,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
-        self.assert_contract_is_running();
-        self.assert_attached_storage_fee();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Unstake);
         log!("Prepaid gas: {:?}", env::prepaid_gas());
         let staker_id = env::predecessor_account_id();
+        self.internal_assert_storage_prepaid(&staker_id);
 
+        let nonce = self.next_uuid();
         self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
-            staking_pool.lock()
+            staking_pool.assert_not_paused();
+            staking_pool.lock(staker_id.clone(), StakingPoolOperation::Unstake, nonce)
         });
 
         return self
             .ping(Option::None)
             .then(
                 Self::ext(env::current_account_id())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_UNSTAKE_AFTER_PING))
+                    .with_attached_deposit(env::attached_deposit())
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.unstake_after_ping()))
                     .unstake_after_ping(
                         staker_id.clone(),
                         beneficiary.unwrap_or(staker_id.clone()),
                         withdraw_by_anyone.unwrap_or(true),
+                        nonce,
                     ),
             )
             .into();
     }
 
     fn withdraw_unstake_batch(&mut self, pool_id: PoolId, unstake_batch_id: UnstakeBatchId) {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::BatchProcessing);
+        let nonce = self.next_uuid();
         let submitted_unstake_batch =
             self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
                 assert!(staking_pool.is_unstake_batch_withdrawable(&unstake_batch_id));
 
-                staking_pool.lock();
+                staking_pool.lock(
+                    env::predecessor_account_id(),
+                    StakingPoolOperation::WithdrawUnstakeBatch { unstake_batch_id },
+                    nonce,
+                );
                 staking_pool
                     .submitted_unstake_batches
                     .get(&unstake_batch_id)
@@ -166,83 +204,202 @@ impl StakerAction for RestakingBaseContract {
             });
 
         ext_staking_pool::ext(pool_id.clone())
-            .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_WITHDRAW))
+            .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.withdraw))
             .with_unused_gas_weight(0)
             .withdraw(submitted_unstake_batch.total_unstake_amount.into())
             .then(
                 Self::ext(current_account_id())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_WITHDRAW_UNSTAKE_BATCH_CALLBACK))
+                    .with_static_gas(
+                        Gas::ONE_TERA.mul(self.config.gas.withdraw_unstake_batch_callback),
+                    )
                     .with_unused_gas_weight(0)
-                    .withdraw_unstake_batch_callback(pool_id.clone(), unstake_batch_id),
+                    .withdraw_unstake_batch_callback(
+                        pool_id.clone(),
+                        unstake_batch_id,
+                        env::predecessor_account_id(),
+                        nonce,
+                    ),
             );
     }
 
     fn submit_unstake_batch(&mut self, pool_id: PoolId) {
-        self.assert_contract_is_running();
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::BatchProcessing);
         let mut staking_pool = self.internal_get_staking_pool_or_panic(&pool_id);
+        staking_pool.assert_not_paused();
         assert!(staking_pool.is_able_submit_unstake_batch());
 
-        staking_pool.lock();
+        let nonce = self.next_uuid();
+        staking_pool.lock(
+            env::predecessor_account_id(),
+            StakingPoolOperation::SubmitUnstakeBatch,
+            nonce,
+        );
 
         self.internal_save_staking_pool(&staking_pool);
 
         ext_staking_pool::ext(pool_id.clone())
-            .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_UNSTAKE))
+            .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.unstake))
             .with_unused_gas_weight(0)
             .unstake(staking_pool.batched_unstake_amount.into())
             .then(
                 Self::ext(current_account_id())
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_UNSTAKE_BATCH_CALLBACK))
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.unstake_batch_callback))
                     .with_unused_gas_weight(0)
-                    .submit_unstake_batch_callback(pool_id),
+                    .submit_unstake_batch_callback(pool_id, env::predecessor_account_id(), nonce),
             );
     }
 
     fn withdraw(&mut self, staker: AccountId, id: WithdrawalCertificate) -> PromiseOrValue<U128> {
-        self.assert_contract_is_running();
-        let pending_withdrawal = self.internal_use_account(&staker, |account| {
-            account.pending_withdrawals.remove(&id).unwrap()
-        });
-        let mut staking_pool = self.internal_get_staking_pool_or_panic(&pending_withdrawal.pool_id);
-        assert!(
-            self.internal_is_withdrawable(&staking_pool, &pending_withdrawal),
-            "unlock timestamp:{}, current timestamp:{}, current epoch: {}",
-            pending_withdrawal.unlock_time,
-            env::block_timestamp(),
-            env::epoch_height(),
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Withdraw);
+        self.internal_withdraw(staker, id)
+    }
+
+    fn withdraw_all(&mut self, account_id: AccountId, limit: u32) -> U128 {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Withdraw);
+        let predecessor_account_id = env::predecessor_account_id();
+        let mut account = self.internal_get_account_or_panic(&account_id);
+
+        // Only pending withdrawals from a withdrawn unstake batch can be paid out directly,
+        // the others still need `withdraw` to fetch near from the staking pool.
+        // The scan is bounded, so that the gas doesn't grow with the pending withdrawals of account.
+        let limit = min(limit, MAX_WITHDRAW_ALL_LIMIT) as usize;
+        let mut staking_pools: HashMap<PoolId, StakingPool> = HashMap::new();
+        let mut withdrawable_pending_withdrawals = vec![];
+        for pending_withdrawal in account
+            .pending_withdrawals
+            .values()
+            .take(MAX_WITHDRAW_ALL_SCAN as usize)
+        {
+            if withdrawable_pending_withdrawals.len() == limit {
+                break;
+            }
+            if pending_withdrawal.unstake_batch_id.is_none()
+                || !(pending_withdrawal.allow_other_withdraw
+                    || predecessor_account_id.eq(&pending_withdrawal.beneficiary))
+            {
+                continue;
+            }
+            let staking_pool = staking_pools
+                .entry(pending_withdrawal.pool_id.clone())
+                .or_insert_with(|| {
+                    self.internal_get_staking_pool_or_panic(&pending_withdrawal.pool_id)
+                });
+            if self.internal_is_withdrawable(staking_pool, &pending_withdrawal) {
+                withdrawable_pending_withdrawals.push(pending_withdrawal);
+            }
+        }
+
+        // The amounts are claimed from unstake batches before transferring, as `withdraw` does,
+        // and the claims are rolled back by callback if the transfer is failed.
+        let initial_storage_usage = env::storage_usage();
+        let mut beneficiary_claims: BTreeMap<
+            AccountId,
+            Vec<(PendingWithdrawal, SubmittedUnstakeBatch)>,
+        > = BTreeMap::new();
+        for pending_withdrawal in withdrawable_pending_withdrawals {
+            account
+                .pending_withdrawals
+                .remove(&pending_withdrawal.withdrawal_certificate);
+            let claimed_unstake_batch = self.internal_use_staking_pool_or_panic(
+                &pending_withdrawal.pool_id,
+                |staking_pool| {
+                    staking_pool.withdraw_from_unstake_batch(
+                        pending_withdrawal.amount,
+                        pending_withdrawal.unstake_batch_id.unwrap(),
+                    )
+                },
+            );
+            beneficiary_claims
+                .entry(pending_withdrawal.beneficiary.clone())
+                .or_default()
+                .push((pending_withdrawal, claimed_unstake_batch));
+        }
+        self.internal_save_account(&account_id, &account);
+        // As `withdraw` does, the released storage is refunded since the claims are paid out,
+        // the storage taken back by a failed transfer is charged in callback.
+        self.internal_refund_released_storage(&account_id, initial_storage_usage);
+
+        let mut total_amount: Balance = 0;
+        for (beneficiary, claims) in beneficiary_claims {
+            let amount: Balance = claims
+                .iter()
+                .map(|(pending_withdrawal, _)| pending_withdrawal.amount)
+                .sum();
+            total_amount += amount;
+            Promise::new(beneficiary).transfer(amount).then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.withdraw_all_callback))
+                    .withdraw_all_callback(account_id.clone(), claims),
+            );
+        }
+
+        total_amount.into()
+    }
+
+    #[payable]
+    fn transfer_position(&mut self, new_staker_id: StakerId) -> PromiseOrValue<bool> {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::Stake);
+        assert_one_yocto();
+        let staker_id = env::predecessor_account_id();
+        assert_ne!(
+            staker_id, new_staker_id,
+            "Failed to transfer position to staker itself."
         );
+        let staker = self.internal_get_staker_or_panic(&staker_id);
+        let bonding_consumer_chain_ids = staker.bonding_consumer_chains.keys().collect_vec();
+        if let Some(reason) = self.internal_check_position_transferable(
+            &staker_id,
+            &new_staker_id,
+            &bonding_consumer_chain_ids,
+        ) {
+            panic!("Failed to transfer position, {}", reason);
+        }
+        // The storage is recorded in callback without checking, so it should be prepaid.
+        let storage_cost = self
+            .internal_get_account_or_panic(&staker_id)
+            .storage_cost();
+        let available_storage_balance = self
+            .internal_get_account_or_panic(&new_staker_id)
+            .available_storage_balance();
         assert!(
-            pending_withdrawal.allow_other_withdraw
-                || env::predecessor_account_id().eq(&pending_withdrawal.beneficiary)
+            available_storage_balance >= storage_cost,
+            "Failed to transfer position, the available storage balance({}) of {} is less than {}.",
+            available_storage_balance,
+            new_staker_id,
+            storage_cost
         );
 
-        if let Some(unstake_batch_id) = pending_withdrawal.unstake_batch_id {
-            let submitted_unstake_batch = staking_pool
-                .submitted_unstake_batches
-                .get(&unstake_batch_id)
-                .unwrap();
-            assert!(submitted_unstake_batch.is_withdrawn);
-            staking_pool.withdraw_from_unstake_batch(pending_withdrawal.amount, unstake_batch_id);
-            self.internal_save_staking_pool(&staking_pool);
-
-            self.transfer_near(pending_withdrawal.beneficiary, pending_withdrawal.amount);
-            Event::Withdraw {
-                withdrawal_certificate: &pending_withdrawal.withdrawal_certificate,
-            }
-            .emit();
-
-            PromiseOrValue::Value(pending_withdrawal.amount.into())
-        } else {
-            ext_staking_pool::ext(pending_withdrawal.pool_id.clone())
-                .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_WITHDRAW))
-                .withdraw(pending_withdrawal.amount.into())
-                .then(
-                    Self::ext(env::current_account_id())
-                        .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_SINGLE_WITHDRAW_CALLBACK))
-                        .withdraw_callback(staker, pending_withdrawal),
-                )
-                .into()
+        if bonding_consumer_chain_ids.is_empty() {
+            self.internal_transfer_position(&staker_id, &new_staker_id);
+            return PromiseOrValue::Value(true);
         }
+        // The position is transferred after all bonding consumer chains have transferred it.
+        bonding_consumer_chain_ids
+            .iter()
+            .map(|consumer_chain_id| {
+                ext_consumer_chain_pos::ext(
+                    self.internal_get_consumer_chain_or_panic(consumer_chain_id)
+                        .pos_account_id,
+                )
+                .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.transfer_position))
+                .transfer_position(staker_id.clone(), new_staker_id.clone())
+            })
+            .reduce(|promise, next_promise| promise.and(next_promise))
+            .unwrap()
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.transfer_position_callback))
+                    .transfer_position_callback(
+                        staker_id,
+                        new_staker_id,
+                        bonding_consumer_chain_ids,
+                    ),
+            )
+            .into()
     }
 }
 
@@ -252,16 +409,42 @@ impl StakeView for RestakingBaseContract {
         self.stakers.get(&staker_id).map(|e| (&e).into())
     }
 
-    fn get_pending_withdrawals(&self, account_id: AccountId) -> Vec<PendingWithdrawal> {
+    fn get_staker_overview(
+        &self,
+        staker_id: StakerId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Option<StakerOverview> {
+        self.stakers.get(&staker_id).map(|staker| {
+            self.internal_get_staker_overview(
+                &staker,
+                from_index.unwrap_or(0),
+                limit.unwrap_or(DEFAULT_VIEW_LIMIT),
+            )
+        })
+    }
+
+    fn get_pending_withdrawals(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<PendingWithdrawal> {
         let account = self.internal_get_account_or_panic(&account_id);
-        account.pending_withdrawals.values().collect_vec()
+        account
+            .pending_withdrawals
+            .values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .map(|pending_withdrawal| self.internal_refresh_unlock_epoch(pending_withdrawal))
+            .collect_vec()
     }
 
     fn get_staker_bonding_consumer_chains(
         &self,
         staker_id: StakerId,
-        skip: u32,
-        limit: u32,
+        from_index: Option<u32>,
+        limit: Option<u32>,
     ) -> Vec<ConsumerChainInfo> {
         self.stakers
             .get(&staker_id)
@@ -270,8 +453,8 @@ impl StakeView for RestakingBaseContract {
                     staker
                         .bonding_consumer_chains
                         .iter()
-                        .skip(skip as usize)
-                        .take(limit as usize)
+                        .skip(from_index.unwrap_or(0) as usize)
+                        .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
                         .map(|chain_id| self.consumer_chains.get(&chain_id.0).unwrap())
                         .map(ConsumerChainInfo::from)
                         .collect(),
@@ -284,8 +467,45 @@ impl StakeView for RestakingBaseContract {
         self.internal_get_staking_pool_or_panic(&pool_id).into()
     }
 
-    fn get_staking_pools(&self) -> Vec<StakingPoolInfo> {
-        self.staking_pools.values().map_into().collect_vec()
+    fn get_staking_pools(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<StakingPoolInfo> {
+        self.staking_pools
+            .values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .map_into()
+            .collect_vec()
+    }
+
+    fn get_staking_pool_stakers(
+        &self,
+        pool_id: PoolId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<AccountId> {
+        self.internal_get_staking_pool_or_panic(&pool_id)
+            .stakers
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .collect_vec()
+    }
+
+    fn get_staking_pool_unstake_batches(
+        &self,
+        pool_id: PoolId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<SubmittedUnstakeBatch> {
+        self.internal_get_staking_pool_or_panic(&pool_id)
+            .submitted_unstake_batches
+            .values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .collect_vec()
     }
 
     fn get_account_staked_balance(&self, account_id: AccountId) -> U128 {
@@ -311,6 +531,220 @@ impl StakeView for RestakingBaseContract {
 
         self.internal_is_withdrawable(&staking_pool, &pending_withdrawal)
     }
+
+    fn check_invariants(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+        state: Option<InvariantCheckState>,
+    ) -> InvariantReport {
+        self.internal_check_invariants(
+            from_index.unwrap_or(0),
+            limit.unwrap_or(DEFAULT_VIEW_LIMIT),
+            state.unwrap_or_default(),
+        )
+    }
+
+    fn get_pending_keeper_work(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<KeeperWork> {
+        self.staking_pools
+            .values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .filter_map(|staking_pool| staking_pool.get_keeper_work())
+            .collect_vec()
+    }
+
+    fn get_keeper_reward(&self) -> U128 {
+        self.keeper_reward.into()
+    }
+
+    fn get_keeper_fund(&self) -> U128 {
+        self.keeper_fund.into()
+    }
+
+    fn get_shortfall_pause_threshold(&self) -> u32 {
+        self.shortfall_pause_threshold_basis_points
+    }
+
+    fn get_failed_operation(&self, id: U64) -> Option<FailedOperation> {
+        self.failed_operations.get(&id.0)
+    }
+
+    fn get_failed_operations(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<FailedOperation> {
+        self.failed_operations
+            .values()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_VIEW_LIMIT) as usize)
+            .collect_vec()
+    }
+
+    fn get_liquidity_pool(&self) -> LiquidityPoolInfo {
+        (&self.liquidity_pool).into()
+    }
+
+    fn get_liquidity_pool_shares(&self, account_id: AccountId) -> U128 {
+        self.liquidity_pool.get_shares(&account_id).into()
+    }
+}
+
+#[near_bindgen]
+impl KeeperAction for RestakingBaseContract {
+    #[payable]
+    fn deposit_keeper_fund(&mut self) {
+        assert_attached_near();
+        let amount = env::attached_deposit();
+        self.keeper_fund += amount;
+        Event::DepositKeeperFund {
+            account_id: &env::predecessor_account_id(),
+            amount: &amount.into(),
+            keeper_fund: &self.keeper_fund.into(),
+        }
+        .emit();
+    }
+
+    /// Anyone can reconcile a staking pool, the staking pool is locked until the balances are compared.
+    fn reconcile_pool(&mut self, pool_id: PoolId) -> Promise {
+        self.assert_state_migrated();
+        self.assert_not_paused(PauseCategory::BatchProcessing);
+        let nonce = self.next_uuid();
+        self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+            staking_pool.lock(
+                env::predecessor_account_id(),
+                StakingPoolOperation::Reconcile,
+                nonce,
+            )
+        });
+        self.internal_reconcile_pool(pool_id, None, nonce)
+    }
+
+    /// Anyone can clear a stale lock, the staking pool is reconciled to finalize the unfinished operation.
+    fn clear_stale_lock(&mut self, pool_id: PoolId) -> Promise {
+        assert!(
+            self.internal_get_staking_pool_or_panic(&pool_id)
+                .is_lock_stale(),
+            "The lock of staking pool({}) is not stale.",
+            pool_id
+        );
+        self.internal_clear_lock(pool_id)
+    }
+
+    /// Anyone can check whether a staking pool is still whitelisted,
+    /// the staking pool is deprecated if it's removed from whitelist.
+    /// The stakers of deprecated pool can't increase stake, they exit by `unstake`
+    /// whose unstake batch is submitted without waiting, and then stake into another pool.
+    fn check_pool_whitelisted(&mut self, pool_id: PoolId) -> Promise {
+        self.internal_get_staking_pool_or_panic(&pool_id);
+        ext_whitelist::ext(self.staking_pool_whitelist_account.clone())
+            .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.is_whitelisted))
+            .is_whitelisted(pool_id.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(
+                        Gas::ONE_TERA.mul(self.config.gas.check_pool_whitelisted_callback),
+                    )
+                    .check_pool_whitelisted_callback(pool_id),
+            )
+    }
+
+    /// Anyone can retry a failed operation, the operation is replayed as it was requested.
+    fn retry_failed_operation(&mut self, id: U64) {
+        let failed_operation = self.internal_take_failed_operation_or_panic(id);
+        self.assert_state_migrated();
+        self.assert_not_paused(failed_operation.operation.pause_category());
+        Event::RetryFailedOperation {
+            failed_operation: &failed_operation,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+
+        match failed_operation.operation {
+            RetryableOperation::Stake {
+                staker_id,
+                pool_id,
+                funder_id,
+                amount,
+            } => {
+                let staker = self.internal_get_staker_or_panic(&staker_id);
+                assert_eq!(staker.shares, 0, "Can't stake, shares is not zero");
+                assert!(
+                    staker.select_staking_pool.is_none(),
+                    "Staker({}) have selected pool({:?}).",
+                    staker_id,
+                    staker.select_staking_pool
+                );
+                self.internal_stake(staker_id, pool_id, funder_id, amount.0);
+            }
+            RetryableOperation::IncreaseStake {
+                staker_id,
+                funder_id,
+                amount,
+            } => {
+                self.internal_increase_stake(staker_id, funder_id, amount.0);
+            }
+            RetryableOperation::SubmitUnstakeBatch { pool_id } => {
+                self.submit_unstake_batch(pool_id);
+            }
+            RetryableOperation::WithdrawUnstakeBatch {
+                pool_id,
+                unstake_batch_id,
+            } => {
+                self.withdraw_unstake_batch(pool_id, unstake_batch_id);
+            }
+        }
+    }
+
+    /// Anyone can abandon a failed operation, the held near is refunded to funder.
+    /// The unstake batch operations can only be abandoned after they're done by others.
+    fn abandon_failed_operation(&mut self, id: U64) {
+        let failed_operation = self.internal_take_failed_operation_or_panic(id);
+        match &failed_operation.operation {
+            RetryableOperation::Stake {
+                funder_id, amount, ..
+            }
+            | RetryableOperation::IncreaseStake {
+                funder_id, amount, ..
+            } => {
+                self.transfer_near(funder_id.clone(), amount.0);
+            }
+            RetryableOperation::SubmitUnstakeBatch { pool_id } => {
+                assert!(
+                    !self
+                        .internal_get_staking_pool_or_panic(pool_id)
+                        .is_able_submit_unstake_batch(),
+                    "The unstake batch of staking pool({}) is still submittable, retry it instead.",
+                    pool_id
+                );
+            }
+            RetryableOperation::WithdrawUnstakeBatch {
+                pool_id,
+                unstake_batch_id,
+            } => {
+                let is_in_flight = self
+                    .internal_get_staking_pool_or_panic(pool_id)
+                    .submitted_unstake_batches
+                    .get(unstake_batch_id)
+                    .is_some_and(|submitted_unstake_batch| !submitted_unstake_batch.is_withdrawn);
+                assert!(
+                    !is_in_flight,
+                    "The unstake batch({}) of staking pool({}) is not withdrawn, retry it instead.",
+                    unstake_batch_id.0, pool_id
+                );
+            }
+        }
+        Event::AbandonFailedOperation {
+            failed_operation: &failed_operation,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
 }
 
 #[near_bindgen]
@@ -332,32 +766,93 @@ impl StakingCallback for RestakingBaseContract {
                 PromiseOrValue::Value(pending_withdrawal.amount.into())
             }
             PromiseResult::Failed => {
+                // The pending withdrawal is rolled back, so it can be withdrawn again.
+                let initial_storage_usage = env::storage_usage();
                 self.internal_use_account(&account_id, |account| {
                     account.rollback_pending_withdrawals(&pending_withdrawal)
                 });
+                self.internal_record_storage_usage(&account_id, initial_storage_usage);
                 emit_callback_failed_event();
                 PromiseOrValue::Value(0.into())
             }
         }
     }
 
+    #[private]
+    fn withdraw_all_callback(
+        &mut self,
+        account_id: AccountId,
+        claims: Vec<(PendingWithdrawal, SubmittedUnstakeBatch)>,
+    ) -> U128 {
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {
+                let mut withdrawn_amount: Balance = 0;
+                for (pending_withdrawal, _) in &claims {
+                    withdrawn_amount += pending_withdrawal.amount;
+                    Event::Withdraw {
+                        withdrawal_certificate: &pending_withdrawal.withdrawal_certificate,
+                    }
+                    .emit();
+                }
+                withdrawn_amount.into()
+            }
+            PromiseResult::Failed => {
+                for (pending_withdrawal, claimed_unstake_batch) in &claims {
+                    self.internal_use_staking_pool_or_panic(
+                        &pending_withdrawal.pool_id,
+                        |staking_pool| {
+                            staking_pool.rollback_withdraw_from_unstake_batch(
+                                pending_withdrawal.amount,
+                                claimed_unstake_batch.clone(),
+                            )
+                        },
+                    );
+                }
+                let initial_storage_usage = env::storage_usage();
+                self.internal_use_account(&account_id, |account| {
+                    for (pending_withdrawal, _) in &claims {
+                        account.rollback_pending_withdrawals(pending_withdrawal);
+                    }
+                });
+                self.internal_record_storage_usage(&account_id, initial_storage_usage);
+                emit_callback_failed_event();
+                0.into()
+            }
+        }
+    }
+
+    #[payable]
     #[private]
     fn unstake_after_ping(
         &mut self,
         staker_id: AccountId,
         beneficiary: AccountId,
         withdraw_by_anyone: bool,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
+        if !self.internal_is_staker_pool_locked_by(&staker_id, nonce) {
+            if env::attached_deposit() > 0 {
+                self.transfer_near(staker_id, env::attached_deposit());
+            }
+            return PromiseOrValue::Value(None);
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Failed => {
                 self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
                     staking_pool.unlock();
                 });
+                // The storage deposit is optional since storage may be prepaid.
+                if env::attached_deposit() > 0 {
+                    self.transfer_near(staker_id, env::attached_deposit());
+                }
                 emit_callback_failed_event();
                 PromiseOrValue::Value(None)
             }
             PromiseResult::Successful(_) => {
+                self.internal_add_storage_balance(&staker_id, env::attached_deposit());
+                let initial_storage_usage = env::storage_usage();
                 let mut staker = self.internal_get_staker_or_panic(&staker_id);
                 let mut staking_pool =
                     self.internal_get_staking_pool_by_staker_or_panic(&staker_id);
@@ -375,7 +870,7 @@ impl StakingCallback for RestakingBaseContract {
                     &staker,
                     beneficiary,
                     receive_amount,
-                    staking_pool.pool_id.clone(),
+                    &staking_pool,
                     withdraw_by_anyone,
                     unstake_batch_id.clone(),
                 );
@@ -385,7 +880,13 @@ impl StakingCallback for RestakingBaseContract {
                 for consumer_chain_id in &staker_bonding_consumer_chains {
                     self.internal_use_consumer_chain_or_panic(
                         &consumer_chain_id,
-                        |consumer_chain| consumer_chain.unbond(&staker_id),
+                        |consumer_chain| {
+                            consumer_chain.unbond(
+                                &staker_id,
+                                &staking_pool.pool_id,
+                                decrease_shares,
+                            )
+                        },
                     );
                 }
 
@@ -393,6 +894,12 @@ impl StakingCallback for RestakingBaseContract {
 
                 self.internal_save_staker(&staker_id, &staker);
                 self.internal_save_staking_pool(&staking_pool);
+                self.internal_record_storage_usage(&staker_id, initial_storage_usage);
+                self.internal_refund_unused_storage_deposit(
+                    &staker_id,
+                    staker_id.clone(),
+                    env::attached_deposit(),
+                );
 
                 let sequence = U64(self.next_sequence());
 
@@ -418,23 +925,37 @@ impl StakingCallback for RestakingBaseContract {
         }
     }
 
+    #[payable]
     #[private]
     fn decrease_stake_after_ping(
         &mut self,
         staker_id: AccountId,
         decrease_amount: U128,
         beneficiary: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
+        if !self.internal_is_staker_pool_locked_by(&staker_id, nonce) {
+            if env::attached_deposit() > 0 {
+                self.transfer_near(staker_id, env::attached_deposit());
+            }
+            return PromiseOrValue::Value(None);
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Failed => {
                 self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
                     staking_pool.unlock();
                 });
+                // The storage deposit is optional since storage may be prepaid.
+                if env::attached_deposit() > 0 {
+                    self.transfer_near(staker_id, env::attached_deposit());
+                }
                 emit_callback_failed_event();
                 PromiseOrValue::Value(None)
             }
             PromiseResult::Successful(_) => {
+                self.internal_add_storage_balance(&staker_id, env::attached_deposit());
+                let initial_storage_usage = env::storage_usage();
                 let mut staker = self.internal_get_staker_or_panic(&staker_id);
                 let mut staking_pool =
                     self.internal_get_staking_pool_by_staker_or_panic(&staker_id);
@@ -448,6 +969,7 @@ impl StakingCallback for RestakingBaseContract {
                     .shares
                     .checked_sub(decrease_shares)
                     .expect("Failed decrease shares in staker.");
+                self.internal_decrease_bonded_shares(&staker, decrease_shares);
 
                 staking_pool.decrease_stake(decrease_shares);
 
@@ -457,13 +979,19 @@ impl StakingCallback for RestakingBaseContract {
                     &mut staker,
                     beneficiary,
                     receive_amount,
-                    staking_pool.pool_id.clone(),
+                    &staking_pool,
                     true,
                     unstake_batch_id.clone(),
                 );
 
                 self.internal_save_staker(&staker_id, &staker);
                 self.internal_save_staking_pool(&staking_pool);
+                self.internal_record_storage_usage(&staker_id, initial_storage_usage);
+                self.internal_refund_unused_storage_deposit(
+                    &staker_id,
+                    staker_id.clone(),
+                    env::attached_deposit(),
+                );
 
                 let sequence = U64::from(self.next_sequence());
                 Event::StakerDecreaseStake {
@@ -494,11 +1022,17 @@ impl StakingCallback for RestakingBaseContract {
         &mut self,
         staker_id: AccountId,
         pool_id: PoolId,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
+        // The funder is refunded when the lock is cleared, see `internal_finalize_stale_operation`.
+        if !self.internal_is_pool_locked_by(&pool_id, nonce) {
+            return PromiseOrValue::Value(None);
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => ext_staking_pool::ext(pool_id.clone())
-                .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_DEPOSIT_AND_STAKE))
+                .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.deposit_and_stake))
                 .with_attached_deposit(env::attached_deposit())
                 .deposit_and_stake()
                 .function_call(
@@ -507,19 +1041,30 @@ impl StakingCallback for RestakingBaseContract {
                         .to_string()
                         .into_bytes(),
                     0,
-                    Gas::ONE_TERA.mul(TGAS_FOR_GET_ACCOUNT_STAKED_BALANCE),
+                    Gas::ONE_TERA.mul(self.config.gas.get_account_staked_balance),
                 )
                 .then(
                     Self::ext(env::current_account_id())
-                        .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_INCREASE_STAKE_CALL_BACK))
-                        .stake_callback(staker_id, env::attached_deposit().into(), pool_id.clone()),
+                        .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.increase_stake_callback))
+                        .stake_callback(
+                            staker_id,
+                            env::attached_deposit().into(),
+                            pool_id.clone(),
+                            funder_id,
+                            nonce,
+                        ),
                 )
                 .into(),
             PromiseResult::Failed => {
-                self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
+                self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
                     staking_pool.unlock()
                 });
-                self.transfer_near(staker_id, env::attached_deposit());
+                self.internal_save_failed_operation(RetryableOperation::Stake {
+                    staker_id,
+                    pool_id,
+                    funder_id,
+                    amount: env::attached_deposit().into(),
+                });
                 emit_callback_failed_event();
                 return PromiseOrValue::Value(None);
             }
@@ -531,8 +1076,14 @@ impl StakingCallback for RestakingBaseContract {
     fn increase_stake_after_ping(
         &mut self,
         staker_id: AccountId,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
         log!("increase_stake_after_ping, gas: {:?}", env::prepaid_gas());
+        // The funder is refunded when the lock is cleared, see `internal_finalize_stale_operation`.
+        if !self.internal_is_staker_pool_locked_by(&staker_id, nonce) {
+            return PromiseOrValue::Value(None);
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => {
@@ -540,7 +1091,7 @@ impl StakingCallback for RestakingBaseContract {
                     self.internal_get_staker_selected_pool_or_panic(&staker_id);
 
                 ext_staking_pool::ext(pool_id)
-                    .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_DEPOSIT_AND_STAKE))
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.deposit_and_stake))
                     .with_attached_deposit(env::attached_deposit())
                     .deposit_and_stake()
                     .function_call(
@@ -549,12 +1100,19 @@ impl StakingCallback for RestakingBaseContract {
                             .to_string()
                             .into_bytes(),
                         0,
-                        Gas::ONE_TERA.mul(TGAS_FOR_GET_ACCOUNT_STAKED_BALANCE),
+                        Gas::ONE_TERA.mul(self.config.gas.get_account_staked_balance),
                     )
                     .then(
                         Self::ext(env::current_account_id())
-                            .with_static_gas(Gas::ONE_TERA.mul(TGAS_FOR_INCREASE_STAKE_CALL_BACK))
-                            .increase_stake_callback(staker_id, env::attached_deposit().into()),
+                            .with_static_gas(
+                                Gas::ONE_TERA.mul(self.config.gas.increase_stake_callback),
+                            )
+                            .increase_stake_callback(
+                                staker_id,
+                                env::attached_deposit().into(),
+                                funder_id,
+                                nonce,
+                            ),
                     )
                     .into()
             }
@@ -562,7 +1120,11 @@ impl StakingCallback for RestakingBaseContract {
                 self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
                     staking_pool.unlock()
                 });
-                self.transfer_near(staker_id, env::attached_deposit());
+                self.internal_save_failed_operation(RetryableOperation::IncreaseStake {
+                    staker_id,
+                    funder_id,
+                    amount: env::attached_deposit().into(),
+                });
                 emit_callback_failed_event();
                 return PromiseOrValue::Value(None);
             }
@@ -575,7 +1137,13 @@ impl StakingCallback for RestakingBaseContract {
         staker_id: AccountId,
         stake_amount: U128,
         pool_id: PoolId,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
+        // The stake is credited or refunded when the lock is cleared.
+        if !self.internal_is_pool_locked_by(&pool_id, nonce) {
+            return PromiseOrValue::Value(None);
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(value) => {
@@ -583,6 +1151,7 @@ impl StakingCallback for RestakingBaseContract {
                     .expect("Failed to deserialize in increase_stake_callback by value.")
                     .0;
 
+                let initial_storage_usage = env::storage_usage();
                 let mut staker = self.internal_get_staker_or_panic(&staker_id);
 
                 let sequence = U64(self.next_sequence());
@@ -601,6 +1170,7 @@ impl StakingCallback for RestakingBaseContract {
                             staker_info: &(&staker).into(),
                             select_pool: &staking_pool.pool_id,
                             stake_amount: &stake_amount,
+                            funder_id: &funder_id,
                             increase_shares: &increase_shares.into(),
                             sequence: &sequence,
                         }
@@ -608,6 +1178,7 @@ impl StakingCallback for RestakingBaseContract {
                         staking_pool.staked_amount_from_shares_balance_rounded_down(staker.shares)
                     });
                 self.internal_save_staker(&staker_id, &staker);
+                self.internal_record_storage_usage(&staker_id, initial_storage_usage);
 
                 return PromiseOrValue::Value(Some(StakingChangeResult {
                     sequence,
@@ -616,11 +1187,16 @@ impl StakingCallback for RestakingBaseContract {
                 }));
             }
             PromiseResult::Failed => {
-                self.internal_use_staker_staking_pool_or_panic(&staker_id, |pool| pool.unlock());
+                self.internal_use_staking_pool_or_panic(&pool_id, |pool| pool.unlock());
                 self.internal_use_staker_or_panic(&staker_id, |staker| {
                     staker.select_staking_pool = None
                 });
-                self.transfer_near(staker_id, stake_amount.0);
+                self.internal_save_failed_operation(RetryableOperation::Stake {
+                    staker_id,
+                    pool_id,
+                    funder_id,
+                    amount: stake_amount,
+                });
                 emit_callback_failed_event();
                 return PromiseOrValue::Value(None);
             }
@@ -632,8 +1208,14 @@ impl StakingCallback for RestakingBaseContract {
         &mut self,
         staker_id: AccountId,
         increase_amount: U128,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
         log!("increase_stake_callback, gas: {:?}", env::prepaid_gas());
+        // The stake is credited or refunded when the lock is cleared.
+        if !self.internal_is_staker_pool_locked_by(&staker_id, nonce) {
+            return PromiseOrValue::Value(None);
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(value) => {
@@ -653,6 +1235,7 @@ impl StakingCallback for RestakingBaseContract {
                     new_total_staked_balance,
                 );
                 staking_pool.unlock();
+                self.internal_increase_bonded_shares(&staker, increase_shares);
 
                 self.internal_save_staker(&staker_id, &staker);
                 self.internal_save_staking_pool(&staking_pool);
@@ -664,6 +1247,7 @@ impl StakingCallback for RestakingBaseContract {
                     staker_info: &(&staker).into(),
                     increase_stake_amount: &increase_amount,
                     increase_shares: &increase_shares.into(),
+                    funder_id: &funder_id,
                     sequence: &sequence,
                 }
                 .emit();
@@ -680,7 +1264,11 @@ impl StakingCallback for RestakingBaseContract {
                 self.internal_use_staker_staking_pool_or_panic(&staker_id, |staking_pool| {
                     staking_pool.unlock()
                 });
-                self.transfer_near(staker_id, increase_amount.0);
+                self.internal_save_failed_operation(RetryableOperation::IncreaseStake {
+                    staker_id,
+                    funder_id,
+                    amount: increase_amount,
+                });
                 emit_callback_failed_event();
                 return PromiseOrValue::Value(None);
             }
@@ -693,6 +1281,7 @@ impl StakingCallback for RestakingBaseContract {
         &mut self,
         staker_id: AccountId,
         pool_id: PoolId,
+        funder_id: AccountId,
     ) -> PromiseOrValue<Option<StakingChangeResult>> {
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
@@ -702,7 +1291,7 @@ impl StakingCallback for RestakingBaseContract {
 
                 if !whitelisted {
                     log!("Failed to select pool, {} is not whitelisted.", pool_id);
-                    self.transfer_near(staker_id, env::attached_deposit());
+                    self.transfer_near(funder_id, env::attached_deposit());
                     return PromiseOrValue::Value(None);
                 }
 
@@ -712,11 +1301,22 @@ impl StakingCallback for RestakingBaseContract {
                 }
 
                 let mut staking_pool = self.internal_get_staking_pool_or_panic(&pool_id);
-                if staking_pool.locked {
-                    self.transfer_near(staker_id, env::attached_deposit());
+                // The staking pool is whitelisted again.
+                staking_pool.deprecated = false;
+                if staking_pool.is_locked() || staking_pool.paused {
+                    self.internal_save_staking_pool(&staking_pool);
+                    self.transfer_near(funder_id, env::attached_deposit());
                     return PromiseOrValue::Value(None);
                 } else {
-                    staking_pool.lock();
+                    let nonce = self.next_uuid();
+                    staking_pool.lock(
+                        funder_id.clone(),
+                        StakingPoolOperation::Stake {
+                            staker_id: staker_id.clone(),
+                            amount: env::attached_deposit().into(),
+                        },
+                        nonce,
+                    );
                     self.internal_save_staking_pool(&staking_pool);
 
                     self.ping(Some(pool_id.clone()))
@@ -724,15 +1324,20 @@ impl StakingCallback for RestakingBaseContract {
                             Self::ext(env::current_account_id())
                                 .with_attached_deposit(env::attached_deposit())
                                 .with_static_gas(
-                                    Gas::ONE_TERA.mul(TGAS_FOR_INCREASE_STAKE_AFTER_PING),
+                                    Gas::ONE_TERA.mul(self.config.gas.increase_stake_after_ping()),
                                 )
-                                .stake_after_ping(staker_id, pool_id.clone()),
+                                .stake_after_ping(staker_id, pool_id.clone(), funder_id, nonce),
                         )
                         .into()
                 }
             }
             PromiseResult::Failed => {
-                self.transfer_near(staker_id, env::attached_deposit());
+                self.internal_save_failed_operation(RetryableOperation::Stake {
+                    staker_id,
+                    pool_id,
+                    funder_id,
+                    amount: env::attached_deposit().into(),
+                });
                 emit_callback_failed_event();
                 return PromiseOrValue::Value(None);
             }
@@ -740,13 +1345,17 @@ impl StakingCallback for RestakingBaseContract {
     }
 
     #[private]
-    fn submit_unstake_batch_callback(&mut self, pool_id: PoolId) {
+    fn submit_unstake_batch_callback(&mut self, pool_id: PoolId, keeper_id: AccountId, nonce: u64) {
+        if !self.internal_is_pool_locked_by(&pool_id, nonce) {
+            return;
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => {
                 let mut staking_pool = self.internal_get_staking_pool_or_panic(&pool_id);
 
-                let submitted_unstake_batch = staking_pool.submit_unstake();
+                let submitted_unstake_batch =
+                    staking_pool.submit_unstake(self.config.num_epochs_to_unlock);
                 staking_pool.unlock();
                 self.internal_save_staking_pool(&staking_pool);
 
@@ -755,11 +1364,19 @@ impl StakingCallback for RestakingBaseContract {
                     staking_pool: &staking_pool.into(),
                 }
                 .emit();
+                self.internal_reward_keeper(
+                    &keeper_id,
+                    &pool_id,
+                    submitted_unstake_batch.total_unstake_amount,
+                );
             }
             PromiseResult::Failed => {
                 self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
                     staking_pool.unlock();
                 });
+                self.internal_save_failed_operation(RetryableOperation::SubmitUnstakeBatch {
+                    pool_id,
+                });
                 emit_callback_failed_event();
             }
         }
@@ -770,15 +1387,24 @@ impl StakingCallback for RestakingBaseContract {
         &mut self,
         pool_id: PoolId,
         unstake_batch_id: UnstakeBatchId,
+        keeper_id: AccountId,
+        nonce: u64,
     ) {
+        if !self.internal_is_pool_locked_by(&pool_id, nonce) {
+            return;
+        }
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => {
                 let mut staking_pool = self.internal_get_staking_pool_or_panic(&pool_id);
-                staking_pool.last_unstake_batch_id = None;
                 staking_pool.withdraw_unstake_batch(&unstake_batch_id);
                 staking_pool.unlock();
                 self.internal_save_staking_pool(&staking_pool);
+                let unstake_amount = staking_pool
+                    .submitted_unstake_batches
+                    .get(&unstake_batch_id)
+                    .unwrap()
+                    .total_unstake_amount;
 
                 Event::WithdrawUnstakeBatch {
                     unstake_batch_id: &unstake_batch_id,
@@ -786,11 +1412,16 @@ impl StakingCallback for RestakingBaseContract {
                     epoch_height: &env::epoch_height().into(),
                 }
                 .emit();
+                self.internal_reward_keeper(&keeper_id, &pool_id, unstake_amount);
             }
             PromiseResult::Failed => {
                 self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
                     staking_pool.unlock();
                 });
+                self.internal_save_failed_operation(RetryableOperation::WithdrawUnstakeBatch {
+                    pool_id,
+                    unstake_batch_id,
+                });
                 emit_callback_failed_event();
             }
         }
@@ -818,27 +1449,603 @@ impl StakingCallback for RestakingBaseContract {
             }
         }
     }
-}
 
-impl RestakingBaseContract {
-    pub(crate) fn internal_create_pending_withdrawal_in_staker(
+    #[private]
+    fn reconcile_pool_callback(
         &mut self,
-        staker: &Staker,
-        beneficiary: AccountId,
-        amount: Balance,
         pool_id: PoolId,
-        allow_other_withdraw: bool,
-        unstake_batch_id: UnstakeBatchId,
-    ) -> PendingWithdrawal {
-        let pending_withdrawal = PendingWithdrawal::new(
-            self.next_uuid().into(),
-            pool_id,
-            amount,
-            env::epoch_height() + NUM_EPOCHS_TO_UNLOCK,
-            staker.get_unlock_time(),
-            beneficiary,
-            allow_other_withdraw,
-            unstake_batch_id,
+        stale_lock: Option<StakingPoolLock>,
+        nonce: u64,
+    ) -> Option<PoolReconciliation> {
+        if !self.internal_is_pool_locked_by(&pool_id, nonce) {
+            return None;
+        }
+        let balances = (0..env::promise_results_count())
+            .map(|index| match env::promise_result(index) {
+                PromiseResult::NotReady => unreachable!(),
+                PromiseResult::Successful(value) => Some(
+                    near_sdk::serde_json::from_slice::<U128>(&value)
+                        .expect("Failed to deserialize in reconcile_pool_callback by value.")
+                        .0,
+                ),
+                PromiseResult::Failed => None,
+            })
+            .collect::<Option<Vec<Balance>>>();
+
+        let mut staking_pool = self.internal_get_staking_pool_or_panic(&pool_id);
+        let (staked_balance, unstaked_balance, total_balance) = match balances.as_deref() {
+            Some(&[staked_balance, unstaked_balance, total_balance]) => {
+                (staked_balance, unstaked_balance, total_balance)
+            }
+            _ => {
+                // The stale lock is kept until its operation can be finalized.
+                staking_pool.lock = stale_lock;
+                self.internal_save_staking_pool(&staking_pool);
+                emit_callback_failed_event();
+                return None;
+            }
+        };
+        if let Some(stale_lock) = stale_lock {
+            let is_done =
+                staking_pool.is_stale_operation_done(&stale_lock, staked_balance, unstaked_balance);
+            if is_done.is_none() {
+                // The balances can't tell whether the operation is done,
+                // the stale lock is kept until it's resolved by pool operator.
+                Event::UnresolvedStaleOperation {
+                    pool_id: &pool_id,
+                    lock: &stale_lock,
+                    staked_balance: &staked_balance.into(),
+                    unstaked_balance: &unstaked_balance.into(),
+                }
+                .emit();
+                staking_pool.lock = Some(stale_lock);
+                self.internal_save_staking_pool(&staking_pool);
+                return None;
+            }
+            staking_pool.unlock();
+            self.internal_finalize_stale_operation(
+                &mut staking_pool,
+                &stale_lock,
+                is_done.unwrap(),
+                staked_balance,
+            );
+        } else {
+            staking_pool.unlock();
+        }
+
+        let was_paused = staking_pool.paused;
+        let pool_reconciliation = staking_pool.reconcile(
+            staked_balance,
+            unstaked_balance,
+            total_balance,
+            self.shortfall_pause_threshold_basis_points,
+        );
+        self.internal_save_staking_pool(&staking_pool);
+        Event::ReconcilePool {
+            pool_reconciliation: &pool_reconciliation,
+        }
+        .emit();
+        if !was_paused && pool_reconciliation.paused {
+            Event::PauseStakingPool {
+                pool_id: &pool_id,
+                total_shortfall: &pool_reconciliation.total_shortfall,
+            }
+            .emit();
+        }
+        Some(pool_reconciliation)
+    }
+
+    #[private]
+    fn transfer_position_callback(
+        &mut self,
+        staker_id: StakerId,
+        new_staker_id: StakerId,
+        consumer_chain_ids: Vec<ConsumerChainId>,
+    ) -> bool {
+        let transferred_consumer_chain_ids = consumer_chain_ids
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                matches!(
+                    env::promise_result(*index as u64),
+                    PromiseResult::Successful(_)
+                )
+            })
+            .map(|(_, consumer_chain_id)| consumer_chain_id.clone())
+            .collect_vec();
+        // The staker may have changed its position since the transfer is requested.
+        if transferred_consumer_chain_ids.len() == consumer_chain_ids.len()
+            && self
+                .internal_check_position_transferable(
+                    &staker_id,
+                    &new_staker_id,
+                    &consumer_chain_ids,
+                )
+                .is_none()
+        {
+            self.internal_transfer_position(&staker_id, &new_staker_id);
+            return true;
+        }
+
+        // Transfer the position back in the consumer chains which have transferred it.
+        for consumer_chain_id in transferred_consumer_chain_ids {
+            if let Some(consumer_chain) = self.consumer_chains.get(&consumer_chain_id) {
+                ext_consumer_chain_pos::ext(consumer_chain.pos_account_id)
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.transfer_position))
+                    .transfer_position(new_staker_id.clone(), staker_id.clone());
+            }
+        }
+        emit_callback_failed_event();
+        false
+    }
+
+    #[private]
+    fn check_pool_whitelisted_callback(&mut self, pool_id: PoolId) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(value) => {
+                let whitelisted = near_sdk::serde_json::from_slice::<bool>(&value)
+                    .expect("Failed to deserialize in check_pool_whitelisted_callback by value.");
+                let deprecated =
+                    self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+                        staking_pool.deprecated = !whitelisted;
+                        staking_pool.deprecated
+                    });
+                Event::CheckPoolWhitelisted {
+                    pool_id: &pool_id,
+                    is_whitelisted: &whitelisted,
+                    deprecated: &deprecated,
+                }
+                .emit();
+                whitelisted
+            }
+            PromiseResult::Failed => {
+                emit_callback_failed_event();
+                false
+            }
+        }
+    }
+}
+
+impl RestakingBaseContract {
+    /// Returns the reason if the position of staker can't be transferred to the new staker,
+    /// `consumer_chain_ids` are the consumer chains which the staker is expected to bond.
+    pub(crate) fn internal_check_position_transferable(
+        &self,
+        staker_id: &StakerId,
+        new_staker_id: &StakerId,
+        consumer_chain_ids: &[ConsumerChainId],
+    ) -> Option<String> {
+        let staker = match self.stakers.get(staker_id) {
+            Some(staker) => staker,
+            None => return Some(format!("the staker({}) doesn't exist.", staker_id)),
+        };
+        if !self.accounts.contains_key(new_staker_id) {
+            return Some(format!(
+                "the new staker({}) should register by storage_deposit first.",
+                new_staker_id
+            ));
+        }
+        if self
+            .stakers
+            .get(new_staker_id)
+            .is_some_and(|new_staker| new_staker.has_position())
+        {
+            return Some(format!(
+                "the new staker({}) already has position.",
+                new_staker_id
+            ));
+        }
+        if self
+            .staker_slashes
+            .get(staker_id)
+            .is_some_and(|slash_ids| !slash_ids.is_empty())
+        {
+            return Some(format!("the staker({}) has open slashes.", staker_id));
+        }
+        if staker.bonding_consumer_chains.keys().collect_vec() != consumer_chain_ids {
+            return Some(format!(
+                "the bonding consumer chains of staker({}) are changed.",
+                staker_id
+            ));
+        }
+        if let Some(pool_id) = &staker.select_staking_pool {
+            if self.internal_get_staking_pool_or_panic(pool_id).is_locked() {
+                return Some(format!("the staking pool({}) is locked.", pool_id));
+            }
+        }
+        consumer_chain_ids.iter().find_map(|consumer_chain_id| {
+            self.consumer_chains
+                .get(consumer_chain_id)
+                .filter(|consumer_chain| !consumer_chain.blacklist.contains(new_staker_id))
+                .is_none()
+                .then(|| {
+                    format!(
+                        "{} has been blacklisted by {} or it's not found.",
+                        new_staker_id, consumer_chain_id
+                    )
+                })
+        })
+    }
+
+    /// Move the position and pending withdrawals of staker to the new staker,
+    /// it should be checked by `internal_check_position_transferable` first.
+    pub(crate) fn internal_transfer_position(
+        &mut self,
+        staker_id: &StakerId,
+        new_staker_id: &StakerId,
+    ) {
+        let mut staker = self.internal_get_staker_or_panic(staker_id);
+        let mut new_staker = self
+            .stakers
+            .get(new_staker_id)
+            .unwrap_or(Staker::new(new_staker_id.clone()));
+
+        // The storage is released from old staker first, then recorded to new staker.
+        let initial_storage_usage = env::storage_usage();
+        if let Some(pool_id) = staker.select_staking_pool.clone() {
+            self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+                staking_pool.stakers.remove(staker_id);
+            });
+        }
+        let bonding_consumer_chain_ids = staker.bonding_consumer_chains.keys().collect_vec();
+        for consumer_chain_id in &bonding_consumer_chain_ids {
+            self.internal_use_consumer_chain_or_panic(consumer_chain_id, |consumer_chain| {
+                consumer_chain.bonding_stakers.remove(staker_id);
+            });
+        }
+        let position = staker.take_position();
+        staker.consumer_chain_operators.clear();
+        self.stakers.remove(staker_id);
+
+        // The pending withdrawals are moved as well, so that they still can be slashed.
+        let mut account = self.internal_get_account_or_panic(staker_id);
+        let pending_withdrawals = account.pending_withdrawals.values().collect_vec();
+        account.pending_withdrawals.clear();
+        self.internal_save_account(staker_id, &account);
+        self.internal_record_storage_usage(staker_id, initial_storage_usage);
+
+        let initial_storage_usage = env::storage_usage();
+        if let Some(pool_id) = position.select_staking_pool.clone() {
+            self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+                staking_pool.stakers.insert(new_staker_id);
+            });
+        }
+        for consumer_chain_id in &bonding_consumer_chain_ids {
+            self.internal_use_consumer_chain_or_panic(consumer_chain_id, |consumer_chain| {
+                consumer_chain.bonding_stakers.insert(new_staker_id);
+            });
+        }
+        new_staker.put_position(position);
+        self.internal_save_staker(new_staker_id, &new_staker);
+
+        let mut new_account = self.internal_get_account_or_panic(new_staker_id);
+        for mut pending_withdrawal in pending_withdrawals {
+            if pending_withdrawal.beneficiary == *staker_id {
+                pending_withdrawal.beneficiary = new_staker_id.clone();
+            }
+            new_account.pending_withdrawals.insert(
+                &pending_withdrawal.withdrawal_certificate,
+                &pending_withdrawal,
+            );
+        }
+        self.internal_save_account(new_staker_id, &new_account);
+        self.internal_record_storage_usage(new_staker_id, initial_storage_usage);
+
+        let sequence = U64(self.next_sequence());
+        Event::StakerTransferPosition {
+            staker_id,
+            new_staker_id,
+            staker_info: &(&new_staker).into(),
+            sequence: &sequence,
+        }
+        .emit();
+    }
+
+    /// Check whitelist of the staking pool and then stake the amount into it,
+    /// the staker should have been saved before calling it.
+    pub(crate) fn internal_stake(
+        &mut self,
+        staker_id: StakerId,
+        pool_id: PoolId,
+        funder_id: AccountId,
+        amount: Balance,
+    ) -> Promise {
+        ext_whitelist::ext(self.staking_pool_whitelist_account.clone())
+            .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.is_whitelisted))
+            .is_whitelisted(pool_id.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_attached_deposit(amount)
+                    .with_static_gas(
+                        Gas::ONE_TERA.mul(self.config.gas.select_pool_after_check_whitelist),
+                    )
+                    .stake_after_check_whitelisted(staker_id, pool_id, funder_id),
+            )
+    }
+
+    /// Lock the selected staking pool of staker, ping it and then increase stake by the amount.
+    pub(crate) fn internal_increase_stake(
+        &mut self,
+        staker_id: StakerId,
+        funder_id: AccountId,
+        amount: Balance,
+    ) -> Promise {
+        let pool_id = self.internal_get_staker_selected_pool_or_panic(&staker_id);
+        let nonce = self.next_uuid();
+        self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+            staking_pool.assert_not_paused();
+            staking_pool.assert_not_deprecated();
+            staking_pool.lock(
+                funder_id.clone(),
+                StakingPoolOperation::IncreaseStake {
+                    staker_id: staker_id.clone(),
+                    amount: amount.into(),
+                },
+                nonce,
+            )
+        });
+
+        self.ping(Some(pool_id)).then(
+            Self::ext(env::current_account_id())
+                .with_attached_deposit(amount)
+                .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.increase_stake_after_ping()))
+                .increase_stake_after_ping(staker_id, funder_id, nonce),
+        )
+    }
+
+    /// Withdraw the pending withdrawal, it's rolled back if the near is not paid out.
+    pub(crate) fn internal_withdraw(
+        &mut self,
+        staker: AccountId,
+        id: WithdrawalCertificate,
+    ) -> PromiseOrValue<U128> {
+        let initial_storage_usage = env::storage_usage();
+        let pending_withdrawal = self.internal_use_account(&staker, |account| {
+            account.pending_withdrawals.remove(&id).unwrap()
+        });
+        let mut staking_pool = self.internal_get_staking_pool_or_panic(&pending_withdrawal.pool_id);
+        assert!(
+            self.internal_is_withdrawable(&staking_pool, &pending_withdrawal),
+            "unlock timestamp:{}, current timestamp:{}, current epoch: {}",
+            pending_withdrawal.unlock_time,
+            env::block_timestamp(),
+            env::epoch_height(),
+        );
+        assert!(
+            pending_withdrawal.allow_other_withdraw
+                || env::predecessor_account_id().eq(&pending_withdrawal.beneficiary)
+        );
+
+        if let Some(unstake_batch_id) = pending_withdrawal.unstake_batch_id {
+            let submitted_unstake_batch = staking_pool
+                .submitted_unstake_batches
+                .get(&unstake_batch_id)
+                .unwrap();
+            assert!(submitted_unstake_batch.is_withdrawn);
+            staking_pool.withdraw_from_unstake_batch(pending_withdrawal.amount, unstake_batch_id);
+            self.internal_save_staking_pool(&staking_pool);
+            self.internal_refund_released_storage(&staker, initial_storage_usage);
+
+            self.transfer_near(pending_withdrawal.beneficiary, pending_withdrawal.amount);
+            Event::Withdraw {
+                withdrawal_certificate: &pending_withdrawal.withdrawal_certificate,
+            }
+            .emit();
+
+            PromiseOrValue::Value(pending_withdrawal.amount.into())
+        } else {
+            // The released storage may be taken back if withdraw is failed,
+            // so it's left in storage balance which can be withdrawn by `storage_withdraw`.
+            self.internal_record_storage_usage(&staker, initial_storage_usage);
+            ext_staking_pool::ext(pending_withdrawal.pool_id.clone())
+                .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.withdraw))
+                .withdraw(pending_withdrawal.amount.into())
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(
+                            Gas::ONE_TERA.mul(self.config.gas.single_withdraw_callback),
+                        )
+                        .withdraw_callback(staker, pending_withdrawal),
+                )
+                .into()
+        }
+    }
+
+    /// Compare the balances in staking pool contract with the tracked ones,
+    /// the staking pool should be locked before calling it.
+    pub(crate) fn internal_reconcile_pool(
+        &mut self,
+        pool_id: PoolId,
+        stale_lock: Option<StakingPoolLock>,
+        nonce: u64,
+    ) -> Promise {
+        let account_id = env::current_account_id();
+        ext_staking_pool::ext(pool_id.clone())
+            .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.get_account_staked_balance))
+            .get_account_staked_balance(account_id.clone())
+            .and(
+                ext_staking_pool::ext(pool_id.clone())
+                    .with_static_gas(
+                        Gas::ONE_TERA.mul(self.config.gas.get_account_unstaked_balance),
+                    )
+                    .get_account_unstaked_balance(account_id.clone()),
+            )
+            .and(
+                ext_staking_pool::ext(pool_id.clone())
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.get_account_total_balance))
+                    .get_account_total_balance(account_id),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::ONE_TERA.mul(self.config.gas.reconcile_pool_callback))
+                    .with_unused_gas_weight(0)
+                    .reconcile_pool_callback(pool_id, stale_lock, nonce),
+            )
+    }
+
+    /// Clear the lock of staking pool and reconcile it to finalize the unfinished operation,
+    /// the callbacks of the cleared lock are ignored since then.
+    pub(crate) fn internal_clear_lock(&mut self, pool_id: PoolId) -> Promise {
+        let nonce = self.next_uuid();
+        let stale_lock = self.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+            let stale_lock = staking_pool
+                .lock
+                .take()
+                .expect(format!("The staking pool({}) is not locked.", pool_id).as_str());
+            staking_pool.lock(
+                env::predecessor_account_id(),
+                StakingPoolOperation::Reconcile,
+                nonce,
+            );
+            stale_lock
+        });
+        Event::ForceUnlockStakingPool {
+            pool_id: &pool_id,
+            lock: &stale_lock,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+        self.internal_reconcile_pool(pool_id, Some(stale_lock), nonce)
+    }
+
+    /// Returns whether the staking pool is still locked by the operation of `nonce`,
+    /// the callbacks of the operation neither change state nor unlock if it returns false.
+    pub(crate) fn internal_is_pool_locked_by(&self, pool_id: &PoolId, nonce: u64) -> bool {
+        let is_locked = self
+            .staking_pools
+            .get(pool_id)
+            .is_some_and(|staking_pool| staking_pool.is_locked_by(nonce));
+        if !is_locked {
+            log!(
+                "The lock({}) of staking pool({}) has been cleared.",
+                nonce,
+                pool_id
+            );
+        }
+        is_locked
+    }
+
+    pub(crate) fn internal_is_staker_pool_locked_by(
+        &self,
+        staker_id: &StakerId,
+        nonce: u64,
+    ) -> bool {
+        self.stakers
+            .get(staker_id)
+            .and_then(|staker| staker.select_staking_pool)
+            .is_some_and(|pool_id| self.internal_is_pool_locked_by(&pool_id, nonce))
+    }
+
+    /// Finalize the operation whose callback is not executed, `is_done` is told by the balances
+    /// in staking pool contract or by pool operator. The stake is credited to staker if it's done,
+    /// otherwise it's held by this contract and refunded to funder. The unstake operations change
+    /// state in a single callback, so their state is rolled back when the callback fails.
+    pub(crate) fn internal_finalize_stale_operation(
+        &mut self,
+        staking_pool: &mut StakingPool,
+        stale_lock: &StakingPoolLock,
+        is_done: bool,
+        staked_balance: Balance,
+    ) {
+        match &stale_lock.operation {
+            StakingPoolOperation::Stake { staker_id, amount }
+            | StakingPoolOperation::IncreaseStake { staker_id, amount } => {
+                let funder_id = &stale_lock.owner;
+                if !is_done {
+                    self.transfer_near(funder_id.clone(), amount.0);
+                    return;
+                }
+                let initial_storage_usage = env::storage_usage();
+                let mut staker = self.internal_get_staker_or_panic(staker_id);
+                let is_new_stake =
+                    matches!(stale_lock.operation, StakingPoolOperation::Stake { .. });
+                let increase_shares = if is_new_stake {
+                    staking_pool.stake(&mut staker, amount.0, staked_balance)
+                } else {
+                    staking_pool.increase_stake(&mut staker, amount.0, staked_balance)
+                };
+                self.internal_increase_bonded_shares(&staker, increase_shares);
+                self.internal_save_staker(staker_id, &staker);
+                self.internal_record_storage_usage(staker_id, initial_storage_usage);
+
+                let sequence = U64(self.next_sequence());
+                if is_new_stake {
+                    Event::StakerStake {
+                        staking_pool_info: &(&mut *staking_pool).into(),
+                        staker_info: &(&staker).into(),
+                        select_pool: &staking_pool.pool_id,
+                        stake_amount: amount,
+                        funder_id,
+                        increase_shares: &increase_shares.into(),
+                        sequence: &sequence,
+                    }
+                    .emit();
+                } else {
+                    Event::StakerIncreaseStake {
+                        staking_pool_info: &(&mut *staking_pool).into(),
+                        staker_info: &(&staker).into(),
+                        increase_stake_amount: amount,
+                        increase_shares: &increase_shares.into(),
+                        funder_id,
+                        sequence: &sequence,
+                    }
+                    .emit();
+                }
+            }
+            StakingPoolOperation::SubmitUnstakeBatch
+                if is_done && staking_pool.batched_unstake_amount > 0 =>
+            {
+                let submitted_unstake_batch =
+                    staking_pool.submit_unstake(self.config.num_epochs_to_unlock);
+                Event::SubmitUnstakeBatch {
+                    submitted_unstake_batch: &submitted_unstake_batch,
+                    staking_pool: &(&mut *staking_pool).into(),
+                }
+                .emit();
+            }
+            StakingPoolOperation::WithdrawUnstakeBatch { unstake_batch_id } => {
+                let is_withdrawn = is_done
+                    && staking_pool
+                        .submitted_unstake_batches
+                        .get(unstake_batch_id)
+                        .is_some_and(|submitted_unstake_batch| {
+                            !submitted_unstake_batch.is_withdrawn
+                        });
+                if is_withdrawn {
+                    staking_pool.withdraw_unstake_batch(unstake_batch_id);
+                    Event::WithdrawUnstakeBatch {
+                        unstake_batch_id,
+                        pool_id: &staking_pool.pool_id,
+                        epoch_height: &env::epoch_height().into(),
+                    }
+                    .emit();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn internal_create_pending_withdrawal_in_staker(
+        &mut self,
+        staker: &Staker,
+        beneficiary: AccountId,
+        amount: Balance,
+        staking_pool: &StakingPool,
+        allow_other_withdraw: bool,
+        unstake_batch_id: UnstakeBatchId,
+    ) -> PendingWithdrawal {
+        let pending_withdrawal = PendingWithdrawal::new(
+            self.next_uuid().into(),
+            staking_pool.pool_id.clone(),
+            amount,
+            staking_pool
+                .get_unstake_batch_unlock_epoch(&unstake_batch_id, self.config.num_epochs_to_unlock)
+                .unwrap(),
+            staker.get_unlock_time(),
+            beneficiary,
+            allow_other_withdraw,
+            unstake_batch_id,
         );
 
         self.internal_use_account(&staker.staker_id, |account| {
@@ -851,6 +2058,28 @@ impl RestakingBaseContract {
         pending_withdrawal
     }
 
+    /// Pay keeper reward from keeper fund for processing an unstake batch of `unstake_amount`,
+    /// the reward is limited by the remaining fund, and tiny batches are not rewarded.
+    pub(crate) fn internal_reward_keeper(
+        &mut self,
+        keeper_id: &AccountId,
+        pool_id: &PoolId,
+        unstake_amount: Balance,
+    ) {
+        let reward = min(self.keeper_reward, self.keeper_fund);
+        if reward == 0 || unstake_amount < MIN_REWARDED_UNSTAKE_BATCH_AMOUNT {
+            return;
+        }
+        self.keeper_fund -= reward;
+        self.transfer_near(keeper_id.clone(), reward);
+        Event::RewardKeeper {
+            keeper_id,
+            pool_id,
+            reward: &reward.into(),
+        }
+        .emit();
+    }
+
     pub(crate) fn internal_get_staker_selected_pool_or_panic(
         &self,
         account_id: &AccountId,
@@ -861,3 +2090,466 @@ impl RestakingBaseContract {
             .expect(format!("The staker({}) haven't select pool!", account_id).as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::STAKING_POOL_LOCK_EXPIRY_BLOCKS;
+    use crate::contract_interface::owner::OwnerAction;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, BlockHeight, RuntimeFeesConfig, VMConfig, ONE_NEAR};
+
+    fn set_context(predecessor_account_id: AccountId, attached_deposit: Balance) {
+        set_context_with_promise_results(predecessor_account_id, attached_deposit, 0, vec![]);
+    }
+
+    fn set_context_with_promise_results(
+        predecessor_account_id: AccountId,
+        attached_deposit: Balance,
+        block_height: BlockHeight,
+        promise_results: Vec<PromiseResult>,
+    ) {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(accounts(0))
+                .predecessor_account_id(predecessor_account_id)
+                .attached_deposit(attached_deposit)
+                .block_index(block_height)
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            promise_results,
+        );
+    }
+
+    fn setup_contract() -> RestakingBaseContract {
+        set_context(accounts(0), 0);
+        RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into())
+    }
+
+    fn balance_result(balance: Balance) -> PromiseResult {
+        PromiseResult::Successful(json!(U128(balance)).to_string().into_bytes())
+    }
+
+    /// The staker(accounts(2)) has 1000 shares of 1000 staked near in the staking pool,
+    /// which is locked by accounts(3) increasing stake of 100 near for the staker at block 0.
+    fn setup_pool_locked_by_increase_stake(contract: &mut RestakingBaseContract) -> PoolId {
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let staker_id = accounts(2);
+        let mut staker = Staker::new(staker_id.clone());
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.stake(&mut staker, 1000, 1000);
+        staking_pool.lock(
+            accounts(3),
+            StakingPoolOperation::IncreaseStake {
+                staker_id: staker_id.clone(),
+                amount: 100.into(),
+            },
+            contract.next_uuid(),
+        );
+        contract.internal_save_staking_pool(&staking_pool);
+        contract.internal_save_staker(&staker_id, &staker);
+        contract.internal_save_account(&staker_id, &Account::new(staker_id.clone()));
+        pool_id
+    }
+
+    /// Clear the expired lock by accounts(4), returns the stale lock and the nonce of reconciling.
+    fn clear_expired_lock(
+        contract: &mut RestakingBaseContract,
+        pool_id: &PoolId,
+    ) -> (StakingPoolLock, u64) {
+        let stale_lock = contract
+            .internal_get_staking_pool_or_panic(pool_id)
+            .lock
+            .unwrap();
+        set_context_with_promise_results(
+            accounts(4),
+            0,
+            stale_lock.expire_block_height + 1,
+            vec![],
+        );
+        contract.clear_stale_lock(pool_id.clone());
+        let lock = contract
+            .internal_get_staking_pool_or_panic(pool_id)
+            .lock
+            .unwrap();
+        assert!(matches!(lock.operation, StakingPoolOperation::Reconcile));
+        assert_eq!(lock.owner, accounts(4));
+        assert_ne!(lock.nonce, stale_lock.nonce);
+        (stale_lock, lock.nonce)
+    }
+
+    #[test]
+    #[should_panic(expected = "is not stale")]
+    fn test_clear_unexpired_lock() {
+        let mut contract = setup_contract();
+        let pool_id = setup_pool_locked_by_increase_stake(&mut contract);
+        set_context_with_promise_results(accounts(4), 0, STAKING_POOL_LOCK_EXPIRY_BLOCKS, vec![]);
+        contract.clear_stale_lock(pool_id);
+    }
+
+    #[test]
+    fn test_clear_expired_lock_credits_stake() {
+        let mut contract = setup_contract();
+        let pool_id = setup_pool_locked_by_increase_stake(&mut contract);
+        let (stale_lock, nonce) = clear_expired_lock(&mut contract, &pool_id);
+
+        // The callback of the cleared lock is ignored.
+        set_context_with_promise_results(accounts(0), 0, 300, vec![balance_result(1100)]);
+        contract.increase_stake_callback(accounts(2), 100.into(), accounts(3), stale_lock.nonce);
+        assert_eq!(
+            contract.internal_get_staker_or_panic(&accounts(2)).shares,
+            1000
+        );
+        assert!(contract
+            .internal_get_staking_pool_or_panic(&pool_id)
+            .is_locked_by(nonce));
+
+        // The stake is found in staked balance, so it's credited to staker.
+        set_context_with_promise_results(
+            accounts(0),
+            0,
+            300,
+            vec![
+                balance_result(1100),
+                balance_result(0),
+                balance_result(1100),
+            ],
+        );
+        let pool_reconciliation = contract
+            .reconcile_pool_callback(pool_id.clone(), Some(stale_lock), nonce)
+            .unwrap();
+        assert_eq!(pool_reconciliation.total_shortfall.0, 0);
+        assert_eq!(
+            contract.internal_get_staker_or_panic(&accounts(2)).shares,
+            1100
+        );
+        let staking_pool = contract.internal_get_staking_pool_or_panic(&pool_id);
+        assert!(!staking_pool.is_locked());
+        assert_eq!(staking_pool.total_share_balance, 1100);
+        assert_eq!(staking_pool.total_staked_balance, 1100);
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn test_clear_expired_lock_refunds_stake() {
+        let mut contract = setup_contract();
+        let pool_id = setup_pool_locked_by_increase_stake(&mut contract);
+        let (stale_lock, nonce) = clear_expired_lock(&mut contract, &pool_id);
+
+        // The stake is not found in staked balance, so it's refunded to funder.
+        set_context_with_promise_results(
+            accounts(0),
+            0,
+            300,
+            vec![
+                balance_result(1000),
+                balance_result(0),
+                balance_result(1000),
+            ],
+        );
+        contract.reconcile_pool_callback(pool_id.clone(), Some(stale_lock), nonce);
+        assert_eq!(
+            contract.internal_get_staker_or_panic(&accounts(2)).shares,
+            1000
+        );
+        assert!(!contract
+            .internal_get_staking_pool_or_panic(&pool_id)
+            .is_locked());
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(3));
+        assert_eq!(
+            receipts[0].actions,
+            vec![VmAction::Transfer { deposit: 100 }]
+        );
+    }
+
+    #[test]
+    fn test_clear_expired_lock_credits_rounded_stake() {
+        let mut contract = setup_contract();
+        let pool_id = setup_pool_locked_by_increase_stake(&mut contract);
+        let (stale_lock, nonce) = clear_expired_lock(&mut contract, &pool_id);
+
+        // The staked balance is a few yocto less than expected by rounding of staking pool.
+        set_context_with_promise_results(
+            accounts(0),
+            0,
+            300,
+            vec![
+                balance_result(1097),
+                balance_result(0),
+                balance_result(1097),
+            ],
+        );
+        contract.reconcile_pool_callback(pool_id.clone(), Some(stale_lock), nonce);
+        assert!(contract.internal_get_staker_or_panic(&accounts(2)).shares > 1000);
+        let staking_pool = contract.internal_get_staking_pool_or_panic(&pool_id);
+        assert!(!staking_pool.is_locked());
+        assert_eq!(staking_pool.total_staked_balance, 1097);
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn test_clear_expired_lock_kept_when_balance_inflated_by_rewards() {
+        let mut contract = setup_contract();
+        let pool_id = setup_pool_locked_by_increase_stake(&mut contract);
+        let (stale_lock, nonce) = clear_expired_lock(&mut contract, &pool_id);
+
+        // The staked balance is inflated by rewards, so the stake can't be told from it.
+        set_context_with_promise_results(
+            accounts(0),
+            0,
+            300,
+            vec![
+                balance_result(1150),
+                balance_result(0),
+                balance_result(1150),
+            ],
+        );
+        assert!(contract
+            .reconcile_pool_callback(pool_id.clone(), Some(stale_lock.clone()), nonce)
+            .is_none());
+        assert_eq!(
+            contract.internal_get_staker_or_panic(&accounts(2)).shares,
+            1000
+        );
+        assert!(contract
+            .internal_get_staking_pool_or_panic(&pool_id)
+            .is_locked_by(stale_lock.nonce));
+        assert!(get_created_receipts().is_empty());
+
+        // The pool operator resolves it as not staked, so it's refunded to funder.
+        set_context_with_promise_results(accounts(0), 1, 300, vec![]);
+        contract.resolve_stale_operation(pool_id.clone(), false);
+        assert_eq!(
+            contract.internal_get_staker_or_panic(&accounts(2)).shares,
+            1000
+        );
+        assert!(!contract
+            .internal_get_staking_pool_or_panic(&pool_id)
+            .is_locked());
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(3));
+        assert_eq!(
+            receipts[0].actions,
+            vec![VmAction::Transfer { deposit: 100 }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is not stale")]
+    fn test_resolve_unexpired_lock() {
+        let mut contract = setup_contract();
+        let pool_id = setup_pool_locked_by_increase_stake(&mut contract);
+        set_context_with_promise_results(accounts(0), 1, STAKING_POOL_LOCK_EXPIRY_BLOCKS, vec![]);
+        contract.resolve_stale_operation(pool_id, true);
+    }
+
+    #[test]
+    fn test_whitelisted_again_when_pool_locked() {
+        let mut contract = setup_contract();
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.deprecated = true;
+        staking_pool.lock(accounts(3), StakingPoolOperation::Unstake, 1);
+        contract.internal_save_staking_pool(&staking_pool);
+
+        set_context_with_promise_results(
+            accounts(0),
+            100,
+            0,
+            vec![PromiseResult::Successful(b"true".to_vec())],
+        );
+        let result =
+            contract.stake_after_check_whitelisted(accounts(2), pool_id.clone(), accounts(2));
+        assert!(matches!(result, PromiseOrValue::Value(None)));
+        let staking_pool = contract.internal_get_staking_pool_or_panic(&pool_id);
+        assert!(!staking_pool.deprecated);
+        assert!(staking_pool.is_locked());
+    }
+
+    fn save_failed_operation(
+        contract: &mut RestakingBaseContract,
+        operation: RetryableOperation,
+    ) -> U64 {
+        contract.internal_save_failed_operation(operation);
+        contract.get_failed_operations(None, None)[0].id
+    }
+
+    #[test]
+    fn test_failed_withdraw_is_rolled_back_only() {
+        let mut contract = setup_contract();
+        let staker_id = accounts(2);
+        contract.internal_save_account(&staker_id, &Account::new(staker_id.clone()));
+        let pending_withdrawal = PendingWithdrawal::new(
+            1.into(),
+            "pool.near".parse().unwrap(),
+            100,
+            0,
+            0,
+            staker_id.clone(),
+            false,
+            0.into(),
+        );
+
+        set_context_with_promise_results(accounts(0), 0, 0, vec![PromiseResult::Failed]);
+        let result = contract.withdraw_callback(staker_id.clone(), pending_withdrawal.clone());
+        assert!(matches!(result, PromiseOrValue::Value(U128(0))));
+        assert!(contract
+            .internal_get_account_or_panic(&staker_id)
+            .pending_withdrawals
+            .get(&pending_withdrawal.withdrawal_certificate)
+            .is_some());
+        assert!(contract.failed_operations.is_empty());
+    }
+
+    #[test]
+    fn test_withdraw_all_is_limited_and_refunds_storage() {
+        let mut contract = setup_contract();
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let staker_id = accounts(2);
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.total_staked_balance = 300;
+        staking_pool.batch_unstake(300);
+        let submitted_unstake_batch = staking_pool.submit_unstake(4);
+        staking_pool.withdraw_unstake_batch(&submitted_unstake_batch.unstake_batch_id);
+        contract.internal_save_staking_pool(&staking_pool);
+
+        let mut account = Account::new(staker_id.clone());
+        for id in 0..30 {
+            account.pending_withdrawals.insert(
+                &id.into(),
+                &PendingWithdrawal::new(
+                    id.into(),
+                    pool_id.clone(),
+                    10,
+                    0,
+                    0,
+                    staker_id.clone(),
+                    false,
+                    submitted_unstake_batch.unstake_batch_id,
+                ),
+            );
+        }
+        account.storage_balance = ONE_NEAR;
+        account.storage_usage = 100_000;
+        contract.internal_save_account(&staker_id, &account);
+
+        set_context(staker_id.clone(), 0);
+        let withdrawn = contract.withdraw_all(staker_id.clone(), 100);
+        assert_eq!(withdrawn.0, 10 * MAX_WITHDRAW_ALL_LIMIT as Balance);
+        let account = contract.internal_get_account_or_panic(&staker_id);
+        assert_eq!(
+            account.pending_withdrawals.len(),
+            30 - MAX_WITHDRAW_ALL_LIMIT as u64
+        );
+        // The storage released by the paid out pending withdrawals is refunded.
+        assert!(account.storage_usage < 100_000);
+        assert!(account.storage_balance < ONE_NEAR);
+    }
+
+    #[test]
+    fn test_pending_withdrawal_keeps_unlock_epoch() {
+        let mut contract = setup_contract();
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let staker_id = accounts(2);
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.total_staked_balance = 100;
+        staking_pool.batch_unstake(100);
+        let submitted_unstake_batch = staking_pool.submit_unstake(4);
+        contract.internal_save_staking_pool(&staking_pool);
+        let pending_withdrawal = PendingWithdrawal::new(
+            1.into(),
+            pool_id,
+            100,
+            1,
+            0,
+            staker_id.clone(),
+            false,
+            submitted_unstake_batch.unstake_batch_id,
+        );
+        let mut account = Account::new(staker_id.clone());
+        account
+            .pending_withdrawals
+            .insert(&1.into(), &pending_withdrawal);
+        contract.internal_save_account(&staker_id, &account);
+
+        // The unlock epoch is kept in callback args.
+        let json = near_sdk::serde_json::to_string(&pending_withdrawal).unwrap();
+        let decoded: PendingWithdrawal = near_sdk::serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.unlock_epoch, 1);
+
+        // The unlock epoch in views is derived from the submitted unstake batch.
+        let pending_withdrawals = contract.get_pending_withdrawals(staker_id, None, None);
+        assert_eq!(pending_withdrawals[0].unlock_epoch, 4);
+    }
+
+    #[test]
+    fn test_abandon_failed_stake_by_anyone() {
+        let mut contract = setup_contract();
+        let id = save_failed_operation(
+            &mut contract,
+            RetryableOperation::IncreaseStake {
+                staker_id: accounts(2),
+                funder_id: accounts(3),
+                amount: 100.into(),
+            },
+        );
+
+        set_context(accounts(4), 0);
+        contract.abandon_failed_operation(id);
+        assert!(contract.failed_operations.is_empty());
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(3));
+        assert_eq!(
+            receipts[0].actions,
+            vec![VmAction::Transfer { deposit: 100 }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is still submittable, retry it instead")]
+    fn test_abandon_submittable_unstake_batch() {
+        let mut contract = setup_contract();
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.batch_unstake(100);
+        contract.internal_save_staking_pool(&staking_pool);
+        let id = save_failed_operation(
+            &mut contract,
+            RetryableOperation::SubmitUnstakeBatch { pool_id },
+        );
+
+        set_context(accounts(4), 0);
+        contract.abandon_failed_operation(id);
+    }
+
+    #[test]
+    fn test_abandon_withdrawn_unstake_batch() {
+        let mut contract = setup_contract();
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.total_staked_balance = 100;
+        staking_pool.batch_unstake(100);
+        let submitted_unstake_batch = staking_pool.submit_unstake(4);
+        staking_pool.withdraw_unstake_batch(&submitted_unstake_batch.unstake_batch_id);
+        contract.internal_save_staking_pool(&staking_pool);
+        let id = save_failed_operation(
+            &mut contract,
+            RetryableOperation::WithdrawUnstakeBatch {
+                pool_id,
+                unstake_batch_id: submitted_unstake_batch.unstake_batch_id,
+            },
+        );
+
+        set_context(accounts(4), 0);
+        contract.abandon_failed_operation(id);
+        assert!(contract.failed_operations.is_empty());
+        assert!(get_created_receipts().is_empty());
+    }
+}
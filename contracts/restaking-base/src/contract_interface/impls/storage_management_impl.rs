@@ -2,28 +2,37 @@ use near_contract_standards::storage_management::{
     StorageBalance, StorageBalanceBounds, StorageManagement,
 };
 
-use crate::{constants::REGISTER_STORAGE_FEE, *};
+use crate::*;
 
 #[near_bindgen]
 impl StorageManagement for RestakingBaseContract {
-    /// Only support register account, if account exist, the deposit near will be refund
+    /// If account exist, the deposit near will be added to its storage balance,
+    /// unless `registration_only` is true, then the deposit near will be refund.
     #[payable]
     fn storage_deposit(
         &mut self,
         account_id: Option<AccountId>,
-        #[allow(unused)] registration_only: Option<bool>,
+        registration_only: Option<bool>,
     ) -> StorageBalance {
         let account_id = account_id.unwrap_or(env::predecessor_account_id());
         let exist = self.accounts.contains_key(&account_id);
         if exist {
-            self.transfer_near(env::predecessor_account_id(), env::attached_deposit())
+            if registration_only.unwrap_or(false) {
+                self.transfer_near(env::predecessor_account_id(), env::attached_deposit())
+            } else {
+                self.internal_add_storage_balance(&account_id, env::attached_deposit());
+            }
         } else {
-            assert!(env::attached_deposit() >= REGISTER_STORAGE_FEE);
-            self.internal_save_account(&account_id, &Account::new(account_id.clone()));
-            if env::attached_deposit() > REGISTER_STORAGE_FEE {
+            assert!(env::attached_deposit() >= self.config.register_storage_fee);
+            let initial_storage_usage = env::storage_usage();
+            let mut account = Account::new(account_id.clone());
+            account.storage_balance = self.config.register_storage_fee;
+            self.internal_save_account(&account_id, &account);
+            self.internal_charge_storage_usage(&account_id, initial_storage_usage);
+            if env::attached_deposit() > self.config.register_storage_fee {
                 self.transfer_near(
                     env::predecessor_account_id(),
-                    env::attached_deposit() - REGISTER_STORAGE_FEE,
+                    env::attached_deposit() - self.config.register_storage_fee,
                 )
             }
         }
@@ -31,27 +40,88 @@ impl StorageManagement for RestakingBaseContract {
         self.storage_balance_of(account_id).unwrap()
     }
 
+    /// Withdraw the available storage balance, including the storage released by actions
+    /// which may take it back in callback, such as `withdraw` from staking pool.
     #[payable]
-    fn storage_withdraw(&mut self, #[allow(unused)] amount: Option<U128>) -> StorageBalance {
-        unreachable!()
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let withdraw_amount = self.internal_use_account(&account_id, |account| {
+            let available = account.available_storage_balance();
+            let withdraw_amount = amount.map(|e| e.0).unwrap_or(available);
+            assert!(
+                withdraw_amount <= available,
+                "Failed to withdraw storage, the amount({}) is greater than available balance({}).",
+                withdraw_amount,
+                available
+            );
+            account.storage_balance -= withdraw_amount;
+            withdraw_amount
+        });
+        if withdraw_amount > 0 {
+            self.transfer_near(account_id.clone(), withdraw_amount);
+        }
+        self.storage_balance_of(account_id).unwrap()
     }
 
+    /// Unregister is allowed only if account has no shares, bonds or pending withdrawals.
     #[payable]
-    fn storage_unregister(&mut self, #[allow(unused)] force: Option<bool>) -> bool {
-        return false;
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        assert!(
+            !force.unwrap_or(false),
+            "Force unregister is not supported."
+        );
+        let account_id = env::predecessor_account_id();
+        let mut account = match self.accounts.get(&account_id) {
+            Some(account) => account,
+            None => return false,
+        };
+        assert!(
+            account.pending_withdrawals.is_empty(),
+            "Failed to unregister, {} still has pending withdrawals.",
+            account_id
+        );
+        assert_eq!(
+            self.liquidity_pool.get_shares(&account_id),
+            0,
+            "Failed to unregister, {} still has shares in liquidity pool.",
+            account_id
+        );
+
+        if let Some(mut staker) = self.stakers.get(&account_id) {
+            assert!(
+                !staker.has_position(),
+                "Failed to unregister, {} still has shares or bonds.",
+                account_id
+            );
+            staker.unbonding_consumer_chains.clear();
+            staker.consumer_chain_operators.clear();
+            self.stakers.remove(&account_id);
+        }
+        account.pending_withdrawals.clear();
+        self.accounts.remove(&account_id);
+        self.pending_withdrawal_accounts.remove(&account_id);
+
+        if account.storage_balance > 0 {
+            self.transfer_near(account_id, account.storage_balance);
+        }
+        true
     }
 
     fn storage_balance_bounds(&self) -> StorageBalanceBounds {
         return StorageBalanceBounds {
-            min: REGISTER_STORAGE_FEE.into(),
+            min: self.config.register_storage_fee.into(),
             max: Option::None,
         };
     }
 
-    fn storage_balance_of(&self, #[allow(unused)] account_id: AccountId) -> Option<StorageBalance> {
-        Some(StorageBalance {
-            total: REGISTER_STORAGE_FEE.into(),
-            available: 0.into(),
-        })
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.accounts
+            .get(&account_id)
+            .map(|account| StorageBalance {
+                total: account.storage_balance.into(),
+                available: account.available_storage_balance().into(),
+            })
     }
 }
@@ -0,0 +1,15 @@
+use crate::*;
+
+pub trait LiquidityPoolAction {
+    /// Provide the attached near as liquidity, returns the increased shares.
+    fn add_liquidity(&mut self) -> U128;
+    /// Remove liquidity by shares, returns the near sent back.
+    fn remove_liquidity(&mut self, shares: U128) -> U128;
+    /// Swap the pending withdrawal for immediate near, the liquidity pool takes over it.
+    fn instant_withdraw(&mut self, id: WithdrawalCertificate) -> U128;
+    /// Settle the withdrawable pending withdrawals taken over by liquidity pool, returns the settled amount.
+    /// A pending withdrawal is settled only after its unstake batch is withdrawn from staking pool
+    /// by `withdraw_unstake_batch`, before that it's skipped. The limit is capped by
+    /// `MAX_SETTLE_LIQUIDITY_POOL_LIMIT`.
+    fn settle_liquidity_pool(&mut self, limit: u32) -> U128;
+}
@@ -4,6 +4,7 @@ use crate::*;
 use near_sdk::json_types::U128;
 
 pub mod impls;
+pub mod liquidity_pool;
 pub mod owner;
 pub mod restaking;
 pub mod staking;
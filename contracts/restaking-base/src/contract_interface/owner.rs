@@ -1,17 +1,48 @@
 use crate::*;
 
 pub trait OwnerAction {
+    /// Propose the new owner, the ownership is transferred after the new owner accepts it.
     fn set_new_owner(&mut self, new_owner: AccountId);
+    fn accept_ownership(&mut self);
+    fn cancel_ownership_transfer(&mut self);
+    fn grant_role(&mut self, account_id: AccountId, role: Role);
+    fn revoke_role(&mut self, account_id: AccountId, role: Role);
     fn set_cc_register_fee(&mut self, new_cc_register_fee: U128);
+    /// Schedule to set the slash guarantee after timelock delay.
     fn set_slash_guarantee(&mut self, new_slash_guarantee: U128);
+    fn set_keeper_reward(&mut self, new_keeper_reward: U128);
+    fn set_liquidity_pool_fee(&mut self, fee_basis_points: u32);
     fn set_contract_running(&mut self);
     fn set_contract_pause(&mut self);
+    fn set_paused(&mut self, category: PauseCategory, paused: bool);
+    /// Schedule to set the beneficiary of pending withdrawal after timelock delay.
     fn set_withdrawal_beneficiary(
         &mut self,
         account_id: AccountId,
         withdraw_certificate: WithdrawalCertificate,
         new_beneficiary: AccountId,
     );
+    /// Schedule to unlock the unbonding of staker after timelock delay.
     fn set_staker_unbonding_unlock_time_as_current_time(&mut self, staker_id: AccountId);
-    fn set_staking_pool_unlock(&mut self, pool_id: PoolId);
+    /// Clear the lock of staking pool without waiting for expiry,
+    /// the staking pool is reconciled to finalize the unfinished operation as `clear_stale_lock`.
+    fn set_staking_pool_unlock(&mut self, pool_id: PoolId) -> Promise;
+    /// Finalize the operation of stale lock whose outcome can't be told by the balances in
+    /// staking pool contract, `is_done` is checked by pool operator from the receipts of the operation.
+    fn resolve_stale_operation(&mut self, pool_id: PoolId, is_done: bool);
+    fn set_shortfall_pause_threshold(&mut self, basis_points: u32);
+    fn resume_staking_pool(&mut self, pool_id: PoolId);
+    /// Schedule to set the timelock delay after the current timelock delay.
+    fn set_timelock_delay(&mut self, delay_seconds: DurationOfSeconds);
+    /// Anyone can execute the pending action after its timelock delay passed,
+    /// the action is dropped if its target has gone during the delay.
+    fn execute_pending_action(&mut self, id: U64);
+    fn cancel_pending_action(&mut self, id: U64);
+    /// Replace the protocol config, it's validated before taking effect.
+    fn update_config(&mut self, config: ProtocolConfig);
+    fn set_protocol_fee(&mut self, protocol_fee: ProtocolFee);
+    fn set_protocol_treasury_account(&mut self, account_id: AccountId);
+    /// Called by protocol treasury account to withdraw the accrued protocol fee,
+    /// all of it is withdrawn if `amount` is not given.
+    fn withdraw_protocol_fee(&mut self, amount: Option<U128>) -> U128;
 }
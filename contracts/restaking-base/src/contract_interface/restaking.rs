@@ -13,7 +13,15 @@ pub trait GovernanceAction {
         update_param: ConsumerChainUpdateParam,
     );
 
+    fn accept_consumer_chain_governance(&mut self, consumer_chain_id: ConsumerChainId);
+
+    fn cancel_consumer_chain_governance_transfer(&mut self, consumer_chain_id: ConsumerChainId);
+
     fn slash(&mut self, consumer_chain_id: ConsumerChainId, slash_id: SlashId, is_approve: bool);
+
+    /// Reject the slash request by consumer chain governance or slash arbiter,
+    /// the slash guarantee is refunded to consumer chain pos account.
+    fn reject_slash(&mut self, consumer_chain_id: ConsumerChainId, slash_id: SlashId);
 }
 
 pub trait ConsumerChainAction {
@@ -24,12 +32,33 @@ pub trait ConsumerChainAction {
         slash_items: Vec<(AccountId, U128)>,
         evidence_sha256_hash: String,
     ) -> SlashId;
+    /// Pay the attached near to the staker bonding in consumer chain as reward,
+    /// the protocol fee is cut from it. Returns the reward paid to the staker.
+    fn deposit_reward(&mut self, consumer_chain_id: ConsumerChainId, staker_id: StakerId) -> U128;
 }
 
 pub trait StakerRestakingAction {
-    fn change_key(&mut self, consumer_chain_id: ConsumerChainId, new_key: String);
-    fn bond(&mut self, consumer_chain_id: ConsumerChainId, key: String) -> PromiseOrValue<bool>;
-    fn unbond(&mut self, consumer_chain_id: ConsumerChainId);
+    fn change_key(
+        &mut self,
+        consumer_chain_id: ConsumerChainId,
+        new_key: String,
+        staker_id: Option<StakerId>,
+    );
+    fn bond(
+        &mut self,
+        consumer_chain_id: ConsumerChainId,
+        key: String,
+        staker_id: Option<StakerId>,
+    ) -> PromiseOrValue<bool>;
+    fn unbond(&mut self, consumer_chain_id: ConsumerChainId, staker_id: Option<StakerId>);
+    /// Authorize operator to bond, change key and unbond for predecessor,
+    /// it'll be authorized in all consumer chains if consumer_chain_id is None.
+    fn authorize_operator(
+        &mut self,
+        operator_id: AccountId,
+        consumer_chain_id: Option<ConsumerChainId>,
+    );
+    fn revoke_operator(&mut self, consumer_chain_id: Option<ConsumerChainId>);
 }
 
 pub trait RestakingCallback {
@@ -38,6 +67,7 @@ pub trait RestakingCallback {
         consumer_chain_id: ConsumerChainId,
         key: String,
         staker_id: AccountId,
+        payer_id: AccountId,
     ) -> PromiseOrValue<bool>;
 
     fn change_key_callback(
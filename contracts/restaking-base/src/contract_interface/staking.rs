@@ -2,8 +2,15 @@ use crate::*;
 
 pub trait StakerAction {
     fn ping(&mut self, pool_id: Option<PoolId>) -> Promise;
-    fn stake(&mut self, pool_id: PoolId) -> PromiseOrValue<Option<StakingChangeResult>>;
-    fn increase_stake(&mut self) -> PromiseOrValue<Option<StakingChangeResult>>;
+    fn stake(
+        &mut self,
+        pool_id: PoolId,
+        staker_id: Option<StakerId>,
+    ) -> PromiseOrValue<Option<StakingChangeResult>>;
+    fn increase_stake(
+        &mut self,
+        staker_id: Option<StakerId>,
+    ) -> PromiseOrValue<Option<StakingChangeResult>>;
     fn decrease_stake(
         &mut self,
         decrease_amount: U128,
@@ -17,6 +24,17 @@ pub trait StakerAction {
     fn withdraw_unstake_batch(&mut self, pool_id: PoolId, unstake_batch_id: UnstakeBatchId);
     fn submit_unstake_batch(&mut self, pool_id: PoolId);
     fn withdraw(&mut self, staker: AccountId, id: WithdrawalCertificate) -> PromiseOrValue<U128>;
+    fn withdraw_all(&mut self, account_id: AccountId, limit: u32) -> U128;
+    fn transfer_position(&mut self, new_staker_id: StakerId) -> PromiseOrValue<bool>;
+}
+
+pub trait KeeperAction {
+    fn deposit_keeper_fund(&mut self);
+    fn reconcile_pool(&mut self, pool_id: PoolId) -> Promise;
+    fn check_pool_whitelisted(&mut self, pool_id: PoolId) -> Promise;
+    fn clear_stale_lock(&mut self, pool_id: PoolId) -> Promise;
+    fn retry_failed_operation(&mut self, id: U64);
+    fn abandon_failed_operation(&mut self, id: U64);
 }
 
 pub trait StakingCallback {
@@ -24,17 +42,22 @@ pub trait StakingCallback {
         &mut self,
         staker_id: AccountId,
         pool_id: PoolId,
+        funder_id: AccountId,
     ) -> PromiseOrValue<Option<StakingChangeResult>>;
 
     fn stake_after_ping(
         &mut self,
         staker_id: AccountId,
         pool_id: PoolId,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>>;
 
     fn increase_stake_after_ping(
         &mut self,
         staker_id: AccountId,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>>;
 
     fn stake_callback(
@@ -42,12 +65,16 @@ pub trait StakingCallback {
         staker_id: AccountId,
         stake_amount: U128,
         pool_id: PoolId,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>>;
 
     fn increase_stake_callback(
         &mut self,
         staker_id: AccountId,
         increase_amount: U128,
+        funder_id: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>>;
 
     fn decrease_stake_after_ping(
@@ -55,6 +82,7 @@ pub trait StakingCallback {
         staker_id: AccountId,
         decrease_amount: U128,
         beneficiary: AccountId,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>>;
 
     fn unstake_after_ping(
@@ -62,6 +90,7 @@ pub trait StakingCallback {
         staker_id: AccountId,
         beneficiary: AccountId,
         withdraw_by_anyone: bool,
+        nonce: u64,
     ) -> PromiseOrValue<Option<StakingChangeResult>>;
 
     fn withdraw_callback(
@@ -70,13 +99,37 @@ pub trait StakingCallback {
         pending_withdrawal: PendingWithdrawal,
     ) -> PromiseOrValue<U128>;
 
-    fn submit_unstake_batch_callback(&mut self, pool_id: PoolId);
+    fn withdraw_all_callback(
+        &mut self,
+        account_id: AccountId,
+        claims: Vec<(PendingWithdrawal, SubmittedUnstakeBatch)>,
+    ) -> U128;
+
+    fn submit_unstake_batch_callback(&mut self, pool_id: PoolId, keeper_id: AccountId, nonce: u64);
 
     fn withdraw_unstake_batch_callback(
         &mut self,
         pool_id: PoolId,
         unstake_batch_id: UnstakeBatchId,
+        keeper_id: AccountId,
+        nonce: u64,
     );
 
     fn ping_callback(&mut self, pool_id: PoolId);
+
+    fn reconcile_pool_callback(
+        &mut self,
+        pool_id: PoolId,
+        stale_lock: Option<StakingPoolLock>,
+        nonce: u64,
+    ) -> Option<PoolReconciliation>;
+
+    fn check_pool_whitelisted_callback(&mut self, pool_id: PoolId) -> bool;
+
+    fn transfer_position_callback(
+        &mut self,
+        staker_id: StakerId,
+        new_staker_id: StakerId,
+        consumer_chain_ids: Vec<ConsumerChainId>,
+    ) -> bool;
 }
@@ -2,18 +2,49 @@ use crate::*;
 pub trait StakeView {
     fn get_staker(&self, staker_id: StakerId) -> Option<StakerInfo>;
 
-    fn get_pending_withdrawals(&self, account_id: AccountId) -> Vec<PendingWithdrawal>;
+    /// The pending withdrawals in overview are paginated by `from_index` and `limit`.
+    fn get_staker_overview(
+        &self,
+        staker_id: StakerId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Option<StakerOverview>;
+
+    fn get_pending_withdrawals(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<PendingWithdrawal>;
 
     fn get_staker_bonding_consumer_chains(
         &self,
         staker_id: StakerId,
-        skip: u32,
-        limit: u32,
+        from_index: Option<u32>,
+        limit: Option<u32>,
     ) -> Vec<ConsumerChainInfo>;
 
     fn get_staking_pool(&self, pool_id: PoolId) -> StakingPoolDetail;
 
-    fn get_staking_pools(&self) -> Vec<StakingPoolInfo>;
+    fn get_staking_pools(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<StakingPoolInfo>;
+
+    fn get_staking_pool_stakers(
+        &self,
+        pool_id: PoolId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<AccountId>;
+
+    fn get_staking_pool_unstake_batches(
+        &self,
+        pool_id: PoolId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<SubmittedUnstakeBatch>;
 
     fn get_account_staked_balance(&self, account_id: AccountId) -> U128;
 
@@ -22,12 +53,55 @@ pub trait StakeView {
     fn get_current_epoch_height(&self) -> U64;
 
     fn is_withdrawable(&self, staker_id: StakerId, certificate: WithdrawalCertificate) -> bool;
+
+    fn check_invariants(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+        state: Option<InvariantCheckState>,
+    ) -> InvariantReport;
+
+    fn get_pending_keeper_work(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<KeeperWork>;
+
+    fn get_keeper_reward(&self) -> U128;
+
+    fn get_keeper_fund(&self) -> U128;
+
+    fn get_shortfall_pause_threshold(&self) -> u32;
+
+    fn get_failed_operation(&self, id: U64) -> Option<FailedOperation>;
+
+    fn get_failed_operations(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<FailedOperation>;
+
+    fn get_liquidity_pool(&self) -> LiquidityPoolInfo;
+
+    fn get_liquidity_pool_shares(&self, account_id: AccountId) -> U128;
 }
 
 pub trait RestakingView {
     fn get_consumer_chain(&self, consumer_chain_id: ConsumerChainId) -> Option<ConsumerChainInfo>;
 
-    fn get_consumer_chains(&self) -> Vec<ConsumerChainInfo>;
+    fn get_consumer_chains(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<ConsumerChainInfo>;
+
+    fn get_consumer_chain_stats(
+        &self,
+        consumer_chain_id: ConsumerChainId,
+    ) -> Option<ConsumerChainStats>;
+
+    /// The staking pools and consumer chains are paginated by `from_index` and `limit`.
+    fn get_protocol_stats(&self, from_index: Option<u32>, limit: Option<u32>) -> ProtocolStats;
 
     fn get_validator_set(
         &self,
@@ -41,5 +115,28 @@ pub trait RestakingView {
 
     fn get_owner(&self) -> AccountId;
 
+    fn get_pending_owner(&self) -> Option<AccountId>;
+
+    fn get_timelock_delay(&self) -> DurationOfSeconds;
+
+    fn get_pending_actions(
+        &self,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<PendingAction>;
+
+    fn get_roles(&self, from_index: Option<u32>, limit: Option<u32>) -> Vec<AccountRoles>;
+
+    /// Returns false if any category is paused or the state is being migrated.
     fn is_contract_running(&self) -> bool;
+
+    /// The paused categories, the state migration which refuses all operations
+    /// is shown by `get_state_migration_info` instead.
+    fn get_pause_matrix(&self) -> PauseMatrix;
+
+    fn get_state_migration_info(&self) -> StateMigrationInfo;
+
+    fn get_config(&self) -> ProtocolConfig;
+
+    fn get_protocol_fee_info(&self) -> ProtocolFeeInfo;
 }
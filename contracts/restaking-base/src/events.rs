@@ -28,6 +28,7 @@ pub enum Event<'a> {
         stake_amount: &'a U128,
         increase_shares: &'a U128,
         sequence: &'a U64,
+        funder_id: &'a AccountId,
     },
 
     StakerIncreaseStake {
@@ -36,6 +37,7 @@ pub enum Event<'a> {
         increase_stake_amount: &'a U128,
         increase_shares: &'a U128,
         sequence: &'a U64,
+        funder_id: &'a AccountId,
     },
 
     StakerDecreaseStake {
@@ -58,6 +60,13 @@ pub enum Event<'a> {
         unstake_batch_id: &'a UnstakeBatchId,
     },
 
+    StakerTransferPosition {
+        staker_id: &'a StakerId,
+        new_staker_id: &'a StakerId,
+        staker_info: &'a StakerInfo,
+        sequence: &'a U64,
+    },
+
     StakerBond {
         staker_id: &'a StakerId,
         consumer_chain_id: &'a ConsumerChainId,
@@ -75,6 +84,18 @@ pub enum Event<'a> {
         consumer_chain_id: &'a ConsumerChainId,
     },
 
+    AuthorizeOperator {
+        staker_id: &'a StakerId,
+        operator_id: &'a AccountId,
+        consumer_chain_id: &'a Option<ConsumerChainId>,
+    },
+
+    RevokeOperator {
+        staker_id: &'a StakerId,
+        operator_id: &'a AccountId,
+        consumer_chain_id: &'a Option<ConsumerChainId>,
+    },
+
     RegisterConsumerChain {
         consumer_chain_info: &'a ConsumerChainInfo,
         consumer_chain_register_param: &'a ConsumerChainRegisterParam,
@@ -83,6 +104,21 @@ pub enum Event<'a> {
         consumer_chain_info: &'a ConsumerChainInfo,
         consumer_chain_update_param: &'a ConsumerChainUpdateParam,
     },
+    ProposeConsumerChainGovernance {
+        consumer_chain_id: &'a ConsumerChainId,
+        governance: &'a AccountId,
+        pending_governance: &'a AccountId,
+    },
+    AcceptConsumerChainGovernance {
+        consumer_chain_id: &'a ConsumerChainId,
+        old_governance: &'a AccountId,
+        new_governance: &'a AccountId,
+    },
+    CancelConsumerChainGovernanceTransfer {
+        consumer_chain_id: &'a ConsumerChainId,
+        governance: &'a AccountId,
+        pending_governance: &'a AccountId,
+    },
     DeregisterConsumerChain {
         consumer_chain_info: &'a ConsumerChainInfo,
     },
@@ -91,6 +127,17 @@ pub enum Event<'a> {
         slash_items: &'a String,
         evidence_sha256_hash: &'a String,
     },
+    RejectSlash {
+        consumer_chain_id: &'a ConsumerChainId,
+        slash_id: &'a SlashId,
+        operator: &'a AccountId,
+    },
+    DepositReward {
+        consumer_chain_id: &'a ConsumerChainId,
+        staker_id: &'a StakerId,
+        amount: &'a U128,
+        protocol_fee: &'a U128,
+    },
     Withdraw {
         withdrawal_certificate: &'a WithdrawalCertificate,
     },
@@ -107,6 +154,151 @@ pub enum Event<'a> {
         submitted_unstake_batch: &'a SubmittedUnstakeBatch,
         staking_pool: &'a StakingPoolInfo,
     },
+    DepositKeeperFund {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+        keeper_fund: &'a U128,
+    },
+    RewardKeeper {
+        keeper_id: &'a AccountId,
+        pool_id: &'a PoolId,
+        reward: &'a U128,
+    },
+    ReconcilePool {
+        pool_reconciliation: &'a PoolReconciliation,
+    },
+    PauseStakingPool {
+        pool_id: &'a PoolId,
+        total_shortfall: &'a U128,
+    },
+    ResumeStakingPool {
+        pool_id: &'a PoolId,
+    },
+    SaveFailedOperation {
+        failed_operation: &'a FailedOperation,
+    },
+    RetryFailedOperation {
+        failed_operation: &'a FailedOperation,
+        operator: &'a AccountId,
+    },
+    AbandonFailedOperation {
+        failed_operation: &'a FailedOperation,
+        operator: &'a AccountId,
+    },
+    ProposeOwner {
+        owner: &'a AccountId,
+        pending_owner: &'a AccountId,
+    },
+    AcceptOwnership {
+        old_owner: &'a AccountId,
+        new_owner: &'a AccountId,
+    },
+    CancelOwnershipTransfer {
+        owner: &'a AccountId,
+        pending_owner: &'a AccountId,
+    },
+    ScheduleAction {
+        pending_action: &'a PendingAction,
+    },
+    ExecuteAction {
+        pending_action: &'a PendingAction,
+        operator: &'a AccountId,
+    },
+    CancelAction {
+        pending_action: &'a PendingAction,
+        operator: &'a AccountId,
+    },
+    DropAction {
+        pending_action: &'a PendingAction,
+        operator: &'a AccountId,
+    },
+    SetTimelockDelay {
+        timelock_delay: &'a DurationOfSeconds,
+    },
+    Upgrade {
+        previous_version: &'a String,
+        version: &'a String,
+    },
+    MigrateState {
+        schema_version: &'a u32,
+        migration_index: &'a U64,
+    },
+    UpdateConfig {
+        old_config: &'a ProtocolConfig,
+        new_config: &'a ProtocolConfig,
+        operator: &'a AccountId,
+    },
+    SetProtocolFee {
+        protocol_fee: &'a ProtocolFee,
+        operator: &'a AccountId,
+    },
+    SetProtocolTreasuryAccount {
+        old_treasury_account: &'a AccountId,
+        new_treasury_account: &'a AccountId,
+    },
+    AccrueProtocolFee {
+        source: &'a ProtocolFeeSource,
+        amount: &'a U128,
+        protocol_fee_balance: &'a U128,
+    },
+    WithdrawProtocolFee {
+        treasury_account: &'a AccountId,
+        amount: &'a U128,
+        protocol_fee_balance: &'a U128,
+    },
+    SetPause {
+        category: &'a PauseCategory,
+        paused: &'a bool,
+        operator: &'a AccountId,
+    },
+    GrantRole {
+        account_id: &'a AccountId,
+        role: &'a Role,
+        operator: &'a AccountId,
+    },
+    RevokeRole {
+        account_id: &'a AccountId,
+        role: &'a Role,
+        operator: &'a AccountId,
+    },
+    ForceUnlockStakingPool {
+        pool_id: &'a PoolId,
+        lock: &'a StakingPoolLock,
+        operator: &'a AccountId,
+    },
+    UnresolvedStaleOperation {
+        pool_id: &'a PoolId,
+        lock: &'a StakingPoolLock,
+        staked_balance: &'a U128,
+        unstaked_balance: &'a U128,
+    },
+    ResolveStaleOperation {
+        pool_id: &'a PoolId,
+        lock: &'a StakingPoolLock,
+        is_done: &'a bool,
+        operator: &'a AccountId,
+    },
+    CheckPoolWhitelisted {
+        pool_id: &'a PoolId,
+        is_whitelisted: &'a bool,
+        deprecated: &'a bool,
+    },
+    AddLiquidity {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+        increase_shares: &'a U128,
+    },
+    RemoveLiquidity {
+        account_id: &'a AccountId,
+        amount: &'a U128,
+        decrease_shares: &'a U128,
+    },
+    InstantWithdraw {
+        account_id: &'a AccountId,
+        withdrawal_certificate: &'a WithdrawalCertificate,
+        amount: &'a U128,
+        pay_amount: &'a U128,
+    },
 }
 
 impl Event<'_> {
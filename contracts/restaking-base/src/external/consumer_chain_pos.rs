@@ -7,4 +7,5 @@ use crate::types::Key;
 pub trait ConsumerChainPos {
     fn bond(staker_id: AccountId, key: Key);
     fn change_key(staker_id: AccountId, key: Key);
+    fn transfer_position(staker_id: AccountId, new_staker_id: AccountId);
 }
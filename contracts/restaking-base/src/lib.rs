@@ -17,19 +17,34 @@ use crate::models::staker::*;
 use crate::models::staking_pool::*;
 use crate::utils::*;
 use crate::{
-    constants::NUM_EPOCHS_TO_UNLOCK,
-    contract_interface::staking::{StakerAction, StakingCallback},
+    constants::{
+        CURRENT_SCHEMA_VERSION, DEFAULT_SHORTFALL_PAUSE_THRESHOLD_BASIS_POINTS,
+        DEFAULT_TIMELOCK_DELAY_SECONDS,
+    },
+    contract_interface::staking::{KeeperAction, StakerAction, StakingCallback},
     contract_interface::view::*,
     external::staking_pool_whitelist::ext_whitelist,
     types::ShareBalance,
 };
 use crate::{contract_interface::restaking::*, external::staking_pool::ext_staking_pool};
+use contract_interface::liquidity_pool::LiquidityPoolAction;
 use itertools::Itertools;
-use models::account::Account;
-use models::pending_withdrawal::PendingWithdrawal;
+use migrate::StateMigrationInfo;
+use models::account::{Account, VersionedAccount};
+use models::failed_operation::*;
+use models::invariant::*;
+use models::liquidity_pool::*;
+use models::pause::*;
+use models::pending_withdrawal::{PendingWithdrawal, VersionedPendingWithdrawal};
+use models::protocol_config::*;
+use models::protocol_fee::*;
+use models::protocol_stats::ProtocolStats;
+use models::role::*;
 use models::slash::Slash;
 use models::staker::StakingChangeResult;
 use models::staking_pool::StakingPool;
+use models::timelock::*;
+use models::versioned::*;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
 use near_sdk::json_types::U128;
@@ -43,7 +58,8 @@ use near_sdk::{
     PanicOnDefault, Promise,
 };
 use near_sdk::{log, PromiseOrValue};
-use std::cmp::min;
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
 use std::ops::Mul;
 use types::*;
 
@@ -54,16 +70,18 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub struct RestakingBaseContract {
     /// The owner of contract
     pub owner: AccountId,
+    /// The account proposed to be the new owner, it becomes owner after accepting ownership
+    pub pending_owner: Option<AccountId>,
     /// Universally Unique Identifier for some entity
     pub uuid: u64,
     /// Any staking change action will make sequence increase
     pub sequence: u64,
     /// The map from account id to staker struct
-    pub stakers: LookupMap<AccountId, Staker>,
+    pub stakers: VersionedLookupMap<AccountId, VersionedStaker>,
     /// The map from pool account id to staking pool struct
-    pub staking_pools: UnorderedMap<PoolId, StakingPool>,
+    pub staking_pools: VersionedUnorderedMap<PoolId, VersionedStakingPool>,
     /// The map from consumer chain id to consumer chain struct
-    pub consumer_chains: UnorderedMap<ConsumerChainId, ConsumerChain>,
+    pub consumer_chains: VersionedUnorderedMap<ConsumerChainId, VersionedConsumerChain>,
     /// The fee of register consumer chain
     pub cc_register_fee: Balance,
     /// The staking pool whitelist account
@@ -73,8 +91,41 @@ pub struct RestakingBaseContract {
     /// The map from slash id to slash struct
     pub slashes: LookupMap<SlashId, Slash>,
     /// The map from account id to account struct
-    pub accounts: LookupMap<AccountId, Account>,
-    pub is_contract_running: bool,
+    pub accounts: VersionedLookupMap<AccountId, VersionedAccount>,
+    /// Whether each category of operations is paused
+    pub pause_matrix: PauseMatrix,
+    /// The reward paid to the account who advances an unstake batch
+    pub keeper_reward: Balance,
+    /// The fund for paying keeper reward
+    pub keeper_fund: Balance,
+    /// The liquidity pool for instant withdraw
+    pub liquidity_pool: LiquidityPool,
+    /// The map from staker id to the ids of open slashes against it
+    pub staker_slashes: LookupMap<StakerId, Vec<SlashId>>,
+    /// The set of accounts which have pending withdrawals
+    pub pending_withdrawal_accounts: UnorderedSet<AccountId>,
+    /// The staking pool is paused by reconciling if its shortfall is greater than this threshold
+    pub shortfall_pause_threshold_basis_points: u32,
+    /// The map from id to the operation whose cross contract call is failed
+    pub failed_operations: UnorderedMap<u64, FailedOperation>,
+    /// The map from account id to the roles granted by owner
+    pub roles: UnorderedMap<AccountId, Vec<Role>>,
+    /// The delay of executing sensitive owner actions after scheduled
+    pub timelock_delay: DurationOfSeconds,
+    /// The map from id to the scheduled sensitive owner action
+    pub pending_actions: UnorderedMap<u64, PendingAction>,
+    /// The schema version of state, the state is being migrated if it's less than current
+    pub schema_version: u32,
+    /// The index of items to migrate next in current schema version
+    pub migration_index: u64,
+    /// The protocol parameters which can be updated by owner or config manager
+    pub config: ProtocolConfig,
+    /// The cut of protocol fee from each source of income
+    pub protocol_fee: ProtocolFee,
+    /// The account which can withdraw the accrued protocol fee
+    pub protocol_treasury_account: AccountId,
+    /// The protocol fee accrued and not withdrawn yet
+    pub protocol_fee_balance: Balance,
 }
 
 #[near_bindgen]
@@ -87,18 +138,35 @@ impl RestakingBaseContract {
         slash_guarantee: U128,
     ) -> Self {
         Self {
-            owner,
+            owner: owner.clone(),
+            pending_owner: None,
             uuid: 0,
             sequence: 0,
-            stakers: LookupMap::new(StorageKey::Stakers),
-            staking_pools: UnorderedMap::new(StorageKey::StakingPools),
-            consumer_chains: UnorderedMap::new(StorageKey::ConsumerChains),
+            stakers: VersionedLookupMap::new(StorageKey::Stakers),
+            staking_pools: VersionedUnorderedMap::new(StorageKey::StakingPools),
+            consumer_chains: VersionedUnorderedMap::new(StorageKey::ConsumerChains),
             cc_register_fee: cc_register_fee.0,
             staking_pool_whitelist_account,
             slash_guarantee: slash_guarantee.0,
             slashes: LookupMap::new(StorageKey::Slashes),
-            accounts: LookupMap::new(StorageKey::Accounts),
-            is_contract_running: true,
+            accounts: VersionedLookupMap::new(StorageKey::Accounts),
+            pause_matrix: PauseMatrix::new(false),
+            keeper_reward: 0,
+            keeper_fund: 0,
+            liquidity_pool: LiquidityPool::new(),
+            staker_slashes: LookupMap::new(StorageKey::StakerSlashes),
+            pending_withdrawal_accounts: UnorderedSet::new(StorageKey::PendingWithdrawalAccounts),
+            shortfall_pause_threshold_basis_points: DEFAULT_SHORTFALL_PAUSE_THRESHOLD_BASIS_POINTS,
+            failed_operations: UnorderedMap::new(StorageKey::FailedOperations),
+            roles: UnorderedMap::new(StorageKey::Roles),
+            timelock_delay: DEFAULT_TIMELOCK_DELAY_SECONDS,
+            pending_actions: UnorderedMap::new(StorageKey::PendingActions),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            migration_index: 0,
+            config: ProtocolConfig::default(),
+            protocol_fee: ProtocolFee::default(),
+            protocol_treasury_account: owner,
+            protocol_fee_balance: 0,
         }
     }
 
@@ -142,4 +210,12 @@ pub(crate) enum StorageKey {
     PendingWithdrawals { account_id: AccountId },
     StakerUnbondingConsumerChains { staker_id: StakerId },
     SubmittedUnstakeBatches { pool_id: PoolId },
+    LiquidityPoolShares,
+    LiquidityPoolPendingWithdrawals,
+    StakerConsumerChainOperators { staker_id: StakerId },
+    StakerSlashes,
+    PendingWithdrawalAccounts,
+    FailedOperations,
+    Roles,
+    PendingActions,
 }
@@ -1,409 +1,368 @@
-use std::collections::HashMap;
+use crate::{constants::CURRENT_SCHEMA_VERSION, *};
 
-use near_sdk::{EpochHeight, Timestamp};
+/// The last version whose state is saved before versioning.
+const UNVERSIONED_STATE_VERSION: &str = "1.1.2";
 
-use crate::*;
+/// The last step of state migration, the accounts which are not stakers are supplied
+/// by `migrate_accounts` in it, and it's finished once upgrader confirms they're all supplied.
+const MIGRATE_ACCOUNTS_SCHEMA_VERSION: u32 = CURRENT_SCHEMA_VERSION - 1;
 
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct MainnetRestakingBaseContract {
-    /// The owner of contract
-    pub owner: AccountId,
-    /// Universally Unique Identifier for some entity
-    pub uuid: u64,
-    /// Any staking change action will make sequence increase
-    pub sequence: u64,
-    /// The map from account id to staker struct
-    pub stakers: LookupMap<AccountId, OldStaker>,
-    /// The map from pool account id to staking pool struct
-    pub staking_pools: UnorderedMap<PoolId, OldStakingPool>,
-    /// The map from consumer chain id to consumer chain struct
-    pub consumer_chains: UnorderedMap<ConsumerChainId, ConsumerChain>,
-    /// The fee of register consumer chain
-    pub cc_register_fee: Balance,
-    /// The staking pool whitelist account
-    pub staking_pool_whitelist_account: AccountId,
-    /// The guarantee of slash
-    pub slash_guarantee: Balance,
-    /// The map from slash id to slash struct
-    pub slashes: LookupMap<SlashId, Slash>,
-    /// The map from account id to account struct
-    pub accounts: LookupMap<AccountId, OldAccount>,
-    pub is_contract_running: bool,
-}
-
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct OldRestakingBaseContract {
-    /// The owner of contract
-    pub owner: AccountId,
-    /// Universally Unique Identifier for some entity
-    pub uuid: u64,
-    /// Any staking change action will make sequence increase
-    pub sequence: u64,
-    /// The map from account id to staker struct
-    pub stakers: LookupMap<AccountId, OldStaker>,
-    /// The map from pool account id to staking pool struct
-    pub staking_pools: UnorderedMap<PoolId, StakingPool>,
-    /// The map from consumer chain id to consumer chain struct
-    pub consumer_chains: UnorderedMap<ConsumerChainId, ConsumerChain>,
-    /// The fee of register consumer chain
-    pub cc_register_fee: Balance,
-    /// The staking pool whitelist account
-    pub staking_pool_whitelist_account: AccountId,
-    /// The guarantee of slash
-    pub slash_guarantee: Balance,
-    /// The map from slash id to slash struct
-    pub slashes: LookupMap<SlashId, Slash>,
-    /// The map from account id to account struct
-    pub accounts: LookupMap<AccountId, Account>,
-    pub is_contract_running: bool,
-}
-
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-struct RestakingBaseContractForUnstakeBatch {
-    /// The owner of contract
-    pub owner: AccountId,
-    /// Universally Unique Identifier for some entity
-    pub uuid: u64,
-    /// Any staking change action will make sequence increase
-    pub sequence: u64,
-    /// The map from account id to staker struct
-    pub stakers: LookupMap<AccountId, Staker>,
-    /// The map from pool account id to staking pool struct
-    pub staking_pools: UnorderedMap<PoolId, OldStakingPool>,
-    /// The map from consumer chain id to consumer chain struct
-    pub consumer_chains: UnorderedMap<ConsumerChainId, ConsumerChain>,
-    /// The fee of register consumer chain
-    pub cc_register_fee: Balance,
-    /// The staking pool whitelist account
-    pub staking_pool_whitelist_account: AccountId,
-    /// The guarantee of slash
-    pub slash_guarantee: Balance,
-    /// The map from slash id to slash struct
-    pub slashes: LookupMap<SlashId, Slash>,
-    /// The map from account id to account struct
-    pub accounts: LookupMap<AccountId, Account>,
-    pub is_contract_running: bool,
-}
-
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-struct RestakingBaseContractForPendingWithdrawal {
-    /// The owner of contract
+/// The layout of contract saved before versioning, the entities in it are upgraded lazily
+/// when read, and the state derived from them is rebuilt by `migrate_state`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RestakingBaseContractV0 {
     pub owner: AccountId,
-    /// Universally Unique Identifier for some entity
     pub uuid: u64,
-    /// Any staking change action will make sequence increase
     pub sequence: u64,
-    /// The map from account id to staker struct
-    pub stakers: LookupMap<AccountId, Staker>,
-    /// The map from pool account id to staking pool struct
-    pub staking_pools: UnorderedMap<PoolId, StakingPool>,
-    /// The map from consumer chain id to consumer chain struct
-    pub consumer_chains: UnorderedMap<ConsumerChainId, ConsumerChain>,
-    /// The fee of register consumer chain
+    pub stakers: VersionedLookupMap<AccountId, VersionedStaker>,
+    pub staking_pools: VersionedUnorderedMap<PoolId, VersionedStakingPool>,
+    pub consumer_chains: VersionedUnorderedMap<ConsumerChainId, VersionedConsumerChain>,
     pub cc_register_fee: Balance,
-    /// The staking pool whitelist account
     pub staking_pool_whitelist_account: AccountId,
-    /// The guarantee of slash
     pub slash_guarantee: Balance,
-    /// The map from slash id to slash struct
     pub slashes: LookupMap<SlashId, Slash>,
-    /// The map from account id to account struct
-    pub accounts: LookupMap<AccountId, OldAccount>,
+    pub accounts: VersionedLookupMap<AccountId, VersionedAccount>,
     pub is_contract_running: bool,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
-pub struct OldStaker {
-    pub staker_id: StakerId,
-    /// The staking pool which staker is select to stake
-    pub select_staking_pool: Option<PoolId>,
-    /// The share of staker owned in staking pool
-    pub shares: ShareBalance,
-    /// The map from consumer chain id to unbonding period
-    pub bonding_consumer_chains: UnorderedMap<ConsumerChainId, DurationOfSeconds>,
-    /// The max period of bonding unlock
-    pub max_bonding_unlock_period: DurationOfSeconds,
-    /// If execute unbond it'll record unlock time
-    pub unbonding_unlock_time: Timestamp,
-}
-
-impl From<OldStaker> for Staker {
-    fn from(value: OldStaker) -> Self {
-        Self {
-            staker_id: value.staker_id.clone(),
-            select_staking_pool: value.select_staking_pool,
-            shares: value.shares,
-            bonding_consumer_chains: value.bonding_consumer_chains,
-            max_bonding_unlock_period: value.max_bonding_unlock_period,
-            unbonding_unlock_time: value.unbonding_unlock_time,
-            unbonding_consumer_chains: UnorderedMap::new(
-                StorageKey::StakerUnbondingConsumerChains {
-                    staker_id: value.staker_id,
-                },
-            ),
-        }
-    }
-}
-
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub struct OldStakingPool {
-    pub pool_id: AccountId,
-    /// Total minted share balance in this staking pool
-    pub total_share_balance: ShareBalance,
-    /// Total staked near balance in this staking pool
-    pub total_staked_balance: Balance,
-    /// The set of all stakers' ids
-    pub stakers: UnorderedSet<AccountId>,
-    /// When restaking base contract interactive with staking pool contract, it'll lock this staking pool until all cross contract call finished
-    pub locked: bool,
-    /// Record staking pool unlock epoch
-    pub unlock_epoch: EpochHeight,
-}
-
-impl From<OldStakingPool> for StakingPool {
-    fn from(value: OldStakingPool) -> Self {
+impl From<RestakingBaseContractV0> for RestakingBaseContract {
+    fn from(value: RestakingBaseContractV0) -> Self {
         Self {
-            pool_id: value.pool_id.clone(),
-            total_share_balance: value.total_share_balance,
-            total_staked_balance: value.total_staked_balance,
+            owner: value.owner.clone(),
+            pending_owner: None,
+            uuid: value.uuid,
+            sequence: value.sequence,
             stakers: value.stakers,
-            locked: value.locked,
-            unlock_epoch: value.unlock_epoch,
-            last_unstake_epoch: 0,
-            last_unstake_batch_id: None,
-            current_unstake_batch_id: 0.into(),
-            batched_unstake_amount: 0,
-            submitted_unstake_batches: UnorderedMap::new(StorageKey::SubmittedUnstakeBatches {
-                pool_id: value.pool_id,
-            }),
+            staking_pools: value.staking_pools,
+            consumer_chains: value.consumer_chains,
+            cc_register_fee: value.cc_register_fee,
+            staking_pool_whitelist_account: value.staking_pool_whitelist_account,
+            slash_guarantee: value.slash_guarantee,
+            slashes: value.slashes,
+            accounts: value.accounts,
+            pause_matrix: PauseMatrix::new(!value.is_contract_running),
+            keeper_reward: 0,
+            keeper_fund: 0,
+            liquidity_pool: LiquidityPool::new(),
+            staker_slashes: LookupMap::new(StorageKey::StakerSlashes),
+            pending_withdrawal_accounts: UnorderedSet::new(StorageKey::PendingWithdrawalAccounts),
+            shortfall_pause_threshold_basis_points: DEFAULT_SHORTFALL_PAUSE_THRESHOLD_BASIS_POINTS,
+            failed_operations: UnorderedMap::new(StorageKey::FailedOperations),
+            roles: UnorderedMap::new(StorageKey::Roles),
+            timelock_delay: DEFAULT_TIMELOCK_DELAY_SECONDS,
+            pending_actions: UnorderedMap::new(StorageKey::PendingActions),
+            schema_version: 0,
+            migration_index: 0,
+            config: ProtocolConfig::default(),
+            protocol_fee: ProtocolFee::default(),
+            protocol_treasury_account: value.owner,
+            protocol_fee_balance: 0,
         }
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
-pub struct OldAccount {
-    // todo staking pool can't get all shares keeper
-    pub legacy_shares: HashMap<PoolId, ShareBalance>,
-
-    // todo need more suitable datastruct
-    pub pending_withdrawals: UnorderedMap<WithdrawalCertificate, OldPendingWithdrawal>,
-}
-
-#[derive(BorshSerialize, BorshDeserialize, Clone)]
-pub struct OldPendingWithdrawal {
-    pub withdrawal_certificate: WithdrawalCertificate,
-    pub pool_id: PoolId,
-    pub amount: Balance,
-    pub unlock_epoch: EpochHeight,
-    pub unlock_time: Timestamp,
-    pub beneficiary: AccountId,
-    pub allow_other_withdraw: bool,
-}
-
-impl From<OldPendingWithdrawal> for PendingWithdrawal {
-    fn from(value: OldPendingWithdrawal) -> Self {
-        Self {
-            withdrawal_certificate: value.withdrawal_certificate,
-            pool_id: value.pool_id,
-            amount: value.amount,
-            unlock_epoch: value.unlock_epoch,
-            unlock_time: value.unlock_time,
-            beneficiary: value.beneficiary,
-            allow_other_withdraw: value.allow_other_withdraw,
-            unstake_batch_id: None,
-        }
-    }
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StateMigrationInfo {
+    pub schema_version: u32,
+    pub current_schema_version: u32,
+    pub migration_index: U64,
 }
 
 #[near_bindgen]
 impl RestakingBaseContract {
+    /// Deploy the new code and migrate state in the same transaction,
+    /// the deployment is reverted if migration is failed.
+    /// Then the derived state is rebuilt in order, the operations are refused until it's done:
+    /// 1. call `migrate_state` until it returns true, the stakers are iterated in it;
+    /// 2. call `migrate_accounts` with the accounts which have unstaked all and aren't stakers;
+    /// 3. call `migrate_accounts` with `is_finished` true to confirm they're all supplied.
+    #[payable]
+    pub fn upgrade(&mut self, #[serializer(borsh)] code: Vec<u8>) -> Promise {
+        assert_one_yocto();
+        self.assert_role(Role::Upgrader);
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                "migrate".to_string(),
+                json!({ "previous_version": VERSION })
+                    .to_string()
+                    .into_bytes(),
+                NO_DEPOSIT,
+                Gas::ONE_TERA.mul(self.config.gas.migrate),
+            )
+    }
+
+    /// It's called by `upgrade` after the new code is deployed.
+    /// Only the top-level struct is migrated here, the entities are upgraded when read,
+    /// and the derived state is rebuilt by calling `migrate_state` in batches.
     #[private]
     #[init(ignore_state)]
-    pub fn migrate_mainnet(staker_list: Vec<AccountId>) -> Self {
-        let mut mainnet_contract: MainnetRestakingBaseContract =
-            env::state_read().expect("Failed read state");
-
-        let mut new_stakers: LookupMap<AccountId, Staker> = LookupMap::new(StorageKey::Stakers);
-        for staker_id in &staker_list {
-            if let Some(old_staker) = mainnet_contract.stakers.remove(staker_id) {
-                new_stakers.insert(staker_id, &old_staker.into());
-            }
+    pub fn migrate(previous_version: String) -> Self {
+        assert_not_downgrade(&previous_version, VERSION);
+        let contract: Self =
+            if parse_version(&previous_version) <= parse_version(UNVERSIONED_STATE_VERSION) {
+                env::state_read::<RestakingBaseContractV0>()
+                    .expect("Failed read state")
+                    .into()
+            } else {
+                env::state_read().expect("Failed read state")
+            };
+        Event::Upgrade {
+            previous_version: &previous_version,
+            version: &VERSION.to_string(),
         }
+        .emit();
+        contract
+    }
 
-        let staking_pools = mainnet_contract.staking_pools.values().collect_vec();
-        let mut new_staking_pools: UnorderedMap<PoolId, StakingPool> =
-            UnorderedMap::new(StorageKey::StakingPools);
-        mainnet_contract.staking_pools.clear();
-        for e in staking_pools {
-            new_staking_pools.insert(&e.pool_id.clone(), &e.into());
+    /// Run the next batch of state migration, at most `limit` items are migrated in one call.
+    /// Returns whether the batched steps are finished, then the last step is finished by `migrate_accounts`.
+    pub fn migrate_state(&mut self, limit: u32) -> bool {
+        self.assert_role(Role::Upgrader);
+        assert!(
+            self.schema_version < CURRENT_SCHEMA_VERSION,
+            "The state is already migrated."
+        );
+        assert!(
+            self.schema_version < MIGRATE_ACCOUNTS_SCHEMA_VERSION,
+            "The batched steps are finished, please confirm the accounts by migrate_accounts."
+        );
+        let from_index = self.migration_index;
+        let limit = limit as u64;
+        let is_step_finished = match self.schema_version {
+            0 => self.internal_migrate_bonded_shares(from_index, limit),
+            1 => self.internal_migrate_slashes(from_index, limit),
+            2 => self.internal_migrate_pending_withdrawal_accounts(from_index, limit),
+            _ => unreachable!(),
+        };
+        if is_step_finished {
+            self.schema_version += 1;
+            self.migration_index = 0;
+        } else {
+            self.migration_index += limit;
         }
-
-        let mut new_accounts: HashMap<AccountId, Account> = HashMap::new();
-        for account_id in staker_list {
-            if !mainnet_contract.accounts.contains_key(&account_id) {
-                continue;
-            }
-            let mut old_account = mainnet_contract.accounts.get(&account_id).unwrap();
-
-            let old_pending_withdrawals = old_account.pending_withdrawals.values().collect_vec();
-
-            old_account.pending_withdrawals.clear();
-
-            let mut new_account = Account {
-                legacy_shares: old_account.legacy_shares,
-                pending_withdrawals: UnorderedMap::<WithdrawalCertificate, PendingWithdrawal>::new(
-                    StorageKey::PendingWithdrawals {
-                        account_id: account_id.clone(),
-                    },
-                ),
-            };
-
-            for pending_withdrawal in old_pending_withdrawals {
-                new_account.pending_withdrawals.insert(
-                    &pending_withdrawal.withdrawal_certificate,
-                    &pending_withdrawal.clone().into(),
-                );
-            }
-            mainnet_contract.accounts.remove(&account_id);
-            new_accounts.insert(account_id, new_account);
+        Event::MigrateState {
+            schema_version: &self.schema_version,
+            migration_index: &U64(self.migration_index),
         }
+        .emit();
+        self.schema_version == MIGRATE_ACCOUNTS_SCHEMA_VERSION
+    }
 
-        let mut new_accounts_map = LookupMap::new(StorageKey::Accounts);
-        for (account_id, account) in new_accounts {
-            new_accounts_map.insert(&account_id, &account);
+    /// Register the pending withdrawals of the given accounts before the state is migrated.
+    /// The accounts can't be iterated, so the ones which have unstaked all and aren't found
+    /// in stakers of staking pools should be supplied by upgrader. The state migration is
+    /// finished when `is_finished` is true, it's allowed only after `migrate_state` is finished.
+    /// Returns whether the state is migrated to the current schema version.
+    pub fn migrate_accounts(&mut self, account_ids: Vec<AccountId>, is_finished: bool) -> bool {
+        self.assert_role(Role::Upgrader);
+        assert!(
+            self.schema_version < CURRENT_SCHEMA_VERSION,
+            "The state is already migrated."
+        );
+        for account_id in account_ids {
+            if let Some(account) = self.accounts.get(&account_id) {
+                self.internal_save_account(&account_id, &account);
+            }
         }
-
-        Self {
-            owner: mainnet_contract.owner,
-            uuid: mainnet_contract.uuid,
-            sequence: mainnet_contract.sequence,
-            stakers: new_stakers,
-            staking_pools: new_staking_pools,
-            consumer_chains: mainnet_contract.consumer_chains,
-            cc_register_fee: mainnet_contract.cc_register_fee,
-            staking_pool_whitelist_account: mainnet_contract.staking_pool_whitelist_account,
-            slash_guarantee: mainnet_contract.slash_guarantee,
-            slashes: mainnet_contract.slashes,
-            accounts: new_accounts_map,
-            is_contract_running: mainnet_contract.is_contract_running,
+        if is_finished {
+            assert_eq!(
+                self.schema_version, MIGRATE_ACCOUNTS_SCHEMA_VERSION,
+                "The batched steps are not finished, please call migrate_state first."
+            );
+            self.schema_version = CURRENT_SCHEMA_VERSION;
+            self.migration_index = 0;
+            Event::MigrateState {
+                schema_version: &self.schema_version,
+                migration_index: &U64(self.migration_index),
+            }
+            .emit();
         }
+        self.schema_version == CURRENT_SCHEMA_VERSION
     }
+}
 
-    #[private]
-    #[init(ignore_state)]
-    pub fn migrate(staker_list: Vec<AccountId>) -> Self {
-        let mut old_contract: OldRestakingBaseContract = env::state_read().expect("failed");
-
-        let mut new_stakers: LookupMap<AccountId, Staker> = LookupMap::new(StorageKey::Stakers);
+impl RestakingBaseContract {
+    /// The operations which change state are refused until the state is migrated,
+    /// the view of migration is `get_state_migration_info`.
+    pub(crate) fn assert_state_migrated(&self) {
+        assert_eq!(
+            self.schema_version, CURRENT_SCHEMA_VERSION,
+            "The state is being migrated."
+        );
+    }
 
-        for staker_id in staker_list {
-            if let Some(old_staker) = old_contract.stakers.remove(&staker_id) {
-                new_stakers.insert(&staker_id, &old_staker.into());
+    /// Rebuild the bonded shares of consumer chains from their bonding stakers.
+    fn internal_migrate_bonded_shares(&mut self, from_index: u64, limit: u64) -> bool {
+        let segments = self.consumer_chains.values().map(|consumer_chain| {
+            (
+                consumer_chain.consumer_chain_id,
+                consumer_chain.bonding_stakers,
+            )
+        });
+        let (items, is_finished) = page_in_segments(segments, from_index, limit);
+        for (consumer_chain_id, staker_id) in items {
+            let staker = self.internal_get_staker_or_panic(&staker_id);
+            if let Some(pool_id) = &staker.select_staking_pool {
+                self.internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
+                    consumer_chain.increase_bonded_shares(pool_id, staker.shares)
+                });
             }
         }
+        is_finished
+    }
 
-        Self {
-            owner: old_contract.owner,
-            uuid: old_contract.uuid,
-            sequence: old_contract.sequence,
-            stakers: new_stakers,
-            staking_pools: old_contract.staking_pools,
-            consumer_chains: old_contract.consumer_chains,
-            cc_register_fee: old_contract.cc_register_fee,
-            staking_pool_whitelist_account: old_contract.staking_pool_whitelist_account,
-            slash_guarantee: old_contract.slash_guarantee,
-            slashes: old_contract.slashes,
-            accounts: old_contract.accounts,
-            is_contract_running: old_contract.is_contract_running,
+    /// Rebuild the pending slash count of consumer chains and the open slashes of stakers.
+    fn internal_migrate_slashes(&mut self, from_index: u64, limit: u64) -> bool {
+        let end_index = min(from_index + limit, self.uuid);
+        for id in from_index + 1..=end_index {
+            let slash_id = U64(id);
+            if let Some(slash) = self.slashes.get(&slash_id) {
+                self.internal_use_consumer_chain_or_panic(
+                    &slash.consumer_chain_id,
+                    |consumer_chain| consumer_chain.pending_slash_count += 1,
+                );
+                self.internal_save_slash(&slash_id, &slash);
+            }
         }
+        end_index == self.uuid
     }
 
-    #[private]
-    #[init(ignore_state)]
-    pub fn migrate_unstake_batch() -> Self {
-        let mut old_contract: RestakingBaseContractForUnstakeBatch =
-            env::state_read().expect("failed");
-        let staking_pools = old_contract.staking_pools.values().collect_vec();
-        let mut new_staking_pools: UnorderedMap<PoolId, StakingPool> =
-            UnorderedMap::new(StorageKey::StakingPools);
-        old_contract.staking_pools.clear();
-        for e in staking_pools {
-            new_staking_pools.insert(&e.pool_id.clone(), &e.into());
+    /// Register the accounts of stakers which have pending withdrawals,
+    /// the accounts are saved in the current version as well.
+    fn internal_migrate_pending_withdrawal_accounts(
+        &mut self,
+        from_index: u64,
+        limit: u64,
+    ) -> bool {
+        let segments = self
+            .staking_pools
+            .values()
+            .map(|staking_pool| (staking_pool.pool_id, staking_pool.stakers));
+        let (items, is_finished) = page_in_segments(segments, from_index, limit);
+        for (_, staker_id) in items {
+            if let Some(account) = self.accounts.get(&staker_id) {
+                self.internal_save_account(&staker_id, &account);
+            }
         }
+        is_finished
+    }
+}
 
-        Self {
-            owner: old_contract.owner,
-            uuid: old_contract.uuid,
-            sequence: old_contract.sequence,
-            stakers: old_contract.stakers,
-            staking_pools: new_staking_pools,
-            consumer_chains: old_contract.consumer_chains,
-            cc_register_fee: old_contract.cc_register_fee,
-            staking_pool_whitelist_account: old_contract.staking_pool_whitelist_account,
-            slash_guarantee: old_contract.slash_guarantee,
-            slashes: old_contract.slashes,
-            accounts: old_contract.accounts,
-            is_contract_running: old_contract.is_contract_running,
+/// Take the items in range `[from_index, from_index + limit)` of the segments concatenated in order,
+/// returns them with their segment keys and whether the end of segments is reached.
+fn page_in_segments<K: Clone>(
+    segments: impl Iterator<Item = (K, UnorderedSet<AccountId>)>,
+    from_index: u64,
+    limit: u64,
+) -> (Vec<(K, AccountId)>, bool) {
+    let end_index = from_index + limit;
+    let mut items = vec![];
+    let mut offset: u64 = 0;
+    for (key, set) in segments {
+        let start = max(from_index, offset);
+        let stop = min(end_index, offset + set.len());
+        if start < stop {
+            items.extend(
+                set.iter()
+                    .skip((start - offset) as usize)
+                    .take((stop - start) as usize)
+                    .map(|account_id| (key.clone(), account_id)),
+            );
         }
+        offset += set.len();
     }
+    (items, end_index >= offset)
+}
 
-    #[private]
-    #[init(ignore_state)]
-    pub fn migrate_pending_withdrawals(accounts: Vec<AccountId>) -> Self {
-        let mut old_contract: RestakingBaseContractForPendingWithdrawal =
-            env::state_read().expect("failed");
-        let mut new_accounts: HashMap<AccountId, Account> = HashMap::new();
-        for account_id in accounts {
-            if !old_contract.accounts.contains_key(&account_id) {
-                continue;
-            }
-            let mut old_account = old_contract.accounts.get(&account_id).unwrap();
-
-            let old_pending_withdrawals = old_account.pending_withdrawals.values().collect_vec();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_interface::staking::StakerAction;
+    use crate::contract_interface::view::RestakingView;
+    use crate::models::pending_withdrawal::PendingWithdrawal;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    #[test]
+    fn test_migrate_accounts_which_are_not_stakers() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        contract.schema_version = CURRENT_SCHEMA_VERSION - 1;
+
+        // The account unstaked all before upgrade, so it isn't found in stakers of staking pools.
+        let account_id = accounts(2);
+        let mut account = Account::new(account_id.clone());
+        account.pending_withdrawals.insert(
+            &1.into(),
+            &PendingWithdrawal::new(
+                1.into(),
+                "pool.near".parse().unwrap(),
+                100,
+                10,
+                20,
+                account_id.clone(),
+                false,
+                2.into(),
+            ),
+        );
+        contract.accounts.insert(&account_id, &account);
+        assert!(!contract.pending_withdrawal_accounts.contains(&account_id));
 
-            old_account.pending_withdrawals.clear();
+        assert!(!contract.migrate_accounts(vec![account_id.clone(), accounts(3)], false));
+        assert!(contract.pending_withdrawal_accounts.contains(&account_id));
+        assert!(!contract.accounts.contains_key(&accounts(3)));
 
-            let mut new_account = Account {
-                legacy_shares: old_account.legacy_shares,
-                pending_withdrawals: UnorderedMap::<WithdrawalCertificate, PendingWithdrawal>::new(
-                    StorageKey::PendingWithdrawals {
-                        account_id: account_id.clone(),
-                    },
-                ),
-            };
+        // The accounts can still be supplied until the upgrader confirms they're all supplied.
+        assert!(contract.migrate_accounts(vec![], true));
+        assert_eq!(contract.schema_version, CURRENT_SCHEMA_VERSION);
+    }
 
-            for pending_withdrawal in old_pending_withdrawals {
-                new_account.pending_withdrawals.insert(
-                    &pending_withdrawal.withdrawal_certificate,
-                    &pending_withdrawal.clone().into(),
-                );
-            }
-            old_contract.accounts.remove(&account_id);
-            new_accounts.insert(account_id, new_account);
-        }
+    #[test]
+    #[should_panic(
+        expected = "The batched steps are finished, please confirm the accounts by migrate_accounts."
+    )]
+    fn test_migrate_state_not_skip_accounts() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        contract.schema_version = MIGRATE_ACCOUNTS_SCHEMA_VERSION;
+        contract.migrate_state(10);
+    }
 
-        let mut new_accounts_map = LookupMap::new(StorageKey::Accounts);
-        for (account_id, account) in new_accounts {
-            new_accounts_map.insert(&account_id, &account);
-        }
+    #[test]
+    #[should_panic(
+        expected = "The batched steps are not finished, please call migrate_state first."
+    )]
+    fn test_migrate_accounts_finished_before_batched_steps() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        contract.schema_version = 0;
+        contract.migrate_accounts(vec![], true);
+    }
 
-        Self {
-            owner: old_contract.owner,
-            uuid: old_contract.uuid,
-            sequence: old_contract.sequence,
-            stakers: old_contract.stakers,
-            staking_pools: old_contract.staking_pools,
-            consumer_chains: old_contract.consumer_chains,
-            cc_register_fee: old_contract.cc_register_fee,
-            staking_pool_whitelist_account: old_contract.staking_pool_whitelist_account,
-            slash_guarantee: old_contract.slash_guarantee,
-            slashes: old_contract.slashes,
-            accounts: new_accounts_map,
-            is_contract_running: old_contract.is_contract_running,
-        }
+    #[test]
+    #[should_panic(expected = "The state is being migrated.")]
+    fn test_operations_refused_while_migrating() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        contract.schema_version = CURRENT_SCHEMA_VERSION - 1;
+
+        // No category is paused, but the contract isn't running until the state is migrated.
+        assert!(!contract.get_pause_matrix().is_any_paused());
+        assert!(!contract.is_contract_running());
+        contract.ping(None);
     }
 }
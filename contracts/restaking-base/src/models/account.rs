@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use crate::{types::ShareBalance, *};
+use near_sdk::StorageUsage;
+
+use crate::{constants::DEFAULT_REGISTER_STORAGE_FEE, types::ShareBalance, *};
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Account {
@@ -8,17 +10,35 @@ pub struct Account {
     pub legacy_shares: HashMap<PoolId, ShareBalance>,
 
     // todo need more suitable datastruct
-    pub pending_withdrawals: UnorderedMap<WithdrawalCertificate, PendingWithdrawal>,
+    pub pending_withdrawals:
+        VersionedUnorderedMap<WithdrawalCertificate, VersionedPendingWithdrawal>,
+
+    /// The near deposited by storage_deposit or attached for storage
+    pub storage_balance: Balance,
+    /// The bytes used by account, staker and pending withdrawals of this account
+    pub storage_usage: StorageUsage,
 }
 
 impl Account {
     pub(crate) fn new(account_id: AccountId) -> Self {
         Account {
             legacy_shares: HashMap::new(),
-            pending_withdrawals: UnorderedMap::new(StorageKey::PendingWithdrawals { account_id }),
+            pending_withdrawals: VersionedUnorderedMap::new(StorageKey::PendingWithdrawals {
+                account_id,
+            }),
+            storage_balance: 0,
+            storage_usage: 0,
         }
     }
 
+    pub fn storage_cost(&self) -> Balance {
+        Balance::from(self.storage_usage) * env::storage_byte_cost()
+    }
+
+    pub fn available_storage_balance(&self) -> Balance {
+        self.storage_balance.saturating_sub(self.storage_cost())
+    }
+
     pub fn save_legacy_shares(&mut self, shares: ShareBalance, pool_id: PoolId) {
         let new_shares = shares
             .checked_add(self.legacy_shares.get(&pool_id).unwrap_or(&0).to_owned())
@@ -29,11 +49,73 @@ impl Account {
     pub fn rollback_pending_withdrawals(&mut self, pending_withdrawal: &PendingWithdrawal) {
         self.pending_withdrawals.insert(
             &pending_withdrawal.withdrawal_certificate,
-            &pending_withdrawal,
+            pending_withdrawal,
         );
     }
 }
 
+/// The layout of account saved before versioning.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AccountV0 {
+    pub legacy_shares: HashMap<PoolId, ShareBalance>,
+    pub pending_withdrawals:
+        VersionedUnorderedMap<WithdrawalCertificate, VersionedPendingWithdrawal>,
+}
+
+impl From<AccountV0> for Account {
+    fn from(value: AccountV0) -> Self {
+        Self {
+            legacy_shares: value.legacy_shares,
+            pending_withdrawals: value.pending_withdrawals,
+            // The storage usage of legacy account is unknown, treat the register fee as used up.
+            storage_balance: DEFAULT_REGISTER_STORAGE_FEE,
+            storage_usage: (DEFAULT_REGISTER_STORAGE_FEE / env::storage_byte_cost())
+                as StorageUsage,
+        }
+    }
+}
+
+pub enum VersionedAccount {
+    V0(AccountV0),
+    V1(Account),
+}
+
+impl Versioned for VersionedAccount {
+    type Current = Account;
+    const CURRENT_VERSION: u8 = 1;
+}
+
+impl From<VersionedAccount> for Account {
+    fn from(value: VersionedAccount) -> Self {
+        match value {
+            VersionedAccount::V0(account) => account.into(),
+            VersionedAccount::V1(account) => account,
+        }
+    }
+}
+
+impl BorshSerialize for VersionedAccount {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            VersionedAccount::V0(account) => BorshSerialize::serialize(account, writer),
+            VersionedAccount::V1(account) => {
+                write_version(writer, 1)?;
+                BorshSerialize::serialize(account, writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedAccount {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        match read_version(buf)? {
+            None => Ok(VersionedAccount::V0(BorshDeserialize::deserialize(buf)?)),
+            Some(1) => Ok(VersionedAccount::V1(BorshDeserialize::deserialize(buf)?)),
+            Some(version) => Err(unknown_version_error(version)),
+        }
+    }
+}
+
 impl RestakingBaseContract {
     pub(crate) fn internal_get_account_or_panic(&self, account_id: &AccountId) -> Account {
         self.accounts
@@ -49,6 +131,108 @@ impl RestakingBaseContract {
 
     pub(crate) fn internal_save_account(&mut self, account_id: &AccountId, account: &Account) {
         self.accounts.insert(account_id, account);
+        if account.pending_withdrawals.is_empty() {
+            self.pending_withdrawal_accounts.remove(account_id);
+        } else {
+            self.pending_withdrawal_accounts.insert(account_id);
+        }
+    }
+
+    pub(crate) fn internal_add_storage_balance(&mut self, account_id: &AccountId, amount: Balance) {
+        self.internal_use_account(account_id, |account| account.storage_balance += amount);
+    }
+
+    /// Record the storage changed since `initial_storage_usage` in account.
+    /// It doesn't check the storage balance, because it's also used in callbacks.
+    pub(crate) fn internal_record_storage_usage(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        let current_storage_usage = env::storage_usage();
+        self.internal_use_account(account_id, |account| {
+            if current_storage_usage >= initial_storage_usage {
+                account.storage_usage += current_storage_usage - initial_storage_usage;
+            } else {
+                account.storage_usage = account
+                    .storage_usage
+                    .saturating_sub(initial_storage_usage - current_storage_usage);
+            }
+        });
+    }
+
+    /// Record the storage changed since `initial_storage_usage` in account,
+    /// and make sure the storage balance of account is able to cover it.
+    pub(crate) fn internal_charge_storage_usage(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        self.internal_record_storage_usage(account_id, initial_storage_usage);
+        let account = self.internal_get_account_or_panic(account_id);
+        assert!(
+            account.storage_balance >= account.storage_cost(),
+            "Not enough storage balance of {}, the storage cost is {}, but storage balance is {}.",
+            account_id,
+            account.storage_cost(),
+            account.storage_balance
+        );
+    }
+
+    /// Record the storage changed since `initial_storage_usage` in account,
+    /// and refund the storage fee of the released storage to account.
+    /// It should only be used when the released storage can't be taken back by a callback.
+    pub(crate) fn internal_refund_released_storage(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        self.internal_record_storage_usage(account_id, initial_storage_usage);
+        let released_storage_usage = initial_storage_usage.saturating_sub(env::storage_usage());
+        let refund_amount = self.internal_use_account(account_id, |account| {
+            let refund_amount = min(
+                Balance::from(released_storage_usage) * env::storage_byte_cost(),
+                account.available_storage_balance(),
+            );
+            account.storage_balance -= refund_amount;
+            refund_amount
+        });
+        if refund_amount > 0 {
+            self.transfer_near(account_id.clone(), refund_amount);
+        }
+    }
+
+    /// The storage of async action is charged in callback which can't fail,
+    /// so the storage balance and attached deposit should be able to cover the storage fee first.
+    pub(crate) fn internal_assert_storage_prepaid(&self, account_id: &AccountId) {
+        let available_storage_balance = self
+            .internal_get_account_or_panic(account_id)
+            .available_storage_balance();
+        assert!(
+            available_storage_balance + env::attached_deposit() >= self.config.storage_fee,
+            "Not enough storage balance of {}, should attach at least {} near.",
+            account_id,
+            self.config
+                .storage_fee
+                .saturating_sub(available_storage_balance)
+        );
+    }
+
+    /// Refund the part of attached deposit which isn't used by storage to payer.
+    pub(crate) fn internal_refund_unused_storage_deposit(
+        &mut self,
+        account_id: &AccountId,
+        payer_id: AccountId,
+        attached_deposit: Balance,
+    ) {
+        let refund_amount = self.internal_use_account(account_id, |account| {
+            let refund_amount = min(attached_deposit, account.available_storage_balance());
+            account.storage_balance -= refund_amount;
+            refund_amount
+        });
+        if refund_amount > 0 {
+            self.transfer_near(payer_id, refund_amount);
+        }
     }
 
     pub(crate) fn internal_use_account<F, R>(&mut self, account_id: &AccountId, mut f: F) -> R
@@ -1,4 +1,6 @@
-use crate::types::{ConsumerChainId, DurationOfSeconds};
+use std::collections::HashMap;
+
+use crate::types::{ConsumerChainId, DurationOfSeconds, ShareBalance};
 use crate::*;
 use near_sdk::AccountId;
 
@@ -18,6 +20,8 @@ pub struct ConsumerChain {
     pub website: String,
     /// The account id of governance
     pub governance: AccountId,
+    /// The account id proposed to be the new governance, it becomes governance after accepting
+    pub pending_governance: Option<AccountId>,
     /// The set of bonding stakers' ids
     pub bonding_stakers: UnorderedSet<StakerId>,
     /// The account id of treasury, the slash token will send to this account
@@ -26,6 +30,10 @@ pub struct ConsumerChain {
     pub pos_account_id: AccountId,
     pub blacklist: UnorderedSet<AccountId>,
     pub register_fee: Balance,
+    /// The map from pool id to total shares of bonding stakers in that pool
+    pub bonded_shares: HashMap<PoolId, ShareBalance>,
+    /// The count of slashes requested and not processed yet
+    pub pending_slash_count: u32,
 }
 
 impl ConsumerChain {
@@ -58,6 +66,7 @@ impl ConsumerChain {
             unbonding_period: register_param.unbonding_period,
             website: register_param.website,
             governance: governance,
+            pending_governance: None,
             bonding_stakers: UnorderedSet::new(StorageKey::ConsumerChainBondingStakers {
                 consumer_chain_id: register_param.consumer_chain_id.clone(),
             }),
@@ -68,6 +77,8 @@ impl ConsumerChain {
                 consumer_chain_id: register_param.consumer_chain_id.clone(),
             }),
             register_fee: register_fee,
+            bonded_shares: HashMap::new(),
+            pending_slash_count: 0,
         }
     }
 
@@ -85,10 +96,24 @@ impl ConsumerChain {
         }
 
         if update_param.governance.is_some() {
-            self.governance = update_param.governance.unwrap();
+            self.pending_governance = update_param.governance;
         }
     }
 
+    /// Make the pending governance be governance, return the old governance.
+    pub fn accept_governance(&mut self) -> AccountId {
+        let predecessor_account_id = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_governance.as_ref(),
+            Some(&predecessor_account_id),
+            "The predecessor_account_id({}) is not pending governance({:?})",
+            predecessor_account_id,
+            self.pending_governance
+        );
+        self.pending_governance = None;
+        std::mem::replace(&mut self.governance, predecessor_account_id)
+    }
+
     pub fn assert_chain_active(&self) {
         assert!(
             matches!(self.status, ConsumerChainStatus::Active),
@@ -97,7 +122,7 @@ impl ConsumerChain {
         );
     }
 
-    pub fn bond(&mut self, staker_id: &StakerId) {
+    pub fn bond(&mut self, staker_id: &StakerId, pool_id: &PoolId, shares: ShareBalance) {
         self.assert_chain_active();
         assert!(
             !self.blacklist.contains(&staker_id),
@@ -105,11 +130,142 @@ impl ConsumerChain {
             staker_id,
             self.consumer_chain_id
         );
-        self.bonding_stakers.insert(staker_id);
+        if self.bonding_stakers.insert(staker_id) {
+            self.increase_bonded_shares(pool_id, shares);
+        }
+    }
+
+    pub fn unbond(&mut self, staker_id: &StakerId, pool_id: &PoolId, shares: ShareBalance) {
+        if self.bonding_stakers.remove(staker_id) {
+            self.decrease_bonded_shares(pool_id, shares);
+        }
+    }
+
+    pub fn increase_bonded_shares(&mut self, pool_id: &PoolId, shares: ShareBalance) {
+        *self.bonded_shares.entry(pool_id.clone()).or_insert(0) += shares;
+    }
+
+    pub fn decrease_bonded_shares(&mut self, pool_id: &PoolId, shares: ShareBalance) {
+        let remaining_shares = self
+            .bonded_shares
+            .get(pool_id)
+            .unwrap_or(&0)
+            .checked_sub(shares)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Failed to decrease bonded shares of {} in {}, the bonded shares are less than {}.",
+                    pool_id, self.consumer_chain_id, shares
+                )
+            });
+        if remaining_shares == 0 {
+            self.bonded_shares.remove(pool_id);
+        } else {
+            self.bonded_shares.insert(pool_id.clone(), remaining_shares);
+        }
     }
 
-    pub fn unbond(&mut self, staker_id: &StakerId) {
-        self.bonding_stakers.remove(staker_id);
+    /// The staked near of bonding stakers in each pool, it's calculated by the current share
+    /// price, so the rewards updated by ping are included.
+    pub fn get_bonded_stake_per_pool(
+        &self,
+        staking_pools: &VersionedUnorderedMap<PoolId, VersionedStakingPool>,
+    ) -> Vec<(PoolId, Balance)> {
+        self.bonded_shares
+            .iter()
+            .map(|(pool_id, shares)| {
+                let staked_amount = staking_pools
+                    .get(pool_id)
+                    .map(|staking_pool| {
+                        staking_pool.staked_amount_from_shares_balance_rounded_down(*shares)
+                    })
+                    .unwrap_or(0);
+                (pool_id.clone(), staked_amount)
+            })
+            .sorted()
+            .collect_vec()
+    }
+}
+
+/// The layout of consumer chain saved before versioning.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ConsumerChainV0 {
+    pub consumer_chain_id: ConsumerChainId,
+    pub unbonding_period: DurationOfSeconds,
+    pub website: String,
+    pub governance: AccountId,
+    pub bonding_stakers: UnorderedSet<StakerId>,
+    pub treasury: AccountId,
+    pub status: ConsumerChainStatus,
+    pub pos_account_id: AccountId,
+    pub blacklist: UnorderedSet<AccountId>,
+    pub register_fee: Balance,
+}
+
+impl From<ConsumerChainV0> for ConsumerChain {
+    /// The bonded shares and pending slash count are rebuilt by state migration.
+    fn from(value: ConsumerChainV0) -> Self {
+        Self {
+            consumer_chain_id: value.consumer_chain_id,
+            unbonding_period: value.unbonding_period,
+            website: value.website,
+            governance: value.governance,
+            pending_governance: None,
+            bonding_stakers: value.bonding_stakers,
+            treasury: value.treasury,
+            status: value.status,
+            pos_account_id: value.pos_account_id,
+            blacklist: value.blacklist,
+            register_fee: value.register_fee,
+            bonded_shares: HashMap::new(),
+            pending_slash_count: 0,
+        }
+    }
+}
+
+pub enum VersionedConsumerChain {
+    V0(ConsumerChainV0),
+    V1(ConsumerChain),
+}
+
+impl Versioned for VersionedConsumerChain {
+    type Current = ConsumerChain;
+    const CURRENT_VERSION: u8 = 1;
+}
+
+impl From<VersionedConsumerChain> for ConsumerChain {
+    fn from(value: VersionedConsumerChain) -> Self {
+        match value {
+            VersionedConsumerChain::V0(consumer_chain) => consumer_chain.into(),
+            VersionedConsumerChain::V1(consumer_chain) => consumer_chain,
+        }
+    }
+}
+
+impl BorshSerialize for VersionedConsumerChain {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            VersionedConsumerChain::V0(consumer_chain) => {
+                BorshSerialize::serialize(consumer_chain, writer)
+            }
+            VersionedConsumerChain::V1(consumer_chain) => {
+                write_version(writer, 1)?;
+                BorshSerialize::serialize(consumer_chain, writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedConsumerChain {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        match read_version(buf)? {
+            None => Ok(VersionedConsumerChain::V0(BorshDeserialize::deserialize(
+                buf,
+            )?)),
+            Some(1) => Ok(VersionedConsumerChain::V1(BorshDeserialize::deserialize(
+                buf,
+            )?)),
+            Some(version) => Err(unknown_version_error(version)),
+        }
     }
 }
 
@@ -129,7 +285,7 @@ impl RestakingBaseContract {
         consumer_chain: &ConsumerChain,
     ) {
         self.consumer_chains
-            .insert(consumer_chain_id, &consumer_chain);
+            .insert(consumer_chain_id, consumer_chain);
     }
 
     pub(crate) fn internal_use_consumer_chain_or_panic<F, R>(
@@ -147,6 +303,38 @@ impl RestakingBaseContract {
     }
 
     pub fn get_top_stakers_by_shares() {}
+
+    /// Sync the bonded shares of consumer chains which staker is bonding,
+    /// it should be called after the shares of staker increased.
+    pub(crate) fn internal_increase_bonded_shares(
+        &mut self,
+        staker: &Staker,
+        shares: ShareBalance,
+    ) {
+        if let Some(pool_id) = &staker.select_staking_pool {
+            for consumer_chain_id in staker.bonding_consumer_chains.keys() {
+                self.internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
+                    consumer_chain.increase_bonded_shares(pool_id, shares)
+                });
+            }
+        }
+    }
+
+    /// Sync the bonded shares of consumer chains which staker is bonding,
+    /// it should be called after the shares of staker decreased.
+    pub(crate) fn internal_decrease_bonded_shares(
+        &mut self,
+        staker: &Staker,
+        shares: ShareBalance,
+    ) {
+        if let Some(pool_id) = &staker.select_staking_pool {
+            for consumer_chain_id in staker.bonding_consumer_chains.keys() {
+                self.internal_use_consumer_chain_or_panic(&consumer_chain_id, |consumer_chain| {
+                    consumer_chain.decrease_bonded_shares(pool_id, shares)
+                });
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -175,12 +363,48 @@ pub struct ConsumerChainInfo {
     pub unbonding_period: DurationOfSeconds,
     pub website: String,
     pub governance: AccountId,
+    pub pending_governance: Option<AccountId>,
     pub treasury: AccountId,
     pub status: ConsumerChainStatus,
     pub pos_account_id: AccountId,
     pub register_fee: U128,
 }
 
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConsumerChainStats {
+    pub consumer_chain_id: ConsumerChainId,
+    pub bonding_stakers_count: u32,
+    pub total_bonded_stake: U128,
+    pub bonded_stake_per_pool: Vec<(PoolId, U128)>,
+    pub blacklist_size: u32,
+    pub pending_slash_count: u32,
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_get_consumer_chain_stats(
+        &self,
+        consumer_chain: &ConsumerChain,
+    ) -> ConsumerChainStats {
+        let bonded_stake_per_pool = consumer_chain.get_bonded_stake_per_pool(&self.staking_pools);
+        ConsumerChainStats {
+            consumer_chain_id: consumer_chain.consumer_chain_id.clone(),
+            bonding_stakers_count: consumer_chain.bonding_stakers.len() as u32,
+            total_bonded_stake: bonded_stake_per_pool
+                .iter()
+                .map(|(_, staked_amount)| staked_amount)
+                .sum::<Balance>()
+                .into(),
+            bonded_stake_per_pool: bonded_stake_per_pool
+                .into_iter()
+                .map(|(pool_id, staked_amount)| (pool_id, staked_amount.into()))
+                .collect_vec(),
+            blacklist_size: consumer_chain.blacklist.len() as u32,
+            pending_slash_count: consumer_chain.pending_slash_count,
+        }
+    }
+}
+
 impl From<ConsumerChain> for ConsumerChainInfo {
     fn from(value: ConsumerChain) -> Self {
         Self {
@@ -188,6 +412,7 @@ impl From<ConsumerChain> for ConsumerChainInfo {
             unbonding_period: value.unbonding_period,
             website: value.website,
             governance: value.governance,
+            pending_governance: value.pending_governance,
             treasury: value.treasury,
             status: value.status,
             pos_account_id: value.pos_account_id,
@@ -195,3 +420,51 @@ impl From<ConsumerChain> for ConsumerChainInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn consumer_chain() -> ConsumerChain {
+        ConsumerChain::new_from_register_param(
+            ConsumerChainRegisterParam {
+                consumer_chain_id: "test:test".to_string(),
+                cc_pos_account: accounts(1),
+                unbonding_period: 86400,
+                website: "website".to_string(),
+                treasury: accounts(2),
+            },
+            accounts(3),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_bond_and_unbond() {
+        testing_env!(VMContextBuilder::new().build());
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let mut consumer_chain = consumer_chain();
+        consumer_chain.bond(&accounts(4), &pool_id, 100);
+        consumer_chain.bond(&accounts(5), &pool_id, 50);
+        // Bonding again doesn't count the shares twice.
+        consumer_chain.bond(&accounts(4), &pool_id, 100);
+        assert_eq!(consumer_chain.bonded_shares.get(&pool_id), Some(&150));
+
+        consumer_chain.unbond(&accounts(4), &pool_id, 100);
+        assert_eq!(consumer_chain.bonded_shares.get(&pool_id), Some(&50));
+        consumer_chain.unbond(&accounts(5), &pool_id, 50);
+        assert!(consumer_chain.bonded_shares.get(&pool_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "the bonded shares are less than")]
+    fn test_decrease_more_than_bonded_shares() {
+        testing_env!(VMContextBuilder::new().build());
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let mut consumer_chain = consumer_chain();
+        consumer_chain.increase_bonded_shares(&pool_id, 100);
+        consumer_chain.decrease_bonded_shares(&pool_id, 101);
+    }
+}
@@ -0,0 +1,73 @@
+use near_sdk::Timestamp;
+
+use crate::*;
+
+/// The operation which can be replayed after its cross contract call is failed.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RetryableOperation {
+    /// The staked near is held by contract until the operation is retried or abandoned.
+    Stake {
+        staker_id: StakerId,
+        pool_id: PoolId,
+        funder_id: AccountId,
+        amount: U128,
+    },
+    /// The increased near is held by contract until the operation is retried or abandoned.
+    IncreaseStake {
+        staker_id: StakerId,
+        funder_id: AccountId,
+        amount: U128,
+    },
+    /// The unstake batch can be submitted by anyone, it's abandoned once it's not submittable.
+    SubmitUnstakeBatch { pool_id: PoolId },
+    /// The unstake batch can be withdrawn by anyone, it's abandoned once it's withdrawn.
+    WithdrawUnstakeBatch {
+        pool_id: PoolId,
+        unstake_batch_id: UnstakeBatchId,
+    },
+}
+
+impl RetryableOperation {
+    /// The category which should not be paused when retrying the operation.
+    pub fn pause_category(&self) -> PauseCategory {
+        match self {
+            RetryableOperation::Stake { .. } | RetryableOperation::IncreaseStake { .. } => {
+                PauseCategory::Stake
+            }
+            RetryableOperation::SubmitUnstakeBatch { .. }
+            | RetryableOperation::WithdrawUnstakeBatch { .. } => PauseCategory::BatchProcessing,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FailedOperation {
+    pub id: U64,
+    pub operation: RetryableOperation,
+    #[serde(with = "u64_dec_format")]
+    pub failed_at: Timestamp,
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_save_failed_operation(&mut self, operation: RetryableOperation) {
+        let failed_operation = FailedOperation {
+            id: self.next_uuid().into(),
+            operation,
+            failed_at: env::block_timestamp(),
+        };
+        self.failed_operations
+            .insert(&failed_operation.id.0, &failed_operation);
+        Event::SaveFailedOperation {
+            failed_operation: &failed_operation,
+        }
+        .emit();
+    }
+
+    pub(crate) fn internal_take_failed_operation_or_panic(&mut self, id: U64) -> FailedOperation {
+        self.failed_operations
+            .remove(&id.0)
+            .expect(format!("Failed to get failed operation by {}", id.0).as_str())
+    }
+}
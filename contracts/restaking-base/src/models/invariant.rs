@@ -0,0 +1,479 @@
+use std::cmp::max;
+use std::collections::HashMap;
+
+use crate::{constants::CURRENT_SCHEMA_VERSION, *};
+
+/// The sums accumulated by previous pages of `check_invariants`.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InvariantCheckState {
+    /// Sum of checked staker shares per staking pool
+    pub staker_shares: Vec<(PoolId, U128)>,
+    /// Sum of checked pending withdrawals per staking pool and unstake batch
+    pub pending_withdrawal_amounts: Vec<(PoolId, UnstakeBatchId, U128)>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InvariantReport {
+    /// The index to continue checking from, it's None when all items are checked
+    pub next_index: Option<u32>,
+    /// The state should be passed to the next call of `check_invariants`
+    pub state: InvariantCheckState,
+    pub discrepancies: Vec<String>,
+}
+
+/// The item checked by `check_invariants`, all items are indexed in the following order:
+/// stakers of each staking pool, accounts having pending withdrawals, pending withdrawals
+/// of liquidity pool and bonding stakers of each consumer chain.
+enum InvariantItem {
+    PoolStaker(PoolId, StakerId),
+    PendingWithdrawalAccount(AccountId),
+    LiquidityPoolPendingWithdrawal(PendingWithdrawal),
+    ConsumerChainStaker(ConsumerChainId, StakerId),
+}
+
+struct InvariantChecker {
+    staker_shares: HashMap<PoolId, ShareBalance>,
+    pending_withdrawal_amounts: HashMap<(PoolId, u64), Balance>,
+    discrepancies: Vec<String>,
+}
+
+impl InvariantChecker {
+    fn new(state: InvariantCheckState) -> Self {
+        Self {
+            staker_shares: state
+                .staker_shares
+                .into_iter()
+                .map(|(pool_id, shares)| (pool_id, shares.0))
+                .collect(),
+            pending_withdrawal_amounts: state
+                .pending_withdrawal_amounts
+                .into_iter()
+                .map(|(pool_id, unstake_batch_id, amount)| {
+                    ((pool_id, unstake_batch_id.0), amount.0)
+                })
+                .collect(),
+            discrepancies: vec![],
+        }
+    }
+
+    fn add_pending_withdrawal(&mut self, pending_withdrawal: &PendingWithdrawal) {
+        if let Some(unstake_batch_id) = pending_withdrawal.unstake_batch_id {
+            *self
+                .pending_withdrawal_amounts
+                .entry((pending_withdrawal.pool_id.clone(), unstake_batch_id.0))
+                .or_insert(0) += pending_withdrawal.amount;
+        }
+    }
+
+    fn add_legacy_shares(&mut self, account: &Account) {
+        for (pool_id, shares) in &account.legacy_shares {
+            *self.staker_shares.entry(pool_id.clone()).or_insert(0) += shares;
+        }
+    }
+
+    fn into_state(self) -> InvariantCheckState {
+        InvariantCheckState {
+            staker_shares: self
+                .staker_shares
+                .into_iter()
+                .map(|(pool_id, shares)| (pool_id, shares.into()))
+                .sorted()
+                .collect_vec(),
+            pending_withdrawal_amounts: self
+                .pending_withdrawal_amounts
+                .into_iter()
+                .sorted()
+                .map(|((pool_id, unstake_batch_id), amount)| {
+                    (pool_id, unstake_batch_id.into(), amount.into())
+                })
+                .collect_vec(),
+        }
+    }
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_check_invariants(
+        &self,
+        from_index: u32,
+        limit: u32,
+        state: InvariantCheckState,
+    ) -> InvariantReport {
+        let (items, total) = self.internal_get_invariant_items(from_index, limit);
+        let mut checker = InvariantChecker::new(state);
+        for item in items {
+            self.internal_check_invariant_item(&mut checker, item);
+        }
+
+        let end_index = from_index.saturating_add(limit);
+        let next_index = if end_index < total {
+            Some(end_index)
+        } else {
+            self.internal_check_invariant_sums(&mut checker);
+            None
+        };
+
+        let discrepancies = std::mem::take(&mut checker.discrepancies);
+        InvariantReport {
+            next_index,
+            state: checker.into_state(),
+            discrepancies,
+        }
+    }
+
+    fn internal_get_invariant_items(
+        &self,
+        from_index: u32,
+        limit: u32,
+    ) -> (Vec<InvariantItem>, u32) {
+        let from_index = from_index as u64;
+        let end_index = from_index + limit as u64;
+        let mut items = vec![];
+        let mut offset: u64 = 0;
+        // Returns the range to take in a segment which starts at offset.
+        let page_in_segment = |offset: u64, len: u64| -> Option<(usize, usize)> {
+            let start = max(from_index, offset);
+            let stop = min(end_index, offset + len);
+            (start < stop).then(|| ((start - offset) as usize, (stop - start) as usize))
+        };
+
+        for staking_pool in self.staking_pools.values() {
+            if let Some((skip, take)) = page_in_segment(offset, staking_pool.stakers.len()) {
+                items.extend(
+                    staking_pool
+                        .stakers
+                        .iter()
+                        .skip(skip)
+                        .take(take)
+                        .map(|staker_id| {
+                            InvariantItem::PoolStaker(staking_pool.pool_id.clone(), staker_id)
+                        }),
+                );
+            }
+            offset += staking_pool.stakers.len();
+        }
+
+        if let Some((skip, take)) = page_in_segment(offset, self.pending_withdrawal_accounts.len())
+        {
+            items.extend(
+                self.pending_withdrawal_accounts
+                    .iter()
+                    .skip(skip)
+                    .take(take)
+                    .map(InvariantItem::PendingWithdrawalAccount),
+            );
+        }
+        offset += self.pending_withdrawal_accounts.len();
+
+        let liquidity_pool_pending_withdrawals = &self.liquidity_pool.pending_withdrawals;
+        if let Some((skip, take)) =
+            page_in_segment(offset, liquidity_pool_pending_withdrawals.len())
+        {
+            items.extend(
+                liquidity_pool_pending_withdrawals
+                    .values()
+                    .skip(skip)
+                    .take(take)
+                    .map(InvariantItem::LiquidityPoolPendingWithdrawal),
+            );
+        }
+        offset += liquidity_pool_pending_withdrawals.len();
+
+        for consumer_chain in self.consumer_chains.values() {
+            if let Some((skip, take)) =
+                page_in_segment(offset, consumer_chain.bonding_stakers.len())
+            {
+                items.extend(
+                    consumer_chain
+                        .bonding_stakers
+                        .iter()
+                        .skip(skip)
+                        .take(take)
+                        .map(|staker_id| {
+                            InvariantItem::ConsumerChainStaker(
+                                consumer_chain.consumer_chain_id.clone(),
+                                staker_id,
+                            )
+                        }),
+                );
+            }
+            offset += consumer_chain.bonding_stakers.len();
+        }
+
+        (items, offset as u32)
+    }
+
+    fn internal_check_invariant_item(&self, checker: &mut InvariantChecker, item: InvariantItem) {
+        match item {
+            InvariantItem::PoolStaker(pool_id, staker_id) => {
+                let staker = match self.stakers.get(&staker_id) {
+                    Some(staker) => staker,
+                    None => {
+                        checker.discrepancies.push(format!(
+                            "The staker({}) in staking pool({}) is not found.",
+                            staker_id, pool_id
+                        ));
+                        return;
+                    }
+                };
+                if staker.select_staking_pool.as_ref() != Some(&pool_id) {
+                    checker.discrepancies.push(format!(
+                        "The staker({}) in staking pool({}) selects {:?}.",
+                        staker_id, pool_id, staker.select_staking_pool
+                    ));
+                }
+                *checker.staker_shares.entry(pool_id).or_insert(0) += staker.shares;
+                if let Some(account) = self.accounts.get(&staker_id) {
+                    checker.add_legacy_shares(&account);
+                    // The account of staker may not be registered if it's saved by old version.
+                    if !self.pending_withdrawal_accounts.contains(&staker_id) {
+                        for pending_withdrawal in account.pending_withdrawals.values() {
+                            checker.add_pending_withdrawal(&pending_withdrawal);
+                        }
+                    }
+                }
+                for consumer_chain_id in staker.bonding_consumer_chains.keys() {
+                    let is_bonding = self
+                        .consumer_chains
+                        .get(&consumer_chain_id)
+                        .map(|consumer_chain| consumer_chain.bonding_stakers.contains(&staker_id))
+                        .unwrap_or(false);
+                    if !is_bonding {
+                        checker.discrepancies.push(format!(
+                            "The staker({}) bonds consumer chain({}), but it's not in bonding stakers.",
+                            staker_id, consumer_chain_id
+                        ));
+                    }
+                }
+            }
+            InvariantItem::PendingWithdrawalAccount(account_id) => {
+                match self.accounts.get(&account_id) {
+                    Some(account) => {
+                        for pending_withdrawal in account.pending_withdrawals.values() {
+                            checker.add_pending_withdrawal(&pending_withdrawal);
+                        }
+                        // The legacy shares of stakers are added when checking pool stakers.
+                        let is_pool_staker = self
+                            .stakers
+                            .get(&account_id)
+                            .map(|staker| staker.select_staking_pool.is_some())
+                            .unwrap_or(false);
+                        if !is_pool_staker {
+                            checker.add_legacy_shares(&account);
+                        }
+                    }
+                    None => checker.discrepancies.push(format!(
+                        "The account({}) having pending withdrawals is not found.",
+                        account_id
+                    )),
+                }
+            }
+            InvariantItem::LiquidityPoolPendingWithdrawal(pending_withdrawal) => {
+                checker.add_pending_withdrawal(&pending_withdrawal);
+            }
+            InvariantItem::ConsumerChainStaker(consumer_chain_id, staker_id) => {
+                let is_bonding = self
+                    .stakers
+                    .get(&staker_id)
+                    .map(|staker| {
+                        staker
+                            .bonding_consumer_chains
+                            .get(&consumer_chain_id)
+                            .is_some()
+                    })
+                    .unwrap_or(false);
+                if !is_bonding {
+                    checker.discrepancies.push(format!(
+                        "The staker({}) is in bonding stakers of consumer chain({}), but it doesn't bond it.",
+                        staker_id, consumer_chain_id
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Compare the sums with staking pools, it should be called after all items are checked.
+    fn internal_check_invariant_sums(&self, checker: &mut InvariantChecker) {
+        // The accounts having pending withdrawals are registered by state migration,
+        // the sums of unstake batches are incomplete before it's finished.
+        let is_migrating = self.schema_version < CURRENT_SCHEMA_VERSION;
+        if is_migrating {
+            checker.discrepancies.push(format!(
+                "The state is migrating to schema version {}, the pending withdrawals of unregistered accounts are not checked.",
+                CURRENT_SCHEMA_VERSION
+            ));
+            checker.pending_withdrawal_amounts.clear();
+        }
+        for staking_pool in self.staking_pools.values() {
+            let pool_id = &staking_pool.pool_id;
+            let staker_shares = checker.staker_shares.remove(pool_id).unwrap_or(0);
+            if staker_shares != staking_pool.total_share_balance {
+                checker.discrepancies.push(format!(
+                    "The sum of staker shares({}) in staking pool({}) is not equal to total share balance({}).",
+                    staker_shares, pool_id, staking_pool.total_share_balance
+                ));
+            }
+
+            if is_migrating {
+                continue;
+            }
+
+            let current_amount = checker
+                .pending_withdrawal_amounts
+                .remove(&(pool_id.clone(), staking_pool.current_unstake_batch_id.0))
+                .unwrap_or(0);
+            if current_amount != staking_pool.batched_unstake_amount {
+                checker.discrepancies.push(format!(
+                    "The sum of pending withdrawals({}) in current unstake batch({}) of staking pool({}) is not equal to batched unstake amount({}).",
+                    current_amount, staking_pool.current_unstake_batch_id.0, pool_id, staking_pool.batched_unstake_amount
+                ));
+            }
+
+            for submitted_unstake_batch in staking_pool.submitted_unstake_batches.values() {
+                let unstake_batch_id = submitted_unstake_batch.unstake_batch_id;
+                let amount = checker
+                    .pending_withdrawal_amounts
+                    .remove(&(pool_id.clone(), unstake_batch_id.0))
+                    .unwrap_or(0);
+                if amount + submitted_unstake_batch.claimed_amount
+                    != submitted_unstake_batch.total_unstake_amount
+                {
+                    checker.discrepancies.push(format!(
+                        "The sum of pending withdrawals({}) and claimed amount({}) in unstake batch({}) of staking pool({}) is not equal to total unstake amount({}).",
+                        amount, submitted_unstake_batch.claimed_amount, unstake_batch_id.0, pool_id, submitted_unstake_batch.total_unstake_amount
+                    ));
+                }
+            }
+        }
+
+        for (pool_id, shares) in std::mem::take(&mut checker.staker_shares) {
+            checker.discrepancies.push(format!(
+                "The stakers have {} shares in unknown staking pool({}).",
+                shares, pool_id
+            ));
+        }
+        for ((pool_id, unstake_batch_id), amount) in
+            std::mem::take(&mut checker.pending_withdrawal_amounts)
+        {
+            checker.discrepancies.push(format!(
+                "The pending withdrawals({}) belong to unknown unstake batch({}) of staking pool({}).",
+                amount, unstake_batch_id, pool_id
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    /// A contract with one staker of 100 shares and 50 legacy shares in the staking pool,
+    /// its pending withdrawal of 10 near is in the current unstake batch and
+    /// its account is saved by old version which doesn't register it.
+    fn setup_contract() -> (RestakingBaseContract, PoolId) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .build());
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let staker_id = accounts(2);
+
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.stakers.insert(&staker_id);
+        staking_pool.total_share_balance = 150;
+        staking_pool.batched_unstake_amount = 10;
+        contract.internal_save_staking_pool(&staking_pool);
+
+        let mut staker = Staker::new(staker_id.clone());
+        staker.select_staking_pool = Some(pool_id.clone());
+        staker.shares = 100;
+        contract.internal_save_staker(&staker_id, &staker);
+
+        let mut account = Account::new(staker_id.clone());
+        account.legacy_shares.insert(pool_id.clone(), 50);
+        let pending_withdrawal = PendingWithdrawal::new(
+            1.into(),
+            pool_id.clone(),
+            10,
+            0,
+            0,
+            staker_id.clone(),
+            false,
+            staking_pool.current_unstake_batch_id,
+        );
+        account.pending_withdrawals.insert(
+            &pending_withdrawal.withdrawal_certificate,
+            &pending_withdrawal,
+        );
+        contract.internal_save_account(&staker_id, &account);
+        contract.pending_withdrawal_accounts.remove(&staker_id);
+
+        (contract, pool_id)
+    }
+
+    #[test]
+    fn test_check_invariants_of_consistent_state() {
+        let (contract, _) = setup_contract();
+        let report = contract.internal_check_invariants(0, 100, InvariantCheckState::default());
+        assert_eq!(report.next_index, None);
+        assert!(
+            report.discrepancies.is_empty(),
+            "{:?}",
+            report.discrepancies
+        );
+    }
+
+    #[test]
+    fn test_check_invariants_in_pages() {
+        let (mut contract, pool_id) = setup_contract();
+        // A fully unstaked account which has pending withdrawal and legacy shares.
+        let account_id = accounts(3);
+        let mut account = Account::new(account_id.clone());
+        account.legacy_shares.insert(pool_id.clone(), 20);
+        let pending_withdrawal = PendingWithdrawal::new(
+            2.into(),
+            pool_id.clone(),
+            5,
+            0,
+            0,
+            account_id.clone(),
+            false,
+            0.into(),
+        );
+        account.pending_withdrawals.insert(
+            &pending_withdrawal.withdrawal_certificate,
+            &pending_withdrawal,
+        );
+        contract.internal_save_account(&account_id, &account);
+        contract.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+            staking_pool.total_share_balance += 20;
+            staking_pool.batched_unstake_amount += 5;
+        });
+
+        let report = contract.internal_check_invariants(0, 1, InvariantCheckState::default());
+        assert_eq!(report.next_index, Some(1));
+        let report = contract.internal_check_invariants(1, 1, report.state);
+        assert_eq!(report.next_index, None);
+        assert!(
+            report.discrepancies.is_empty(),
+            "{:?}",
+            report.discrepancies
+        );
+    }
+
+    #[test]
+    fn test_check_invariants_of_corrupted_state() {
+        let (mut contract, pool_id) = setup_contract();
+        contract.internal_use_staking_pool_or_panic(&pool_id, |staking_pool| {
+            staking_pool.total_share_balance = 100;
+            staking_pool.batched_unstake_amount = 20;
+        });
+        let report = contract.internal_check_invariants(0, 100, InvariantCheckState::default());
+        assert_eq!(report.discrepancies.len(), 2, "{:?}", report.discrepancies);
+        assert!(report.discrepancies[0].contains("sum of staker shares(150)"));
+        assert!(report.discrepancies[1].contains("sum of pending withdrawals(10)"));
+    }
+}
@@ -0,0 +1,279 @@
+use crate::types::{ShareBalance, U256};
+use crate::*;
+
+pub const FULL_BASIS_POINTS: u32 = 10_000;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LiquidityPool {
+    /// Total minted share balance of liquidity providers
+    pub total_share_balance: ShareBalance,
+    /// The map from liquidity provider id to share balance
+    pub shares: LookupMap<AccountId, ShareBalance>,
+    /// The near which can be used by instant withdraw or removing liquidity
+    pub available_balance: Balance,
+    /// The pending withdrawals taken over from stakers by instant withdraw
+    pub pending_withdrawals:
+        VersionedUnorderedMap<WithdrawalCertificate, VersionedPendingWithdrawal>,
+    /// Total amount of pending withdrawals taken over from stakers
+    pub pending_withdrawal_amount: Balance,
+    /// The fee of instant withdraw in basis points, it belongs to liquidity providers
+    pub fee_basis_points: u32,
+}
+
+impl LiquidityPool {
+    pub(crate) fn new() -> Self {
+        Self {
+            total_share_balance: 0,
+            shares: LookupMap::new(StorageKey::LiquidityPoolShares),
+            available_balance: 0,
+            pending_withdrawals: VersionedUnorderedMap::new(
+                StorageKey::LiquidityPoolPendingWithdrawals,
+            ),
+            pending_withdrawal_amount: 0,
+            fee_basis_points: 0,
+        }
+    }
+
+    pub fn total_balance(&self) -> Balance {
+        self.available_balance + self.pending_withdrawal_amount
+    }
+
+    pub fn add_liquidity(&mut self, account_id: &AccountId, amount: Balance) -> ShareBalance {
+        let total_balance = self.total_balance();
+        let increase_shares = if self.total_share_balance == 0 || total_balance == 0 {
+            amount
+        } else {
+            (U256::from(self.total_share_balance) * U256::from(amount) / U256::from(total_balance))
+                .as_u128()
+        };
+        assert!(
+            increase_shares > 0,
+            "The liquidity amount({}) is too small.",
+            amount
+        );
+
+        self.total_share_balance += increase_shares;
+        self.available_balance += amount;
+        self.shares
+            .insert(account_id, &(self.get_shares(account_id) + increase_shares));
+        increase_shares
+    }
+
+    pub fn remove_liquidity(&mut self, account_id: &AccountId, shares: ShareBalance) -> Balance {
+        let remain_shares = self
+            .get_shares(account_id)
+            .checked_sub(shares)
+            .expect("Failed to remove liquidity, the shares is not enough.");
+        let amount = (U256::from(self.total_balance()) * U256::from(shares)
+            / U256::from(self.total_share_balance))
+        .as_u128();
+        assert!(
+            amount <= self.available_balance,
+            "Failed to remove liquidity, the available balance({}) is less than {}.",
+            self.available_balance,
+            amount
+        );
+
+        self.total_share_balance -= shares;
+        self.available_balance -= amount;
+        if remain_shares == 0 {
+            self.shares.remove(account_id);
+        } else {
+            self.shares.insert(account_id, &remain_shares);
+        }
+        amount
+    }
+
+    pub fn get_shares(&self, account_id: &AccountId) -> ShareBalance {
+        self.shares.get(account_id).unwrap_or(0)
+    }
+
+    pub fn calculate_fee(&self, amount: Balance) -> Balance {
+        (U256::from(amount) * U256::from(self.fee_basis_points) / U256::from(FULL_BASIS_POINTS))
+            .as_u128()
+    }
+
+    /// Take over the pending withdrawal and returns the near paid to its beneficiary.
+    pub fn take_over(&mut self, mut pending_withdrawal: PendingWithdrawal) -> Balance {
+        let pay_amount = pending_withdrawal.amount - self.calculate_fee(pending_withdrawal.amount);
+        assert!(
+            pay_amount <= self.available_balance,
+            "Failed to instant withdraw, the available balance({}) of liquidity pool is less than {}.",
+            self.available_balance,
+            pay_amount
+        );
+
+        self.available_balance -= pay_amount;
+        self.pending_withdrawal_amount += pending_withdrawal.amount;
+        pending_withdrawal.beneficiary = env::current_account_id();
+        pending_withdrawal.allow_other_withdraw = true;
+        self.pending_withdrawals.insert(
+            &pending_withdrawal.withdrawal_certificate,
+            &pending_withdrawal,
+        );
+        pay_amount
+    }
+
+    pub fn settle(&mut self, pending_withdrawal: &PendingWithdrawal) {
+        self.pending_withdrawals
+            .remove(&pending_withdrawal.withdrawal_certificate);
+        self.pending_withdrawal_amount -= pending_withdrawal.amount;
+        self.available_balance += pending_withdrawal.amount;
+    }
+}
+
+impl RestakingBaseContract {
+    /// Instant withdraw is refused while any consumer chain still can slash the staker.
+    pub(crate) fn internal_assert_not_slashable(&self, account_id: &AccountId) {
+        if let Some(staker) = self.stakers.get(account_id) {
+            let slashable_consumer_chain_id = staker
+                .bonding_consumer_chains
+                .keys()
+                .chain(staker.unbonding_consumer_chains.keys())
+                .find(|consumer_chain_id| staker.allow_slash(consumer_chain_id));
+            assert!(
+                slashable_consumer_chain_id.is_none(),
+                "Failed to instant withdraw, {} still can be slashed by {}.",
+                account_id,
+                slashable_consumer_chain_id.unwrap_or_default()
+            );
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LiquidityPoolInfo {
+    pub total_share_balance: U128,
+    pub available_balance: U128,
+    pub pending_withdrawal_amount: U128,
+    pub pending_withdrawals_count: u32,
+    pub fee_basis_points: u32,
+}
+
+impl From<&LiquidityPool> for LiquidityPoolInfo {
+    fn from(value: &LiquidityPool) -> Self {
+        Self {
+            total_share_balance: value.total_share_balance.into(),
+            available_balance: value.available_balance.into(),
+            pending_withdrawal_amount: value.pending_withdrawal_amount.into(),
+            pending_withdrawals_count: value.pending_withdrawals.len() as u32,
+            fee_basis_points: value.fee_basis_points,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::MAX_SETTLE_LIQUIDITY_POOL_LIMIT;
+    use crate::contract_interface::liquidity_pool::LiquidityPoolAction;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn pending_withdrawal(amount: Balance) -> PendingWithdrawal {
+        PendingWithdrawal::new(
+            1.into(),
+            "pool.near".parse().unwrap(),
+            amount,
+            0,
+            0,
+            accounts(1),
+            false,
+            0.into(),
+        )
+    }
+
+    #[test]
+    fn test_take_over_and_settle() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .build());
+        let mut liquidity_pool = LiquidityPool::new();
+        liquidity_pool.fee_basis_points = 100;
+        assert_eq!(liquidity_pool.add_liquidity(&accounts(2), 1_000), 1_000);
+
+        // The staker is paid the amount minus 1% fee.
+        assert_eq!(liquidity_pool.take_over(pending_withdrawal(500)), 495);
+        assert_eq!(liquidity_pool.available_balance, 505);
+        assert_eq!(liquidity_pool.pending_withdrawal_amount, 500);
+        assert_eq!(liquidity_pool.total_balance(), 1_005);
+        let taken_over = liquidity_pool.pending_withdrawals.get(&1.into()).unwrap();
+        assert_eq!(taken_over.beneficiary, accounts(0));
+        assert!(taken_over.allow_other_withdraw);
+
+        // The fee is shared by liquidity providers.
+        assert_eq!(liquidity_pool.add_liquidity(&accounts(3), 1_005), 1_000);
+
+        liquidity_pool.settle(&taken_over);
+        assert_eq!(liquidity_pool.available_balance, 2_010);
+        assert_eq!(liquidity_pool.pending_withdrawal_amount, 0);
+        assert!(liquidity_pool.pending_withdrawals.is_empty());
+        assert_eq!(liquidity_pool.remove_liquidity(&accounts(2), 1_000), 1_005);
+        assert_eq!(liquidity_pool.get_shares(&accounts(2)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the available balance(505) is less than 1005")]
+    fn test_remove_liquidity_before_settled() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .build());
+        let mut liquidity_pool = LiquidityPool::new();
+        liquidity_pool.fee_basis_points = 100;
+        liquidity_pool.add_liquidity(&accounts(2), 1_000);
+        liquidity_pool.take_over(pending_withdrawal(500));
+        liquidity_pool.remove_liquidity(&accounts(2), 1_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "the available balance(1000) of liquidity pool is less than 1980")]
+    fn test_take_over_more_than_available() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .build());
+        let mut liquidity_pool = LiquidityPool::new();
+        liquidity_pool.fee_basis_points = 100;
+        liquidity_pool.add_liquidity(&accounts(2), 1_000);
+        liquidity_pool.take_over(pending_withdrawal(2_000));
+    }
+
+    #[test]
+    fn test_settle_is_limited() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .build());
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let mut staking_pool = StakingPool::new(pool_id.clone());
+        staking_pool.total_staked_balance = 1_000;
+        staking_pool.batch_unstake(1_000);
+        let submitted_unstake_batch = staking_pool.submit_unstake(4);
+        staking_pool.withdraw_unstake_batch(&submitted_unstake_batch.unstake_batch_id);
+        contract.internal_save_staking_pool(&staking_pool);
+
+        contract.liquidity_pool.add_liquidity(&accounts(2), 1_000);
+        for id in 0..60 {
+            contract.liquidity_pool.take_over(PendingWithdrawal::new(
+                id.into(),
+                pool_id.clone(),
+                10,
+                0,
+                0,
+                accounts(1),
+                false,
+                submitted_unstake_batch.unstake_batch_id,
+            ));
+        }
+
+        let settled_amount = contract.settle_liquidity_pool(u32::MAX);
+        assert_eq!(
+            settled_amount.0,
+            10 * MAX_SETTLE_LIQUIDITY_POOL_LIMIT as Balance
+        );
+        assert_eq!(
+            contract.liquidity_pool.pending_withdrawals.len(),
+            60 - MAX_SETTLE_LIQUIDITY_POOL_LIMIT as u64
+        );
+    }
+}
@@ -1,6 +1,16 @@
 pub mod account;
 pub mod consumer_chain;
+pub mod failed_operation;
+pub mod invariant;
+pub mod liquidity_pool;
+pub mod pause;
 pub mod pending_withdrawal;
+pub mod protocol_config;
+pub mod protocol_fee;
+pub mod protocol_stats;
+pub mod role;
 pub mod slash;
 pub mod staker;
 pub mod staking_pool;
+pub mod timelock;
+pub mod versioned;
@@ -0,0 +1,118 @@
+use crate::*;
+
+/// The category of operations which can be paused independently.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PauseCategory {
+    /// Stake, increase stake, transfer position and add liquidity
+    Stake,
+    /// Decrease stake and unstake
+    Unstake,
+    /// Withdraw pending withdrawals and remove liquidity
+    Withdraw,
+    /// Bond, unbond, change key, manage operators and deposit rewards
+    Bond,
+    /// Blackout, slash request and slash
+    Slash,
+    /// Register, deregister and update consumer chains
+    ConsumerChainRegistration,
+    /// Ping, reconcile and advance unstake batches, settle liquidity pool
+    BatchProcessing,
+}
+
+impl PauseCategory {
+    pub const ALL: [PauseCategory; 7] = [
+        PauseCategory::Stake,
+        PauseCategory::Unstake,
+        PauseCategory::Withdraw,
+        PauseCategory::Bond,
+        PauseCategory::Slash,
+        PauseCategory::ConsumerChainRegistration,
+        PauseCategory::BatchProcessing,
+    ];
+}
+
+/// Whether each category of operations is paused.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseMatrix {
+    pub stake: bool,
+    pub unstake: bool,
+    pub withdraw: bool,
+    pub bond: bool,
+    pub slash: bool,
+    pub consumer_chain_registration: bool,
+    pub batch_processing: bool,
+}
+
+impl PauseMatrix {
+    /// Create a pause matrix in which all categories are paused or running.
+    pub fn new(paused: bool) -> Self {
+        let mut pause_matrix = Self::default();
+        for category in PauseCategory::ALL {
+            pause_matrix.set_paused(category, paused);
+        }
+        pause_matrix
+    }
+
+    fn flag_mut(&mut self, category: PauseCategory) -> &mut bool {
+        match category {
+            PauseCategory::Stake => &mut self.stake,
+            PauseCategory::Unstake => &mut self.unstake,
+            PauseCategory::Withdraw => &mut self.withdraw,
+            PauseCategory::Bond => &mut self.bond,
+            PauseCategory::Slash => &mut self.slash,
+            PauseCategory::ConsumerChainRegistration => &mut self.consumer_chain_registration,
+            PauseCategory::BatchProcessing => &mut self.batch_processing,
+        }
+    }
+
+    pub fn is_paused(&self, category: PauseCategory) -> bool {
+        match category {
+            PauseCategory::Stake => self.stake,
+            PauseCategory::Unstake => self.unstake,
+            PauseCategory::Withdraw => self.withdraw,
+            PauseCategory::Bond => self.bond,
+            PauseCategory::Slash => self.slash,
+            PauseCategory::ConsumerChainRegistration => self.consumer_chain_registration,
+            PauseCategory::BatchProcessing => self.batch_processing,
+        }
+    }
+
+    pub fn is_any_paused(&self) -> bool {
+        PauseCategory::ALL
+            .into_iter()
+            .any(|category| self.is_paused(category))
+    }
+
+    /// Set the flag of category, return whether the flag is changed.
+    pub fn set_paused(&mut self, category: PauseCategory, paused: bool) -> bool {
+        let flag = self.flag_mut(category);
+        let changed = *flag != paused;
+        *flag = paused;
+        changed
+    }
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn assert_not_paused(&self, category: PauseCategory) {
+        assert!(
+            !self.pause_matrix.is_paused(category),
+            "The {:?} operations are paused.",
+            category
+        );
+    }
+
+    pub(crate) fn internal_set_paused(&mut self, category: PauseCategory, paused: bool) {
+        if self.pause_matrix.set_paused(category, paused) {
+            Event::SetPause {
+                category: &category,
+                paused: &paused,
+                operator: &env::predecessor_account_id(),
+            }
+            .emit();
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use std::cmp::max;
+
 use near_sdk::{EpochHeight, Timestamp};
 
-use crate::{types::WithdrawalCertificate, *};
+use crate::{constants::ESTIMATED_EPOCH_DURATION_SECONDS, types::WithdrawalCertificate, *};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, Clone)]
 #[serde(crate = "near_sdk::serde")]
@@ -9,6 +11,8 @@ pub struct PendingWithdrawal {
     pub pool_id: PoolId,
     #[serde(with = "u128_dec_format")]
     pub amount: Balance,
+    /// The unlock epoch estimated in creation, it's refreshed from the unstake batch in views,
+    /// since the batch may be submitted later or delayed by merging.
     #[serde(with = "u64_dec_format")]
     pub unlock_epoch: EpochHeight,
     #[serde(with = "u64_dec_format")]
@@ -69,7 +73,141 @@ impl PendingWithdrawal {
     }
 }
 
+/// The layout of pending withdrawal saved before versioning.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct PendingWithdrawalV0 {
+    pub withdrawal_certificate: WithdrawalCertificate,
+    pub pool_id: PoolId,
+    pub amount: Balance,
+    pub unlock_epoch: EpochHeight,
+    pub unlock_time: Timestamp,
+    pub beneficiary: AccountId,
+    pub allow_other_withdraw: bool,
+    pub unstake_batch_id: Option<UnstakeBatchId>,
+}
+
+impl From<PendingWithdrawalV0> for PendingWithdrawal {
+    fn from(value: PendingWithdrawalV0) -> Self {
+        Self {
+            withdrawal_certificate: value.withdrawal_certificate,
+            pool_id: value.pool_id,
+            amount: value.amount,
+            unlock_epoch: value.unlock_epoch,
+            unlock_time: value.unlock_time,
+            beneficiary: value.beneficiary,
+            allow_other_withdraw: value.allow_other_withdraw,
+            unstake_batch_id: value.unstake_batch_id,
+        }
+    }
+}
+
+pub enum VersionedPendingWithdrawal {
+    V0(PendingWithdrawalV0),
+    V1(PendingWithdrawal),
+}
+
+impl Versioned for VersionedPendingWithdrawal {
+    type Current = PendingWithdrawal;
+    const CURRENT_VERSION: u8 = 1;
+}
+
+impl From<VersionedPendingWithdrawal> for PendingWithdrawal {
+    fn from(value: VersionedPendingWithdrawal) -> Self {
+        match value {
+            VersionedPendingWithdrawal::V0(pending_withdrawal) => pending_withdrawal.into(),
+            VersionedPendingWithdrawal::V1(pending_withdrawal) => pending_withdrawal,
+        }
+    }
+}
+
+impl BorshSerialize for VersionedPendingWithdrawal {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            VersionedPendingWithdrawal::V0(pending_withdrawal) => {
+                BorshSerialize::serialize(pending_withdrawal, writer)
+            }
+            VersionedPendingWithdrawal::V1(pending_withdrawal) => {
+                write_version(writer, 1)?;
+                BorshSerialize::serialize(pending_withdrawal, writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedPendingWithdrawal {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        match read_version(buf)? {
+            None => Ok(VersionedPendingWithdrawal::V0(
+                BorshDeserialize::deserialize(buf)?,
+            )),
+            Some(1) => Ok(VersionedPendingWithdrawal::V1(
+                BorshDeserialize::deserialize(buf)?,
+            )),
+            Some(version) => Err(unknown_version_error(version)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingWithdrawalOverview {
+    pub pending_withdrawal: PendingWithdrawal,
+    /// The epoch in which the near is expected to be withdrawn from staking pool,
+    /// it's None if the unstake batch has been withdrawn.
+    pub unlock_epoch: Option<U64>,
+    pub is_withdrawable: bool,
+    /// The estimated timestamp when the pending withdrawal becomes withdrawable
+    #[serde(with = "u64_dec_format")]
+    pub eta: Timestamp,
+}
+
 impl RestakingBaseContract {
+    pub(crate) fn internal_get_pending_withdrawal_overview(
+        &self,
+        pending_withdrawal: PendingWithdrawal,
+    ) -> PendingWithdrawalOverview {
+        let staking_pool = self.internal_get_staking_pool_or_panic(&pending_withdrawal.pool_id);
+        let unlock_epoch = self.internal_get_unlock_epoch(&staking_pool, &pending_withdrawal);
+        let mut pending_withdrawal = pending_withdrawal;
+        if let Some(unlock_epoch) = unlock_epoch {
+            pending_withdrawal.unlock_epoch = unlock_epoch;
+        }
+        let is_withdrawable = self.internal_is_withdrawable(&staking_pool, &pending_withdrawal);
+        let eta = if is_withdrawable {
+            env::block_timestamp()
+        } else {
+            let remaining_epochs = unlock_epoch
+                .unwrap_or(env::epoch_height())
+                .saturating_sub(env::epoch_height());
+            max(
+                pending_withdrawal.unlock_time,
+                env::block_timestamp()
+                    + seconds_to_nanoseconds(remaining_epochs * ESTIMATED_EPOCH_DURATION_SECONDS),
+            )
+        };
+        PendingWithdrawalOverview {
+            pending_withdrawal,
+            unlock_epoch: unlock_epoch.map(U64),
+            is_withdrawable,
+            eta,
+        }
+    }
+
+    /// Replace the unlock epoch estimated in creation with the one derived from its unstake batch,
+    /// it's kept if the unstake batch has been withdrawn.
+    pub(crate) fn internal_refresh_unlock_epoch(
+        &self,
+        mut pending_withdrawal: PendingWithdrawal,
+    ) -> PendingWithdrawal {
+        let staking_pool = self.internal_get_staking_pool_or_panic(&pending_withdrawal.pool_id);
+        if let Some(unlock_epoch) =
+            self.internal_get_unlock_epoch(&staking_pool, &pending_withdrawal)
+        {
+            pending_withdrawal.unlock_epoch = unlock_epoch;
+        }
+        pending_withdrawal
+    }
+
     pub(crate) fn internal_is_withdrawable(
         &self,
         staking_pool: &StakingPool,
@@ -83,4 +221,21 @@ impl RestakingBaseContract {
             pending_withdrawal.is_withdrawable() && staking_pool.is_withdrawable()
         }
     }
+
+    /// The unlock epoch recorded in creation is an estimation, the unstake batch may be submitted
+    /// later or delayed by merging, so it's derived from the current state of unstake batch.
+    pub(crate) fn internal_get_unlock_epoch(
+        &self,
+        staking_pool: &StakingPool,
+        pending_withdrawal: &PendingWithdrawal,
+    ) -> Option<EpochHeight> {
+        match pending_withdrawal.unstake_batch_id {
+            Some(unstake_batch_id) => staking_pool.get_unstake_batch_unlock_epoch(
+                &unstake_batch_id,
+                self.config.num_epochs_to_unlock,
+            ),
+            // The legacy pending withdrawal is withdrawn once the staking pool is unlocked.
+            None => Some(staking_pool.unlock_epoch),
+        }
+    }
 }
@@ -0,0 +1,432 @@
+use near_sdk::EpochHeight;
+
+use crate::{
+    constants::{
+        gas_constants::{MAX_MIGRATE_TGAS, RESERVED_TGAS},
+        DEFAULT_NUM_EPOCHS_TO_UNLOCK, DEFAULT_REGISTER_STORAGE_FEE, DEFAULT_STORAGE_FEE,
+        MAX_NUM_EPOCHS_TO_UNLOCK, MAX_STORAGE_FEE, MAX_WITHDRAW_ALL_LIMIT,
+    },
+    *,
+};
+
+/// The protocol parameters which can be tuned without redeploying the contract.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProtocolConfig {
+    /// The number of epochs to wait before the unstaked near can be withdrawn from staking pool
+    #[serde(with = "u64_dec_format")]
+    pub num_epochs_to_unlock: EpochHeight,
+    /// The storage fee should be prepaid for the storage used by async actions
+    #[serde(with = "u128_dec_format")]
+    pub storage_fee: Balance,
+    /// The minimum storage deposit for registering an account
+    #[serde(with = "u128_dec_format")]
+    pub register_storage_fee: Balance,
+    /// The prepaid gas of cross contract calls
+    pub gas: GasConfig,
+}
+
+impl Default for ProtocolConfig {
+    fn default() -> Self {
+        Self {
+            num_epochs_to_unlock: DEFAULT_NUM_EPOCHS_TO_UNLOCK,
+            storage_fee: DEFAULT_STORAGE_FEE,
+            register_storage_fee: DEFAULT_REGISTER_STORAGE_FEE,
+            gas: GasConfig::default(),
+        }
+    }
+}
+
+impl ProtocolConfig {
+    pub fn assert_valid(&self) {
+        assert!(
+            (DEFAULT_NUM_EPOCHS_TO_UNLOCK..=MAX_NUM_EPOCHS_TO_UNLOCK)
+                .contains(&self.num_epochs_to_unlock),
+            "The num_epochs_to_unlock({}) should be in range [{}, {}].",
+            self.num_epochs_to_unlock,
+            DEFAULT_NUM_EPOCHS_TO_UNLOCK,
+            MAX_NUM_EPOCHS_TO_UNLOCK
+        );
+        assert!(
+            self.storage_fee > 0 && self.storage_fee <= MAX_STORAGE_FEE,
+            "The storage_fee({}) should be in range (0, {}].",
+            self.storage_fee,
+            MAX_STORAGE_FEE
+        );
+        assert!(
+            self.register_storage_fee >= self.storage_fee
+                && self.register_storage_fee <= MAX_STORAGE_FEE,
+            "The register_storage_fee({}) should be in range [{}, {}].",
+            self.register_storage_fee,
+            self.storage_fee,
+            MAX_STORAGE_FEE
+        );
+        self.gas.assert_valid();
+    }
+}
+
+/// The prepaid gas in tera of cross contract calls and callbacks.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GasConfig {
+    pub select_pool_after_check_whitelist: u64,
+    pub is_whitelisted: u64,
+    pub check_pool_whitelisted_callback: u64,
+    pub ping: u64,
+    pub ping_callback: u64,
+    pub deposit_and_stake: u64,
+    pub get_account_staked_balance: u64,
+    pub get_account_unstaked_balance: u64,
+    pub get_account_total_balance: u64,
+    pub change_key: u64,
+    pub transfer_position: u64,
+    pub transfer_position_callback: u64,
+    pub bond: u64,
+    pub bond_callback: u64,
+    pub unstake: u64,
+    pub unstake_batch_callback: u64,
+    /// The gas used by the callbacks after ping except their own cross contract calls
+    pub after_ping: u64,
+    pub increase_stake_callback: u64,
+    pub decrease_stake_callback: u64,
+    pub unstake_callback: u64,
+    pub withdraw: u64,
+    pub withdraw_unstake_batch_callback: u64,
+    pub single_withdraw_callback: u64,
+    pub withdraw_all_callback: u64,
+    pub reconcile_pool_callback: u64,
+    pub migrate: u64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            select_pool_after_check_whitelist: 130,
+            is_whitelisted: 5,
+            check_pool_whitelisted_callback: 10,
+            ping: 30,
+            ping_callback: 10,
+            deposit_and_stake: 30,
+            get_account_staked_balance: 5,
+            get_account_unstaked_balance: 5,
+            get_account_total_balance: 5,
+            change_key: 30,
+            transfer_position: 10,
+            transfer_position_callback: 30,
+            bond: 50,
+            bond_callback: 10,
+            unstake: 50,
+            unstake_batch_callback: 15,
+            after_ping: 10,
+            increase_stake_callback: 15,
+            decrease_stake_callback: 10,
+            unstake_callback: 50,
+            withdraw: 30,
+            withdraw_unstake_batch_callback: 30,
+            single_withdraw_callback: 10,
+            withdraw_all_callback: 10,
+            reconcile_pool_callback: 10,
+            migrate: 100,
+        }
+    }
+}
+
+impl GasConfig {
+    pub fn increase_stake_after_ping(&self) -> u64 {
+        self.after_ping
+            + self.deposit_and_stake
+            + self.get_account_staked_balance
+            + self.increase_stake_callback
+    }
+
+    pub fn decrease_stake_after_ping(&self) -> u64 {
+        self.after_ping + self.decrease_stake_callback + self.get_account_staked_balance
+    }
+
+    pub fn unstake_after_ping(&self) -> u64 {
+        self.after_ping + self.unstake_callback + self.get_account_staked_balance
+    }
+
+    /// The gas of the ping chained by the staking actions.
+    pub fn ping_with_callback(&self) -> u64 {
+        self.ping + self.get_account_staked_balance + self.ping_callback
+    }
+
+    /// The least gas of each call, the call is likely to run out of gas with less.
+    pub fn minimum() -> Self {
+        Self {
+            select_pool_after_check_whitelist: 10,
+            is_whitelisted: 3,
+            check_pool_whitelisted_callback: 5,
+            ping: 10,
+            ping_callback: 5,
+            deposit_and_stake: 10,
+            get_account_staked_balance: 3,
+            get_account_unstaked_balance: 3,
+            get_account_total_balance: 3,
+            change_key: 10,
+            transfer_position: 5,
+            transfer_position_callback: 10,
+            bond: 10,
+            bond_callback: 5,
+            unstake: 10,
+            unstake_batch_callback: 10,
+            after_ping: 5,
+            increase_stake_callback: 10,
+            decrease_stake_callback: 5,
+            unstake_callback: 10,
+            withdraw: 10,
+            withdraw_unstake_batch_callback: 10,
+            single_withdraw_callback: 5,
+            withdraw_all_callback: 5,
+            reconcile_pool_callback: 5,
+            migrate: 50,
+        }
+    }
+
+    pub fn assert_valid(&self) {
+        let min = Self::minimum();
+        let items = [
+            (
+                "select_pool_after_check_whitelist",
+                self.select_pool_after_check_whitelist,
+                min.select_pool_after_check_whitelist,
+            ),
+            ("is_whitelisted", self.is_whitelisted, min.is_whitelisted),
+            (
+                "check_pool_whitelisted_callback",
+                self.check_pool_whitelisted_callback,
+                min.check_pool_whitelisted_callback,
+            ),
+            ("ping", self.ping, min.ping),
+            ("ping_callback", self.ping_callback, min.ping_callback),
+            (
+                "deposit_and_stake",
+                self.deposit_and_stake,
+                min.deposit_and_stake,
+            ),
+            (
+                "get_account_staked_balance",
+                self.get_account_staked_balance,
+                min.get_account_staked_balance,
+            ),
+            (
+                "get_account_unstaked_balance",
+                self.get_account_unstaked_balance,
+                min.get_account_unstaked_balance,
+            ),
+            (
+                "get_account_total_balance",
+                self.get_account_total_balance,
+                min.get_account_total_balance,
+            ),
+            ("change_key", self.change_key, min.change_key),
+            (
+                "transfer_position",
+                self.transfer_position,
+                min.transfer_position,
+            ),
+            (
+                "transfer_position_callback",
+                self.transfer_position_callback,
+                min.transfer_position_callback,
+            ),
+            ("bond", self.bond, min.bond),
+            ("bond_callback", self.bond_callback, min.bond_callback),
+            ("unstake", self.unstake, min.unstake),
+            (
+                "unstake_batch_callback",
+                self.unstake_batch_callback,
+                min.unstake_batch_callback,
+            ),
+            ("after_ping", self.after_ping, min.after_ping),
+            (
+                "increase_stake_callback",
+                self.increase_stake_callback,
+                min.increase_stake_callback,
+            ),
+            (
+                "decrease_stake_callback",
+                self.decrease_stake_callback,
+                min.decrease_stake_callback,
+            ),
+            (
+                "unstake_callback",
+                self.unstake_callback,
+                min.unstake_callback,
+            ),
+            ("withdraw", self.withdraw, min.withdraw),
+            (
+                "withdraw_unstake_batch_callback",
+                self.withdraw_unstake_batch_callback,
+                min.withdraw_unstake_batch_callback,
+            ),
+            (
+                "single_withdraw_callback",
+                self.single_withdraw_callback,
+                min.single_withdraw_callback,
+            ),
+            (
+                "withdraw_all_callback",
+                self.withdraw_all_callback,
+                min.withdraw_all_callback,
+            ),
+            (
+                "reconcile_pool_callback",
+                self.reconcile_pool_callback,
+                min.reconcile_pool_callback,
+            ),
+        ];
+        for (name, tgas, min_tgas) in items {
+            assert!(
+                (min_tgas..=MAX_TGAS).contains(&tgas),
+                "The gas of {}({} Tgas) should be in range [{}, {}].",
+                name,
+                tgas,
+                min_tgas,
+                MAX_TGAS
+            );
+        }
+        assert!(
+            (min.migrate..=MAX_MIGRATE_TGAS).contains(&self.migrate),
+            "The gas of migrate({} Tgas) should be in range [{}, {}].",
+            self.migrate,
+            min.migrate,
+            MAX_MIGRATE_TGAS
+        );
+
+        // The callback of whitelist check pings and stakes, so it should be prepaid for them.
+        let stake_after_ping = self.ping_with_callback() + self.increase_stake_after_ping();
+        assert!(
+            self.select_pool_after_check_whitelist >= stake_after_ping + RESERVED_TGAS,
+            "The gas of select_pool_after_check_whitelist({} Tgas) should be at least {} Tgas.",
+            self.select_pool_after_check_whitelist,
+            stake_after_ping + RESERVED_TGAS
+        );
+
+        // The calls chained by one action should fit in the gas of a transaction.
+        let chains = [
+            (
+                "stake",
+                self.is_whitelisted + self.select_pool_after_check_whitelist,
+            ),
+            ("increase_stake", stake_after_ping),
+            (
+                "decrease_stake",
+                self.ping_with_callback() + self.decrease_stake_after_ping(),
+            ),
+            (
+                "unstake",
+                self.ping_with_callback() + self.unstake_after_ping(),
+            ),
+            (
+                "bond",
+                self.ping_with_callback() + self.bond + self.bond_callback,
+            ),
+            (
+                "submit_unstake_batch",
+                self.unstake + self.unstake_batch_callback,
+            ),
+            (
+                "withdraw_unstake_batch",
+                self.withdraw + self.withdraw_unstake_batch_callback,
+            ),
+            ("withdraw", self.withdraw + self.single_withdraw_callback),
+            (
+                "withdraw_all",
+                self.withdraw_all_callback * MAX_WITHDRAW_ALL_LIMIT as u64,
+            ),
+            (
+                "reconcile_pool",
+                self.get_account_staked_balance
+                    + self.get_account_unstaked_balance
+                    + self.get_account_total_balance
+                    + self.reconcile_pool_callback,
+            ),
+            (
+                "check_pool_whitelisted",
+                self.is_whitelisted + self.check_pool_whitelisted_callback,
+            ),
+            (
+                "transfer_position",
+                self.transfer_position + self.transfer_position_callback,
+            ),
+        ];
+        for (name, total_tgas) in chains {
+            assert!(
+                total_tgas + RESERVED_TGAS <= MAX_TGAS,
+                "The total gas of calls chained by {}({} Tgas) should not be greater than {}.",
+                name,
+                total_tgas,
+                MAX_TGAS - RESERVED_TGAS
+            );
+        }
+    }
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_update_config(&mut self, config: ProtocolConfig) {
+        config.assert_valid();
+        let old_config = std::mem::replace(&mut self.config, config);
+        Event::UpdateConfig {
+            old_config: &old_config,
+            new_config: &self.config,
+            operator: &env::predecessor_account_id(),
+        }
+        .emit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        ProtocolConfig::default().assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "The num_epochs_to_unlock(3) should be in range [4, 16].")]
+    fn test_num_epochs_to_unlock_less_than_staking_pool() {
+        let mut config = ProtocolConfig::default();
+        config.num_epochs_to_unlock = 3;
+        config.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "The gas of ping_callback(1 Tgas) should be in range [5, 300].")]
+    fn test_gas_less_than_minimum() {
+        let mut config = GasConfig::default();
+        config.ping_callback = 1;
+        config.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "The gas of migrate(300 Tgas) should be in range [50, 150].")]
+    fn test_migrate_gas_leaves_no_gas_to_upgrade() {
+        let mut config = GasConfig::default();
+        config.migrate = 300;
+        config.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The gas of select_pool_after_check_whitelist(130 Tgas) should be at least 145 Tgas."
+    )]
+    fn test_gas_less_than_chained_calls() {
+        let mut config = GasConfig::default();
+        config.deposit_and_stake = 50;
+        config.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The total gas of calls chained by unstake(310 Tgas) should not be greater than 280."
+    )]
+    fn test_chained_calls_exceed_transaction_gas() {
+        let mut config = GasConfig::default();
+        config.unstake_callback = 250;
+        config.assert_valid();
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{constants::MAX_REGISTER_FEE_BASIS_POINTS, *};
+
+/// The cut of protocol fee in basis points from each source of income.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProtocolFee {
+    /// The cut of the fee paid for registering consumer chain
+    pub register_fee_basis_points: u32,
+    /// The cut of the reward deposited by consumer chain for stakers
+    pub reward_fee_basis_points: u32,
+}
+
+impl ProtocolFee {
+    pub fn assert_valid(&self) {
+        assert!(
+            self.register_fee_basis_points <= MAX_REGISTER_FEE_BASIS_POINTS,
+            "The register fee basis points should not greater than {}.",
+            MAX_REGISTER_FEE_BASIS_POINTS
+        );
+        assert!(
+            self.reward_fee_basis_points <= FULL_BASIS_POINTS,
+            "The reward fee basis points should not greater than {}.",
+            FULL_BASIS_POINTS
+        );
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProtocolFeeSource {
+    RegisterFee,
+    Reward,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProtocolFeeInfo {
+    pub protocol_fee: ProtocolFee,
+    pub treasury_account: AccountId,
+    /// The protocol fee accrued and not withdrawn by treasury yet
+    pub accrued_fee: U128,
+}
+
+impl RestakingBaseContract {
+    /// Accrue the cut of protocol fee from the amount, returns the fee.
+    pub(crate) fn internal_accrue_protocol_fee(
+        &mut self,
+        source: ProtocolFeeSource,
+        amount: Balance,
+    ) -> Balance {
+        let basis_points = match source {
+            ProtocolFeeSource::RegisterFee => self.protocol_fee.register_fee_basis_points,
+            ProtocolFeeSource::Reward => self.protocol_fee.reward_fee_basis_points,
+        };
+        let fee = (U256::from(amount) * U256::from(basis_points) / U256::from(FULL_BASIS_POINTS))
+            .as_u128();
+        if fee > 0 {
+            self.protocol_fee_balance += fee;
+            Event::AccrueProtocolFee {
+                source: &source,
+                amount: &fee.into(),
+                protocol_fee_balance: &self.protocol_fee_balance.into(),
+            }
+            .emit();
+        }
+        fee
+    }
+
+    pub(crate) fn assert_protocol_treasury(&self) {
+        assert_eq!(
+            self.protocol_treasury_account,
+            env::predecessor_account_id(),
+            "Predecessor should be protocol treasury account!"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_interface::owner::OwnerAction;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, ONE_NEAR, ONE_YOCTO};
+
+    fn set_context(predecessor_account_id: AccountId, attached_deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(predecessor_account_id)
+            .attached_deposit(attached_deposit)
+            .build());
+    }
+
+    fn transferred_amount(account_id: &AccountId) -> Balance {
+        get_created_receipts()
+            .iter()
+            .filter(|receipt| &receipt.receiver_id == account_id)
+            .flat_map(|receipt| receipt.actions.iter())
+            .map(|action| match action {
+                VmAction::Transfer { deposit } => *deposit,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    #[should_panic(expected = "The register fee basis points should not greater than 5000.")]
+    fn test_register_fee_basis_points_exceeds_max() {
+        ProtocolFee {
+            register_fee_basis_points: MAX_REGISTER_FEE_BASIS_POINTS + 1,
+            reward_fee_basis_points: 0,
+        }
+        .assert_valid();
+    }
+
+    #[test]
+    fn test_accrue_and_withdraw_protocol_fee() {
+        let treasury = accounts(0);
+        let governance = accounts(1);
+        let staker_id = accounts(2);
+        let pos_account = accounts(3);
+        set_context(treasury.clone(), 0);
+        let mut contract = RestakingBaseContract::new(
+            treasury.clone(),
+            (10 * ONE_NEAR).into(),
+            accounts(4),
+            0.into(),
+        );
+        contract.protocol_fee = ProtocolFee {
+            register_fee_basis_points: 1_000,
+            reward_fee_basis_points: 500,
+        };
+
+        // The fee is only cut from the part of register fee not used by storage.
+        set_context(governance.clone(), 10 * ONE_NEAR);
+        let consumer_chain_id = "test:test".to_string();
+        contract.register_consumer_chain(ConsumerChainRegisterParam {
+            consumer_chain_id: consumer_chain_id.clone(),
+            cc_pos_account: pos_account.clone(),
+            unbonding_period: 60,
+            website: "website".to_string(),
+            treasury: governance,
+        });
+        let register_fee = contract.protocol_fee_balance;
+        assert!(register_fee > 0 && register_fee < ONE_NEAR);
+
+        let mut staker = Staker::new(staker_id.clone());
+        staker.bond(&consumer_chain_id, 60);
+        contract.internal_save_staker(&staker_id, &staker);
+        set_context(pos_account, 1000);
+        let reward = contract.deposit_reward(consumer_chain_id, staker_id.clone());
+        assert_eq!(reward.0, 950);
+        assert_eq!(transferred_amount(&staker_id), 950);
+        assert_eq!(contract.protocol_fee_balance, register_fee + 50);
+
+        set_context(treasury.clone(), ONE_YOCTO);
+        let withdrawn = contract.withdraw_protocol_fee(None);
+        assert_eq!(withdrawn.0, register_fee + 50);
+        assert_eq!(transferred_amount(&treasury), register_fee + 50);
+        assert_eq!(contract.protocol_fee_balance, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The Bond operations are paused.")]
+    fn test_deposit_reward_when_bond_paused() {
+        set_context(accounts(0), 0);
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        contract.pause_matrix.set_paused(PauseCategory::Bond, true);
+        set_context(accounts(3), 1000);
+        contract.deposit_reward("test:test".to_string(), accounts(2));
+    }
+}
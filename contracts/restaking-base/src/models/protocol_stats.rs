@@ -0,0 +1,75 @@
+use crate::*;
+
+/// The stats of a page of staking pools and a page of consumer chains,
+/// the totals of protocol are the sums of all pages.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProtocolStats {
+    /// Total staked near in the staking pools of page
+    pub total_staked_balance: U128,
+    /// Sum of bonded stake in the consumer chains of page, the stake bonded to
+    /// several consumer chains is counted once per chain
+    pub total_restaked_value: U128,
+    pub stake_per_pool: Vec<(PoolId, U128)>,
+    /// Total staked near and unstaking near not claimed yet in the staking pools of page,
+    /// the liquidity pool and keeper fund are counted in the first page
+    pub tvl: U128,
+    /// Whether there are more staking pools or consumer chains after the page
+    pub has_more: bool,
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_get_protocol_stats(&self, from_index: u32, limit: u32) -> ProtocolStats {
+        let mut total_staked_balance: Balance = 0;
+        let mut total_unstaking_balance: Balance = 0;
+        let mut stake_per_pool = vec![];
+        for staking_pool in self
+            .staking_pools
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+        {
+            total_staked_balance += staking_pool.total_staked_balance;
+            total_unstaking_balance += staking_pool.batched_unstake_amount
+                + staking_pool
+                    .submitted_unstake_batches
+                    .values()
+                    .map(|submitted_unstake_batch| {
+                        submitted_unstake_batch.total_unstake_amount
+                            - submitted_unstake_batch.claimed_amount
+                    })
+                    .sum::<Balance>();
+            stake_per_pool.push((
+                staking_pool.pool_id.clone(),
+                staking_pool.total_staked_balance.into(),
+            ));
+        }
+
+        let total_restaked_value: Balance = self
+            .consumer_chains
+            .values()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .flat_map(|consumer_chain| {
+                consumer_chain.get_bonded_stake_per_pool(&self.staking_pools)
+            })
+            .map(|(_, staked_amount)| staked_amount)
+            .sum();
+
+        let other_balance = if from_index == 0 {
+            self.liquidity_pool.available_balance + self.keeper_fund
+        } else {
+            0
+        };
+        let next_index = from_index as u64 + limit as u64;
+
+        ProtocolStats {
+            total_staked_balance: total_staked_balance.into(),
+            total_restaked_value: total_restaked_value.into(),
+            stake_per_pool,
+            tvl: (total_staked_balance + total_unstaking_balance + other_balance).into(),
+            has_more: next_index < self.staking_pools.len()
+                || next_index < self.consumer_chains.len(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::*;
+
+/// The role which can perform a group of admin actions,
+/// the owner is the super admin who can grant and revoke roles.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// Can pause and resume the contract
+    PauseGuardian,
+    /// Can set the fees, the guarantee and the keeper reward
+    FeeManager,
+    /// Can override the beneficiary of pending withdrawal and the unbonding unlock time of staker,
+    /// and reject slash requests
+    SlashArbiter,
+    /// Can unlock and resume staking pools and set the shortfall pause threshold
+    PoolOperator,
+    /// Can upgrade the contract
+    Upgrader,
+    /// Can update the protocol config and the timelock delay
+    ConfigManager,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountRoles {
+    pub account_id: AccountId,
+    pub roles: Vec<Role>,
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_has_role(&self, account_id: &AccountId, role: Role) -> bool {
+        self.owner.eq(account_id)
+            || self
+                .roles
+                .get(account_id)
+                .map(|roles| roles.contains(&role))
+                .unwrap_or(false)
+    }
+
+    pub(crate) fn assert_role(&self, role: Role) {
+        assert!(
+            self.internal_has_role(&env::predecessor_account_id(), role),
+            "Predecessor should be owner or have role {:?}!",
+            role
+        );
+    }
+
+    pub(crate) fn internal_grant_role(&mut self, account_id: &AccountId, role: Role) -> bool {
+        let mut roles = self.roles.get(account_id).unwrap_or_default();
+        if roles.contains(&role) {
+            return false;
+        }
+        roles.push(role);
+        roles.sort();
+        self.roles.insert(account_id, &roles);
+        true
+    }
+
+    pub(crate) fn internal_revoke_role(&mut self, account_id: &AccountId, role: Role) -> bool {
+        let mut roles = self.roles.get(account_id).unwrap_or_default();
+        if !roles.contains(&role) {
+            return false;
+        }
+        roles.retain(|r| *r != role);
+        if roles.is_empty() {
+            self.roles.remove(account_id);
+        } else {
+            self.roles.insert(account_id, &roles);
+        }
+        true
+    }
+}
@@ -26,9 +26,31 @@ impl RestakingBaseContract {
             .expect(format!("Failed to get slash.").as_str())
     }
 
+    pub(crate) fn internal_save_slash(&mut self, slash_id: &SlashId, slash: &Slash) {
+        self.slashes.insert(slash_id, slash);
+        for (staker_id, _) in &slash.slash_items {
+            let mut slash_ids = self.staker_slashes.get(staker_id).unwrap_or_default();
+            slash_ids.push(*slash_id);
+            self.staker_slashes.insert(staker_id, &slash_ids);
+        }
+    }
+
     pub(crate) fn internal_remove_slash(&mut self, slash_id: &SlashId) {
         let slash = self.get_slash_or_panic(slash_id);
         self.slashes.remove(slash_id);
+        self.internal_use_consumer_chain_or_panic(&slash.consumer_chain_id, |consumer_chain| {
+            consumer_chain.pending_slash_count =
+                consumer_chain.pending_slash_count.saturating_sub(1)
+        });
+        for (staker_id, _) in &slash.slash_items {
+            let mut slash_ids = self.staker_slashes.get(staker_id).unwrap_or_default();
+            slash_ids.retain(|id| id != slash_id);
+            if slash_ids.is_empty() {
+                self.staker_slashes.remove(staker_id);
+            } else {
+                self.staker_slashes.insert(staker_id, &slash_ids);
+            }
+        }
 
         let submitter = self
             .internal_get_consumer_chain_or_panic(&slash.consumer_chain_id)
@@ -36,3 +58,92 @@ impl RestakingBaseContract {
         self.transfer_near(submitter, slash.slash_guarantee.0);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, ONE_YOCTO};
+
+    const SLASH_GUARANTEE: Balance = 100;
+
+    fn set_context(predecessor_account_id: AccountId, attached_deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(predecessor_account_id)
+            .attached_deposit(attached_deposit)
+            .build());
+    }
+
+    /// The staker(accounts(4)) is bonding in consumer chain, whose governance is accounts(1)
+    /// and pos account is accounts(2), and a slash is requested against the staker.
+    fn setup_slash_requested() -> (RestakingBaseContract, ConsumerChainId, SlashId) {
+        set_context(accounts(0), 0);
+        let mut contract =
+            RestakingBaseContract::new(accounts(0), 0.into(), accounts(3), SLASH_GUARANTEE.into());
+        let consumer_chain_id = "test:test".to_string();
+        contract.consumer_chains.insert(
+            &consumer_chain_id,
+            &ConsumerChain::new_from_register_param(
+                ConsumerChainRegisterParam {
+                    consumer_chain_id: consumer_chain_id.clone(),
+                    cc_pos_account: accounts(2),
+                    unbonding_period: 60,
+                    website: "website".to_string(),
+                    treasury: accounts(1),
+                },
+                accounts(1),
+                0,
+            ),
+        );
+        let staker_id = accounts(4);
+        let mut staker = Staker::new(staker_id.clone());
+        staker.bond(&consumer_chain_id, 60);
+        contract.internal_save_staker(&staker_id, &staker);
+
+        set_context(accounts(2), SLASH_GUARANTEE);
+        let slash_id = contract.slash_request(
+            consumer_chain_id.clone(),
+            vec![(staker_id, 10.into())],
+            "hash".to_string(),
+        );
+        (contract, consumer_chain_id, slash_id)
+    }
+
+    #[test]
+    fn test_reject_slash() {
+        let (mut contract, consumer_chain_id, slash_id) = setup_slash_requested();
+        assert!(contract.staker_slashes.get(&accounts(4)).is_some());
+
+        set_context(accounts(1), ONE_YOCTO);
+        contract.reject_slash(consumer_chain_id.clone(), slash_id);
+        assert!(contract.slashes.get(&slash_id).is_none());
+        assert!(contract.staker_slashes.get(&accounts(4)).is_none());
+        assert_eq!(
+            contract
+                .internal_get_consumer_chain_or_panic(&consumer_chain_id)
+                .pending_slash_count,
+            0
+        );
+        // The slash guarantee is refunded to pos account.
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == accounts(2))
+            .unwrap();
+        assert!(matches!(
+            receipt.actions[0],
+            VmAction::Transfer {
+                deposit: SLASH_GUARANTEE
+            }
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not consumer chain governance")]
+    fn test_reject_slash_by_others() {
+        let (mut contract, consumer_chain_id, slash_id) = setup_slash_requested();
+        set_context(accounts(5), ONE_YOCTO);
+        contract.reject_slash(consumer_chain_id, slash_id);
+    }
+}
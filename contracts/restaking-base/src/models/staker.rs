@@ -1,5 +1,7 @@
 use std::cmp::max;
 
+use crate::constants::DEFAULT_VIEW_LIMIT;
+use crate::models::pending_withdrawal::PendingWithdrawalOverview;
 use crate::types::{DurationOfSeconds, PoolId, Sequence, ShareBalance};
 use crate::*;
 use near_sdk::Timestamp;
@@ -19,6 +21,10 @@ pub struct Staker {
     pub unbonding_unlock_time: Timestamp,
     /// Record unbonding time of consumer chains
     pub unbonding_consumer_chains: UnorderedMap<ConsumerChainId, Timestamp>,
+    /// The operator who can bond, change key and unbond in any consumer chain
+    pub global_operator: Option<AccountId>,
+    /// The map from consumer chain id to the operator who can bond, change key and unbond in it
+    pub consumer_chain_operators: UnorderedMap<ConsumerChainId, AccountId>,
 }
 
 impl Staker {
@@ -37,9 +43,22 @@ impl Staker {
                     staker_id: staker_id.clone(),
                 },
             ),
+            global_operator: None,
+            consumer_chain_operators: UnorderedMap::new(StorageKey::StakerConsumerChainOperators {
+                staker_id: staker_id.clone(),
+            }),
         }
     }
 
+    pub fn is_operator(&self, account_id: &AccountId, consumer_chain_id: &ConsumerChainId) -> bool {
+        self.global_operator.as_ref() == Some(account_id)
+            || self
+                .consumer_chain_operators
+                .get(consumer_chain_id)
+                .as_ref()
+                == Some(account_id)
+    }
+
     pub fn bond(
         &mut self,
         consumer_chain_id: &ConsumerChainId,
@@ -117,6 +136,48 @@ impl Staker {
         }
     }
 
+    pub fn has_position(&self) -> bool {
+        self.shares > 0
+            || self.select_staking_pool.is_some()
+            || !self.bonding_consumer_chains.is_empty()
+            || self.unbonding_unlock_time > env::block_timestamp()
+    }
+
+    /// Take out shares, selected pool, bonding and unbonding state of staker.
+    /// The operators are authorized by staker itself, so they are not taken.
+    pub fn take_position(&mut self) -> StakerPosition {
+        let position = StakerPosition {
+            select_staking_pool: self.select_staking_pool.take(),
+            shares: std::mem::take(&mut self.shares),
+            bonding_consumer_chains: self.bonding_consumer_chains.to_vec(),
+            max_bonding_unlock_period: std::mem::take(&mut self.max_bonding_unlock_period),
+            unbonding_unlock_time: self.unbonding_unlock_time,
+            unbonding_consumer_chains: self.unbonding_consumer_chains.to_vec(),
+        };
+        self.unbonding_unlock_time = env::block_timestamp();
+        self.bonding_consumer_chains.clear();
+        self.unbonding_consumer_chains.clear();
+        position
+    }
+
+    pub fn put_position(&mut self, position: StakerPosition) {
+        self.select_staking_pool = position.select_staking_pool;
+        self.shares = position.shares;
+        self.max_bonding_unlock_period = position.max_bonding_unlock_period;
+        self.unbonding_unlock_time = position.unbonding_unlock_time;
+
+        self.bonding_consumer_chains.clear();
+        for (consumer_chain_id, unbonding_period) in position.bonding_consumer_chains {
+            self.bonding_consumer_chains
+                .insert(&consumer_chain_id, &unbonding_period);
+        }
+        self.unbonding_consumer_chains.clear();
+        for (consumer_chain_id, unlock_timestamp) in position.unbonding_consumer_chains {
+            self.unbonding_consumer_chains
+                .insert(&consumer_chain_id, &unlock_timestamp);
+        }
+    }
+
     pub fn get_unlock_time(&self) -> Timestamp {
         max(
             self.unbonding_unlock_time,
@@ -136,6 +197,87 @@ impl Staker {
     }
 }
 
+/// The layout of staker saved before versioning.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct StakerV0 {
+    pub staker_id: StakerId,
+    pub select_staking_pool: Option<PoolId>,
+    pub shares: ShareBalance,
+    pub bonding_consumer_chains: UnorderedMap<ConsumerChainId, DurationOfSeconds>,
+    pub max_bonding_unlock_period: DurationOfSeconds,
+    pub unbonding_unlock_time: Timestamp,
+    pub unbonding_consumer_chains: UnorderedMap<ConsumerChainId, Timestamp>,
+}
+
+impl From<StakerV0> for Staker {
+    fn from(value: StakerV0) -> Self {
+        Self {
+            staker_id: value.staker_id.clone(),
+            select_staking_pool: value.select_staking_pool,
+            shares: value.shares,
+            bonding_consumer_chains: value.bonding_consumer_chains,
+            max_bonding_unlock_period: value.max_bonding_unlock_period,
+            unbonding_unlock_time: value.unbonding_unlock_time,
+            unbonding_consumer_chains: value.unbonding_consumer_chains,
+            global_operator: None,
+            consumer_chain_operators: UnorderedMap::new(StorageKey::StakerConsumerChainOperators {
+                staker_id: value.staker_id,
+            }),
+        }
+    }
+}
+
+pub enum VersionedStaker {
+    V0(StakerV0),
+    V1(Staker),
+}
+
+impl Versioned for VersionedStaker {
+    type Current = Staker;
+    const CURRENT_VERSION: u8 = 1;
+}
+
+impl From<VersionedStaker> for Staker {
+    fn from(value: VersionedStaker) -> Self {
+        match value {
+            VersionedStaker::V0(staker) => staker.into(),
+            VersionedStaker::V1(staker) => staker,
+        }
+    }
+}
+
+impl BorshSerialize for VersionedStaker {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            VersionedStaker::V0(staker) => BorshSerialize::serialize(staker, writer),
+            VersionedStaker::V1(staker) => {
+                write_version(writer, 1)?;
+                BorshSerialize::serialize(staker, writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedStaker {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        match read_version(buf)? {
+            None => Ok(VersionedStaker::V0(BorshDeserialize::deserialize(buf)?)),
+            Some(1) => Ok(VersionedStaker::V1(BorshDeserialize::deserialize(buf)?)),
+            Some(version) => Err(unknown_version_error(version)),
+        }
+    }
+}
+
+/// The part of staker which can be transferred to another staker.
+pub struct StakerPosition {
+    pub select_staking_pool: Option<PoolId>,
+    pub shares: ShareBalance,
+    pub bonding_consumer_chains: Vec<(ConsumerChainId, DurationOfSeconds)>,
+    pub max_bonding_unlock_period: DurationOfSeconds,
+    pub unbonding_unlock_time: Timestamp,
+    pub unbonding_consumer_chains: Vec<(ConsumerChainId, Timestamp)>,
+}
+
 impl RestakingBaseContract {
     pub(crate) fn internal_get_staker_or_panic(&self, staker_id: &StakerId) -> Staker {
         self.stakers
@@ -144,7 +286,7 @@ impl RestakingBaseContract {
     }
 
     pub(crate) fn internal_save_staker(&mut self, staker_id: &StakerId, staker: &Staker) {
-        self.stakers.insert(staker_id, &staker);
+        self.stakers.insert(staker_id, staker);
     }
 
     pub(crate) fn internal_use_staker_or_panic<F, R>(&mut self, staker_id: &StakerId, mut f: F) -> R
@@ -162,9 +304,33 @@ impl RestakingBaseContract {
         let pool = self.internal_get_staking_pool_by_staker_or_panic(staker_id);
         return pool.staked_amount_from_shares_balance_rounded_down(staker.shares);
     }
+
+    /// Returns the staker who the restaking action is for, the predecessor should be
+    /// the staker itself or its operator.
+    pub(crate) fn internal_get_restaking_staker_id(
+        &self,
+        staker_id: Option<StakerId>,
+        consumer_chain_id: &ConsumerChainId,
+    ) -> StakerId {
+        let predecessor_account_id = env::predecessor_account_id();
+        match staker_id {
+            Some(staker_id) if staker_id != predecessor_account_id => {
+                let staker = self.internal_get_staker_or_panic(&staker_id);
+                assert!(
+                    staker.is_operator(&predecessor_account_id, consumer_chain_id),
+                    "The predecessor_account_id({}) is not operator of {} in {}",
+                    predecessor_account_id,
+                    staker_id,
+                    consumer_chain_id
+                );
+                staker_id
+            }
+            _ => predecessor_account_id,
+        }
+    }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StakerInfo {
     pub staker_id: StakerId,
@@ -172,6 +338,8 @@ pub struct StakerInfo {
     pub shares: U128,
     pub max_bonding_unlock_period: U64,
     pub unbonding_unlock_time: U64,
+    pub global_operator: Option<AccountId>,
+    pub consumer_chain_operators: Vec<(ConsumerChainId, AccountId)>,
 }
 
 impl From<&Staker> for StakerInfo {
@@ -182,6 +350,103 @@ impl From<&Staker> for StakerInfo {
             shares: value.shares.into(),
             max_bonding_unlock_period: value.max_bonding_unlock_period.into(),
             unbonding_unlock_time: value.unbonding_unlock_time.into(),
+            global_operator: value.global_operator.clone(),
+            consumer_chain_operators: value.consumer_chain_operators.to_vec(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakerOverview {
+    pub staker_id: StakerId,
+    pub select_staking_pool: Option<PoolId>,
+    pub shares: U128,
+    pub staked_balance: U128,
+    /// The bonding consumer chains with their unbonding periods in seconds
+    pub bonding_consumer_chains: Vec<(ConsumerChainId, U64)>,
+    /// The unbonding consumer chains with their unlock timestamps
+    pub unbonding_consumer_chains: Vec<(ConsumerChainId, U64)>,
+    /// The pending withdrawals in the requested page
+    pub pending_withdrawals: Vec<PendingWithdrawalOverview>,
+    /// Whether there are more pending withdrawals after the requested page
+    pub has_more_pending_withdrawals: bool,
+    pub open_slashes: Vec<(SlashId, Slash)>,
+    /// Whether the staker is blacklisted by each consumer chain it's bonding or unbonding
+    pub blacklist_status: Vec<(ConsumerChainId, bool)>,
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_get_staker_overview(
+        &self,
+        staker: &Staker,
+        from_index: u32,
+        limit: u32,
+    ) -> StakerOverview {
+        let staked_balance = match &staker.select_staking_pool {
+            Some(_) => self.get_staker_staked_balance(&staker.staker_id),
+            None => 0,
+        };
+        let (pending_withdrawals, has_more_pending_withdrawals) = self
+            .accounts
+            .get(&staker.staker_id)
+            .map(|account| {
+                let pending_withdrawals = account
+                    .pending_withdrawals
+                    .values()
+                    .skip(from_index as usize)
+                    .take(limit as usize)
+                    .map(|pending_withdrawal| {
+                        self.internal_get_pending_withdrawal_overview(pending_withdrawal)
+                    })
+                    .collect_vec();
+                let has_more =
+                    (from_index as u64 + limit as u64) < account.pending_withdrawals.len();
+                (pending_withdrawals, has_more)
+            })
+            .unwrap_or_default();
+        let open_slashes = self
+            .staker_slashes
+            .get(&staker.staker_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|slash_id| self.slashes.get(&slash_id).map(|slash| (slash_id, slash)))
+            .collect_vec();
+        // Only the consumer chains of staker are checked, scanning all of them is unbounded.
+        let blacklist_status = staker
+            .bonding_consumer_chains
+            .keys()
+            .chain(staker.unbonding_consumer_chains.keys())
+            .unique()
+            .take(DEFAULT_VIEW_LIMIT as usize)
+            .filter_map(|consumer_chain_id| self.consumer_chains.get(&consumer_chain_id))
+            .map(|consumer_chain| {
+                let is_blacklisted = consumer_chain.blacklist.contains(&staker.staker_id);
+                (consumer_chain.consumer_chain_id, is_blacklisted)
+            })
+            .collect_vec();
+
+        StakerOverview {
+            staker_id: staker.staker_id.clone(),
+            select_staking_pool: staker.select_staking_pool.clone(),
+            shares: staker.shares.into(),
+            staked_balance: staked_balance.into(),
+            bonding_consumer_chains: staker
+                .bonding_consumer_chains
+                .iter()
+                .map(|(consumer_chain_id, unbonding_period)| {
+                    (consumer_chain_id, unbonding_period.into())
+                })
+                .collect_vec(),
+            unbonding_consumer_chains: staker
+                .unbonding_consumer_chains
+                .iter()
+                .map(|(consumer_chain_id, unlock_time)| (consumer_chain_id, unlock_time.into()))
+                .collect_vec(),
+            pending_withdrawals,
+            has_more_pending_withdrawals,
+            open_slashes,
+            blacklist_status,
         }
     }
 }
@@ -1,6 +1,8 @@
+use crate::constants::{STAKING_POOL_LOCK_EXPIRY_BLOCKS, STAKING_POOL_ROUNDING_TOLERANCE};
 use crate::types::{ShareBalance, U256};
 use crate::*;
-use near_sdk::{Balance, EpochHeight};
+use near_sdk::{Balance, BlockHeight, EpochHeight};
+use std::cmp::max;
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct StakingPool {
@@ -12,19 +14,27 @@ pub struct StakingPool {
     /// The set of all stakers' ids
     pub stakers: UnorderedSet<AccountId>,
     /// When restaking base contract interactive with staking pool contract, it'll lock this staking pool until all cross contract call finished
-    pub locked: bool,
+    pub lock: Option<StakingPoolLock>,
     /// Record staking pool unlock epoch
     pub unlock_epoch: EpochHeight,
     /// Last epoch for calling unstake method in staking pool.
     pub last_unstake_epoch: EpochHeight,
-    /// Last unstake batch id, it'll be None if it's initial or withdrawn.
+    /// Last unstake batch id, it'll be None if it's initial or all submitted batches are withdrawn.
     pub last_unstake_batch_id: Option<UnstakeBatchId>,
     pub current_unstake_batch_id: UnstakeBatchId,
     pub batched_unstake_amount: u128,
     pub submitted_unstake_batches: UnorderedMap<UnstakeBatchId, SubmittedUnstakeBatch>,
+    /// Total amount of submitted unstake batches which haven't been withdrawn from staking pool.
+    pub in_flight_unstake_amount: u128,
+    /// The staking pool is paused when a shortfall over threshold is found by reconciling,
+    /// staking changes and submitting unstake batch are refused until owner resumes it.
+    pub paused: bool,
+    /// The staking pool is deprecated when it's removed from whitelist,
+    /// stakers can only decrease stake or unstake from a deprecated staking pool.
+    pub deprecated: bool,
 }
 
-#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug)]
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct SubmittedUnstakeBatch {
     pub unstake_batch_id: UnstakeBatchId,
@@ -37,6 +47,60 @@ pub struct SubmittedUnstakeBatch {
     pub is_withdrawn: bool,
 }
 
+/// The operation which locks the staking pool until its cross contract calls are finished.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StakingPoolOperation {
+    Stake { staker_id: StakerId, amount: U128 },
+    IncreaseStake { staker_id: StakerId, amount: U128 },
+    DecreaseStake,
+    Unstake,
+    SubmitUnstakeBatch,
+    WithdrawUnstakeBatch { unstake_batch_id: UnstakeBatchId },
+    Reconcile,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakingPoolLock {
+    /// The account which starts the operation
+    pub owner: AccountId,
+    pub operation: StakingPoolOperation,
+    /// The callbacks of the operation only take effect if the staking pool is still locked by this nonce.
+    #[serde(with = "u64_dec_format")]
+    pub nonce: u64,
+    /// The lock is stale after this block height, then anyone can clear it.
+    #[serde(with = "u64_dec_format")]
+    pub expire_block_height: BlockHeight,
+}
+
+/// The result of comparing the balances in staking pool contract with the internally tracked ones.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PoolReconciliation {
+    pub pool_id: PoolId,
+    pub staked_balance: U128,
+    pub unstaked_balance: U128,
+    pub total_balance: U128,
+    /// The total staked balance tracked before reconciling, including batched unstake amount
+    pub expected_staked_balance: U128,
+    /// The amount of submitted unstake batches which haven't been withdrawn
+    pub expected_unstaked_balance: U128,
+    pub staked_shortfall: U128,
+    pub unstaked_shortfall: U128,
+    pub total_shortfall: U128,
+    pub paused: bool,
+}
+
+/// The unstake batch work of a staking pool which can be advanced by anyone.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeeperWork {
+    pub pool_id: PoolId,
+    pub is_unstake_batch_submittable: bool,
+    pub withdrawable_unstake_batch_ids: Vec<UnstakeBatchId>,
+}
+
 #[derive(Serialize, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StakingPoolInfo {
@@ -46,6 +110,7 @@ pub struct StakingPoolInfo {
     #[serde(with = "u128_dec_format")]
     pub total_staked_balance: Balance,
     pub locked: bool,
+    pub lock: Option<StakingPoolLock>,
     #[serde(with = "u64_dec_format")]
     pub unlock_epoch: EpochHeight,
     #[serde(with = "u64_dec_format")]
@@ -55,6 +120,10 @@ pub struct StakingPoolInfo {
     #[serde(with = "u128_dec_format")]
     pub batched_unstake_amount: u128,
     pub submitted_unstake_batches_count: u32,
+    #[serde(with = "u128_dec_format")]
+    pub in_flight_unstake_amount: u128,
+    pub paused: bool,
+    pub deprecated: bool,
 }
 
 impl From<&mut StakingPool> for StakingPoolInfo {
@@ -63,13 +132,17 @@ impl From<&mut StakingPool> for StakingPoolInfo {
             pool_id: value.pool_id.clone(),
             total_share_balance: value.total_share_balance,
             total_staked_balance: value.total_staked_balance,
-            locked: value.locked,
+            locked: value.is_locked(),
+            lock: value.lock.clone(),
             unlock_epoch: value.unlock_epoch,
             last_unstake_epoch: value.last_unstake_epoch,
             last_unstake_batch_id: value.last_unstake_batch_id,
             current_unstake_batch_id: value.current_unstake_batch_id,
             batched_unstake_amount: value.batched_unstake_amount,
             submitted_unstake_batches_count: value.submitted_unstake_batches.len() as u32,
+            in_flight_unstake_amount: value.in_flight_unstake_amount,
+            paused: value.paused,
+            deprecated: value.deprecated,
         }
     }
 }
@@ -80,13 +153,17 @@ impl From<StakingPool> for StakingPoolInfo {
             pool_id: value.pool_id,
             total_share_balance: value.total_share_balance,
             total_staked_balance: value.total_staked_balance,
-            locked: value.locked,
+            locked: value.lock.is_some(),
+            lock: value.lock,
             unlock_epoch: value.unlock_epoch,
             last_unstake_epoch: value.last_unstake_epoch,
             last_unstake_batch_id: value.last_unstake_batch_id,
             current_unstake_batch_id: value.current_unstake_batch_id,
             batched_unstake_amount: value.batched_unstake_amount,
             submitted_unstake_batches_count: value.submitted_unstake_batches.len() as u32,
+            in_flight_unstake_amount: value.in_flight_unstake_amount,
+            paused: value.paused,
+            deprecated: value.deprecated,
         }
     }
 }
@@ -99,8 +176,9 @@ pub struct StakingPoolDetail {
     pub total_share_balance: ShareBalance,
     #[serde(with = "u128_dec_format")]
     pub total_staked_balance: Balance,
-    pub stakers: Vec<AccountId>,
+    pub stakers_count: u32,
     pub locked: bool,
+    pub lock: Option<StakingPoolLock>,
     pub unlock_epoch: EpochHeight,
     #[serde(with = "u64_dec_format")]
     pub last_unstake_epoch: EpochHeight,
@@ -108,7 +186,11 @@ pub struct StakingPoolDetail {
     pub current_unstake_batch_id: UnstakeBatchId,
     #[serde(with = "u128_dec_format")]
     pub batched_unstake_amount: u128,
-    pub submitted_unstake_batches: Vec<SubmittedUnstakeBatch>,
+    pub submitted_unstake_batches_count: u32,
+    #[serde(with = "u128_dec_format")]
+    pub in_flight_unstake_amount: u128,
+    pub paused: bool,
+    pub deprecated: bool,
 }
 
 impl From<StakingPool> for StakingPoolDetail {
@@ -117,14 +199,18 @@ impl From<StakingPool> for StakingPoolDetail {
             pool_id: value.pool_id,
             total_share_balance: value.total_share_balance,
             total_staked_balance: value.total_staked_balance,
-            stakers: value.stakers.iter().collect_vec(),
-            locked: value.locked,
+            stakers_count: value.stakers.len() as u32,
+            locked: value.lock.is_some(),
+            lock: value.lock,
             unlock_epoch: value.unlock_epoch,
             last_unstake_epoch: value.last_unstake_epoch,
             last_unstake_batch_id: value.last_unstake_batch_id,
             current_unstake_batch_id: value.current_unstake_batch_id,
             batched_unstake_amount: value.batched_unstake_amount,
-            submitted_unstake_batches: value.submitted_unstake_batches.values().collect_vec(),
+            submitted_unstake_batches_count: value.submitted_unstake_batches.len() as u32,
+            in_flight_unstake_amount: value.in_flight_unstake_amount,
+            paused: value.paused,
+            deprecated: value.deprecated,
         }
     }
 }
@@ -138,7 +224,7 @@ impl StakingPool {
             stakers: UnorderedSet::new(StorageKey::StakingPoolStakers {
                 pool_id: pool_id.clone(),
             }),
-            locked: false,
+            lock: None,
             unlock_epoch: 0,
 
             last_unstake_epoch: 0,
@@ -148,12 +234,40 @@ impl StakingPool {
             submitted_unstake_batches: UnorderedMap::new(StorageKey::SubmittedUnstakeBatches {
                 pool_id: pool_id.clone(),
             }),
+            in_flight_unstake_amount: 0,
+            paused: false,
+            deprecated: false,
         };
         pool
     }
 
+    /// The staking pool resets the unlock epoch of all unstaked balance on every `unstake`,
+    /// so submitting a batch while others are in flight delays them as well.
+    /// Submit when nothing is in flight, or when merging is cheaper than waiting.
     pub fn is_able_submit_unstake_batch(&self) -> bool {
-        self.batched_unstake_amount > 0 && self.last_unstake_batch_id.is_none()
+        if self.batched_unstake_amount == 0 {
+            return false;
+        }
+        if self.last_unstake_batch_id.is_none() {
+            return true;
+        }
+        // The in flight batches are unlocked, they should be withdrawn before locking them again.
+        if self.is_withdrawable() {
+            return false;
+        }
+        // Stakers are leaving a deprecated staking pool, don't hold their unstake requests.
+        self.deprecated || self.is_merge_cheaper_than_waiting()
+    }
+
+    /// Compare the amount weighted epochs of delay between:
+    /// 1. merging: the in flight batches are delayed by the epochs elapsed since last unstake.
+    /// 2. waiting: the current batch is delayed by the epochs remaining until in flight batches unlock.
+    pub fn is_merge_cheaper_than_waiting(&self) -> bool {
+        let current_epoch = env::epoch_height();
+        let elapsed_epochs = current_epoch.saturating_sub(self.last_unstake_epoch);
+        let remaining_epochs = self.unlock_epoch.saturating_sub(current_epoch);
+        U256::from(self.in_flight_unstake_amount) * U256::from(elapsed_epochs)
+            <= U256::from(self.batched_unstake_amount) * U256::from(remaining_epochs)
     }
 
     pub fn is_unstake_batch_withdrawable(&self, unstake_batch_id: &UnstakeBatchId) -> bool {
@@ -161,9 +275,57 @@ impl StakingPool {
             .submitted_unstake_batches
             .get(&unstake_batch_id)
             .unwrap();
-        submitted_unstake_batch.is_withdrawn == false
-            && submitted_unstake_batch.submit_unstake_epoch + NUM_EPOCHS_TO_UNLOCK
-                <= env::epoch_height()
+        submitted_unstake_batch.is_withdrawn == false && self.is_withdrawable()
+    }
+
+    pub fn get_keeper_work(&self) -> Option<KeeperWork> {
+        if self.is_locked() || self.paused {
+            return None;
+        }
+        let withdrawable_unstake_batch_ids = self
+            .submitted_unstake_batches
+            .keys()
+            .filter(|unstake_batch_id| self.is_unstake_batch_withdrawable(unstake_batch_id))
+            .collect_vec();
+        let is_unstake_batch_submittable = self.is_able_submit_unstake_batch();
+        if !is_unstake_batch_submittable && withdrawable_unstake_batch_ids.is_empty() {
+            return None;
+        }
+        Some(KeeperWork {
+            pool_id: self.pool_id.clone(),
+            is_unstake_batch_submittable,
+            withdrawable_unstake_batch_ids,
+        })
+    }
+
+    /// Returns the epoch in which the unstake batch is expected to be withdrawable from staking pool.
+    pub fn get_unstake_batch_unlock_epoch(
+        &self,
+        unstake_batch_id: &UnstakeBatchId,
+        num_epochs_to_unlock: EpochHeight,
+    ) -> Option<EpochHeight> {
+        let current_epoch = env::epoch_height();
+        if let Some(submitted_unstake_batch) = self.submitted_unstake_batches.get(unstake_batch_id)
+        {
+            return if submitted_unstake_batch.is_withdrawn {
+                None
+            } else {
+                // All in flight batches share the unlock epoch of staking pool.
+                Some(self.unlock_epoch)
+            };
+        }
+        if unstake_batch_id.0 != self.current_unstake_batch_id.0 {
+            return None;
+        }
+        if self.last_unstake_batch_id.is_none()
+            || self.deprecated
+            || self.is_merge_cheaper_than_waiting()
+        {
+            Some(current_epoch + num_epochs_to_unlock)
+        } else {
+            // The current batch will be submitted once the in flight batches are withdrawn.
+            Some(max(self.unlock_epoch, current_epoch) + num_epochs_to_unlock)
+        }
     }
 
     pub fn remain_staked_balance(&self) -> Balance {
@@ -177,11 +339,12 @@ impl StakingPool {
         self.current_unstake_batch_id.clone()
     }
 
+    /// Claim the amount from a withdrawn unstake batch, returns the unstake batch after claimed.
     pub fn withdraw_from_unstake_batch(
         &mut self,
         amount: Balance,
         unstake_batch_id: UnstakeBatchId,
-    ) {
+    ) -> SubmittedUnstakeBatch {
         let mut submitted_unstake_batch = self
             .submitted_unstake_batches
             .get(&unstake_batch_id)
@@ -198,9 +361,33 @@ impl StakingPool {
             self.submitted_unstake_batches
                 .insert(&unstake_batch_id, &submitted_unstake_batch);
         }
+        submitted_unstake_batch
     }
 
-    pub fn submit_unstake(&mut self) -> SubmittedUnstakeBatch {
+    /// Return the amount claimed by `withdraw_from_unstake_batch` if the near is not paid out,
+    /// the unstake batch is restored from the claimed one if it has been fully claimed and removed.
+    pub fn rollback_withdraw_from_unstake_batch(
+        &mut self,
+        amount: Balance,
+        claimed_unstake_batch: SubmittedUnstakeBatch,
+    ) {
+        let unstake_batch_id = claimed_unstake_batch.unstake_batch_id;
+        let mut submitted_unstake_batch = self
+            .submitted_unstake_batches
+            .get(&unstake_batch_id)
+            .unwrap_or(SubmittedUnstakeBatch {
+                claimed_amount: claimed_unstake_batch.total_unstake_amount,
+                ..claimed_unstake_batch
+            });
+        submitted_unstake_batch.claimed_amount = submitted_unstake_batch
+            .claimed_amount
+            .checked_sub(amount)
+            .expect("Failed to rollback claimed amount of unstake batch");
+        self.submitted_unstake_batches
+            .insert(&unstake_batch_id, &submitted_unstake_batch);
+    }
+
+    pub fn submit_unstake(&mut self, num_epochs_to_unlock: EpochHeight) -> SubmittedUnstakeBatch {
         let submitted_unstake_batch = SubmittedUnstakeBatch {
             unstake_batch_id: self.current_unstake_batch_id,
             submit_unstake_epoch: env::epoch_height(),
@@ -222,9 +409,10 @@ impl StakingPool {
         self.last_unstake_epoch = env::epoch_height();
         self.last_unstake_batch_id = Some(self.current_unstake_batch_id.clone());
         self.current_unstake_batch_id = (self.current_unstake_batch_id.0 + 1).into();
+        self.in_flight_unstake_amount += self.batched_unstake_amount;
         self.batched_unstake_amount = 0;
 
-        self.unlock_epoch = env::epoch_height() + NUM_EPOCHS_TO_UNLOCK;
+        self.unlock_epoch = env::epoch_height() + num_epochs_to_unlock;
 
         submitted_unstake_batch
     }
@@ -237,15 +425,143 @@ impl StakingPool {
         submitted_unstake_batch.is_withdrawn = true;
         self.submitted_unstake_batches
             .insert(&unstake_batch_id, &submitted_unstake_batch);
+
+        self.in_flight_unstake_amount = self
+            .in_flight_unstake_amount
+            .checked_sub(submitted_unstake_batch.total_unstake_amount)
+            .expect("Failed to decrease in flight unstake amount");
+        if self.in_flight_unstake_amount == 0 {
+            self.last_unstake_batch_id = None;
+        }
     }
 
-    pub fn lock(&mut self) {
-        assert!(!self.locked, "The staking pool has been already locked!");
-        self.locked = true;
+    pub fn lock(&mut self, owner: AccountId, operation: StakingPoolOperation, nonce: u64) {
+        assert!(
+            self.lock.is_none(),
+            "The staking pool has been already locked!"
+        );
+        self.lock = Some(StakingPoolLock {
+            owner,
+            operation,
+            nonce,
+            expire_block_height: env::block_height() + STAKING_POOL_LOCK_EXPIRY_BLOCKS,
+        });
     }
 
     pub fn unlock(&mut self) {
-        self.locked = false;
+        self.lock = None;
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    /// The lock may have been cleared as stale or by operator before the callback of its operation.
+    pub fn is_locked_by(&self, nonce: u64) -> bool {
+        self.lock.as_ref().is_some_and(|lock| lock.nonce == nonce)
+    }
+
+    /// The lock is stale if its cross contract calls are not finished before expiry,
+    /// e.g. the callback is failed by running out of gas.
+    pub fn is_lock_stale(&self) -> bool {
+        self.lock
+            .as_ref()
+            .is_some_and(|lock| lock.expire_block_height < env::block_height())
+    }
+
+    /// Returns whether the operation of stale lock is done by the balances in staking pool contract,
+    /// or None if the balances are close to neither outcome, e.g. inflated by rewards.
+    pub fn is_stale_operation_done(
+        &self,
+        stale_lock: &StakingPoolLock,
+        staked_balance: Balance,
+        unstaked_balance: Balance,
+    ) -> Option<bool> {
+        match &stale_lock.operation {
+            StakingPoolOperation::Stake { amount, .. }
+            | StakingPoolOperation::IncreaseStake { amount, .. } => compare_with_outcomes(
+                staked_balance,
+                self.total_staked_balance + amount.0,
+                self.total_staked_balance,
+            ),
+            StakingPoolOperation::SubmitUnstakeBatch => compare_with_outcomes(
+                unstaked_balance,
+                self.in_flight_unstake_amount + self.batched_unstake_amount,
+                self.in_flight_unstake_amount,
+            ),
+            StakingPoolOperation::WithdrawUnstakeBatch { unstake_batch_id } => {
+                let total_unstake_amount = self
+                    .submitted_unstake_batches
+                    .get(unstake_batch_id)
+                    .filter(|submitted_unstake_batch| !submitted_unstake_batch.is_withdrawn)
+                    .map_or(0, |submitted_unstake_batch| {
+                        submitted_unstake_batch.total_unstake_amount
+                    });
+                compare_with_outcomes(
+                    unstaked_balance,
+                    self.in_flight_unstake_amount
+                        .saturating_sub(total_unstake_amount),
+                    self.in_flight_unstake_amount,
+                )
+            }
+            _ => Some(false),
+        }
+    }
+
+    pub fn assert_not_deprecated(&self) {
+        assert!(
+            !self.deprecated,
+            "The staking pool({}) is deprecated, it's removed from whitelist, please unstake and stake into another pool.",
+            self.pool_id
+        );
+    }
+
+    pub fn assert_not_paused(&self) {
+        assert!(
+            !self.paused,
+            "The staking pool({}) is paused.",
+            self.pool_id
+        );
+    }
+
+    /// Compare the balances in staking pool contract with the tracked ones, then refresh
+    /// the staked balance. The pool is paused if the total shortfall is greater than
+    /// `threshold_basis_points` of the expected total balance.
+    pub fn reconcile(
+        &mut self,
+        staked_balance: Balance,
+        unstaked_balance: Balance,
+        total_balance: Balance,
+        threshold_basis_points: u32,
+    ) -> PoolReconciliation {
+        let expected_staked_balance = self.total_staked_balance;
+        let expected_unstaked_balance = self.in_flight_unstake_amount;
+        let expected_total_balance = expected_staked_balance + expected_unstaked_balance;
+        let total_shortfall = expected_total_balance.saturating_sub(total_balance);
+
+        if U256::from(total_shortfall) * U256::from(FULL_BASIS_POINTS)
+            > U256::from(expected_total_balance) * U256::from(threshold_basis_points)
+        {
+            self.paused = true;
+        }
+        self.total_staked_balance = staked_balance;
+
+        PoolReconciliation {
+            pool_id: self.pool_id.clone(),
+            staked_balance: staked_balance.into(),
+            unstaked_balance: unstaked_balance.into(),
+            total_balance: total_balance.into(),
+            expected_staked_balance: expected_staked_balance.into(),
+            expected_unstaked_balance: expected_unstaked_balance.into(),
+            staked_shortfall: expected_staked_balance
+                .saturating_sub(staked_balance)
+                .into(),
+            unstaked_shortfall: expected_unstaked_balance
+                .saturating_sub(unstaked_balance)
+                .into(),
+            total_shortfall: total_shortfall.into(),
+            paused: self.paused,
+        }
     }
 
     pub fn is_withdrawable(&self) -> bool {
@@ -390,6 +706,118 @@ impl StakingPool {
     }
 }
 
+/// Compare the observed balance with the expected ones if the operation is done or not,
+/// returns None if it's within the rounding tolerance of neither or both of them.
+fn compare_with_outcomes(
+    observed_balance: Balance,
+    balance_if_done: Balance,
+    balance_if_not_done: Balance,
+) -> Option<bool> {
+    if balance_if_done == balance_if_not_done {
+        return Some(false);
+    }
+    let is_done = observed_balance.abs_diff(balance_if_done) <= STAKING_POOL_ROUNDING_TOLERANCE;
+    let is_not_done =
+        observed_balance.abs_diff(balance_if_not_done) <= STAKING_POOL_ROUNDING_TOLERANCE;
+    (is_done != is_not_done).then_some(is_done)
+}
+
+/// The layout of staking pool saved before versioning.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct StakingPoolV0 {
+    pub pool_id: AccountId,
+    pub total_share_balance: ShareBalance,
+    pub total_staked_balance: Balance,
+    pub stakers: UnorderedSet<AccountId>,
+    pub locked: bool,
+    pub unlock_epoch: EpochHeight,
+    pub last_unstake_epoch: EpochHeight,
+    pub last_unstake_batch_id: Option<UnstakeBatchId>,
+    pub current_unstake_batch_id: UnstakeBatchId,
+    pub batched_unstake_amount: u128,
+    pub submitted_unstake_batches: UnorderedMap<UnstakeBatchId, SubmittedUnstakeBatch>,
+}
+
+impl From<StakingPoolV0> for StakingPool {
+    fn from(value: StakingPoolV0) -> Self {
+        let in_flight_unstake_amount = value
+            .submitted_unstake_batches
+            .values()
+            .filter(|unstake_batch| !unstake_batch.is_withdrawn)
+            .map(|unstake_batch| unstake_batch.total_unstake_amount)
+            .sum();
+        Self {
+            pool_id: value.pool_id,
+            total_share_balance: value.total_share_balance,
+            total_staked_balance: value.total_staked_balance,
+            stakers: value.stakers,
+            // The lock can be cleared by anyone, then the staking pool is reconciled.
+            lock: value.locked.then(|| StakingPoolLock {
+                owner: env::current_account_id(),
+                operation: StakingPoolOperation::Reconcile,
+                nonce: 0,
+                expire_block_height: env::block_height(),
+            }),
+            unlock_epoch: value.unlock_epoch,
+            last_unstake_epoch: value.last_unstake_epoch,
+            last_unstake_batch_id: value.last_unstake_batch_id,
+            current_unstake_batch_id: value.current_unstake_batch_id,
+            batched_unstake_amount: value.batched_unstake_amount,
+            submitted_unstake_batches: value.submitted_unstake_batches,
+            in_flight_unstake_amount,
+            paused: false,
+            deprecated: false,
+        }
+    }
+}
+
+pub enum VersionedStakingPool {
+    V0(StakingPoolV0),
+    V1(StakingPool),
+}
+
+impl Versioned for VersionedStakingPool {
+    type Current = StakingPool;
+    const CURRENT_VERSION: u8 = 1;
+}
+
+impl From<VersionedStakingPool> for StakingPool {
+    fn from(value: VersionedStakingPool) -> Self {
+        match value {
+            VersionedStakingPool::V0(staking_pool) => staking_pool.into(),
+            VersionedStakingPool::V1(staking_pool) => staking_pool,
+        }
+    }
+}
+
+impl BorshSerialize for VersionedStakingPool {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            VersionedStakingPool::V0(staking_pool) => {
+                BorshSerialize::serialize(staking_pool, writer)
+            }
+            VersionedStakingPool::V1(staking_pool) => {
+                write_version(writer, 1)?;
+                BorshSerialize::serialize(staking_pool, writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedStakingPool {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        match read_version(buf)? {
+            None => Ok(VersionedStakingPool::V0(BorshDeserialize::deserialize(
+                buf,
+            )?)),
+            Some(1) => Ok(VersionedStakingPool::V1(BorshDeserialize::deserialize(
+                buf,
+            )?)),
+            Some(version) => Err(unknown_version_error(version)),
+        }
+    }
+}
+
 impl RestakingBaseContract {
     pub(crate) fn internal_get_staking_pool_or_panic(&self, pool_id: &PoolId) -> StakingPool {
         self.staking_pools
@@ -399,7 +827,7 @@ impl RestakingBaseContract {
 
     pub(crate) fn internal_save_staking_pool(&mut self, staking_pool: &StakingPool) {
         self.staking_pools
-            .insert(&staking_pool.pool_id, &staking_pool);
+            .insert(&staking_pool.pool_id, staking_pool);
     }
 
     pub(crate) fn internal_use_staker_staking_pool_or_panic<F, R>(
@@ -440,3 +868,160 @@ impl RestakingBaseContract {
             .expect(format!("Failed to get staking pool by {}", staker_id).as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    const NUM_EPOCHS_TO_UNLOCK: EpochHeight = 4;
+
+    /// A staking pool whose in flight batch is submitted in epoch 10 and unlocked in epoch 14.
+    fn staking_pool_with_in_flight_batch(
+        in_flight_unstake_amount: Balance,
+        batched_unstake_amount: Balance,
+    ) -> StakingPool {
+        let mut staking_pool = StakingPool::new("pool.near".parse().unwrap());
+        staking_pool.last_unstake_epoch = 10;
+        staking_pool.unlock_epoch = 10 + NUM_EPOCHS_TO_UNLOCK;
+        staking_pool.last_unstake_batch_id = Some(0.into());
+        staking_pool.current_unstake_batch_id = 1.into();
+        staking_pool.in_flight_unstake_amount = in_flight_unstake_amount;
+        staking_pool.batched_unstake_amount = batched_unstake_amount;
+        staking_pool
+    }
+
+    fn set_epoch_height(epoch_height: EpochHeight) {
+        testing_env!(VMContextBuilder::new().epoch_height(epoch_height).build());
+    }
+
+    #[test]
+    fn test_submit_without_in_flight_batch() {
+        set_epoch_height(10);
+        let mut staking_pool = StakingPool::new("pool.near".parse().unwrap());
+        assert!(!staking_pool.is_able_submit_unstake_batch());
+        staking_pool.batched_unstake_amount = 100;
+        assert!(staking_pool.is_able_submit_unstake_batch());
+        assert_eq!(
+            staking_pool.get_unstake_batch_unlock_epoch(&0.into(), NUM_EPOCHS_TO_UNLOCK),
+            Some(10 + NUM_EPOCHS_TO_UNLOCK)
+        );
+    }
+
+    #[test]
+    fn test_merge_when_cheaper_than_waiting() {
+        // Merging delays 100 by 1 epoch, waiting delays 100 by 3 epochs.
+        set_epoch_height(11);
+        let staking_pool = staking_pool_with_in_flight_batch(100, 100);
+        assert!(staking_pool.is_merge_cheaper_than_waiting());
+        assert!(staking_pool.is_able_submit_unstake_batch());
+        assert_eq!(
+            staking_pool.get_unstake_batch_unlock_epoch(&1.into(), NUM_EPOCHS_TO_UNLOCK),
+            Some(11 + NUM_EPOCHS_TO_UNLOCK)
+        );
+    }
+
+    #[test]
+    fn test_wait_when_cheaper_than_merging() {
+        // Merging delays 300 by 3 epochs, waiting delays 100 by 1 epoch.
+        set_epoch_height(13);
+        let mut staking_pool = staking_pool_with_in_flight_batch(300, 100);
+        assert!(!staking_pool.is_merge_cheaper_than_waiting());
+        assert!(!staking_pool.is_able_submit_unstake_batch());
+        assert_eq!(
+            staking_pool.get_unstake_batch_unlock_epoch(&1.into(), NUM_EPOCHS_TO_UNLOCK),
+            Some(14 + NUM_EPOCHS_TO_UNLOCK)
+        );
+
+        // Stakers are not held when leaving a deprecated staking pool.
+        staking_pool.deprecated = true;
+        assert!(staking_pool.is_able_submit_unstake_batch());
+    }
+
+    #[test]
+    fn test_get_keeper_work() {
+        set_epoch_height(13);
+        let mut staking_pool = staking_pool_with_in_flight_batch(300, 100);
+        staking_pool.submitted_unstake_batches.insert(
+            &0.into(),
+            &SubmittedUnstakeBatch {
+                unstake_batch_id: 0.into(),
+                submit_unstake_epoch: 10,
+                total_unstake_amount: 300,
+                claimed_amount: 0,
+                is_withdrawn: false,
+            },
+        );
+        assert!(staking_pool.get_keeper_work().is_none());
+
+        set_epoch_height(14);
+        let keeper_work = staking_pool.get_keeper_work().unwrap();
+        assert!(!keeper_work.is_unstake_batch_submittable);
+        assert_eq!(keeper_work.withdrawable_unstake_batch_ids, vec![0.into()]);
+
+        staking_pool.lock(
+            "keeper.near".parse().unwrap(),
+            StakingPoolOperation::WithdrawUnstakeBatch {
+                unstake_batch_id: 0.into(),
+            },
+            1,
+        );
+        assert!(staking_pool.get_keeper_work().is_none());
+    }
+
+    #[test]
+    fn test_not_merge_unlocked_batch() {
+        set_epoch_height(14);
+        let staking_pool = staking_pool_with_in_flight_batch(100, 1_000);
+        assert!(staking_pool.is_withdrawable());
+        assert!(!staking_pool.is_able_submit_unstake_batch());
+    }
+
+    #[test]
+    fn test_reconcile_shortfall() {
+        set_epoch_height(10);
+        let mut staking_pool = staking_pool_with_in_flight_batch(200, 0);
+        staking_pool.total_staked_balance = 1_000;
+
+        // The staked balance lost 50 and the unstaked balance lost 10.
+        let pool_reconciliation = staking_pool.reconcile(950, 190, 1_140, 1_000);
+        assert_eq!(pool_reconciliation.expected_staked_balance.0, 1_000);
+        assert_eq!(pool_reconciliation.expected_unstaked_balance.0, 200);
+        assert_eq!(pool_reconciliation.staked_shortfall.0, 50);
+        assert_eq!(pool_reconciliation.unstaked_shortfall.0, 10);
+        assert_eq!(pool_reconciliation.total_shortfall.0, 60);
+        assert!(!pool_reconciliation.paused);
+        assert_eq!(staking_pool.total_staked_balance, 950);
+
+        // The rewards are not counted as shortfall.
+        let pool_reconciliation = staking_pool.reconcile(1_000, 200, 1_200, 0);
+        assert_eq!(pool_reconciliation.total_shortfall.0, 0);
+        assert!(!pool_reconciliation.paused);
+        assert_eq!(staking_pool.total_staked_balance, 1_000);
+    }
+
+    #[test]
+    fn test_reconcile_pause_above_threshold() {
+        set_epoch_height(10);
+        let mut staking_pool = StakingPool::new("pool.near".parse().unwrap());
+        staking_pool.total_staked_balance = 10_000;
+
+        // The shortfall equal to threshold doesn't pause the pool.
+        let pool_reconciliation = staking_pool.reconcile(9_900, 0, 9_900, 100);
+        assert_eq!(pool_reconciliation.total_shortfall.0, 100);
+        assert!(!pool_reconciliation.paused);
+        assert!(!staking_pool.paused);
+
+        staking_pool.total_staked_balance = 10_000;
+        let pool_reconciliation = staking_pool.reconcile(9_899, 0, 9_899, 100);
+        assert_eq!(pool_reconciliation.total_shortfall.0, 101);
+        assert!(pool_reconciliation.paused);
+        assert!(staking_pool.paused);
+
+        // The pool is kept paused until it's resumed.
+        let pool_reconciliation = staking_pool.reconcile(9_899, 0, 9_899, 100);
+        assert_eq!(pool_reconciliation.total_shortfall.0, 0);
+        assert!(pool_reconciliation.paused);
+    }
+}
@@ -0,0 +1,173 @@
+use near_sdk::Timestamp;
+
+use crate::*;
+
+/// The sensitive owner action which is executed only after the timelock delay.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TimelockAction {
+    SetWithdrawalBeneficiary {
+        account_id: AccountId,
+        withdraw_certificate: WithdrawalCertificate,
+        new_beneficiary: AccountId,
+    },
+    SetSlashGuarantee {
+        new_slash_guarantee: U128,
+    },
+    SetStakerUnbondingUnlockTimeAsCurrentTime {
+        staker_id: StakerId,
+    },
+    SetTimelockDelay {
+        delay_seconds: DurationOfSeconds,
+    },
+}
+
+impl TimelockAction {
+    /// The role which can schedule and cancel the action.
+    pub fn required_role(&self) -> Role {
+        match self {
+            TimelockAction::SetWithdrawalBeneficiary { .. }
+            | TimelockAction::SetStakerUnbondingUnlockTimeAsCurrentTime { .. } => {
+                Role::SlashArbiter
+            }
+            TimelockAction::SetSlashGuarantee { .. } => Role::FeeManager,
+            TimelockAction::SetTimelockDelay { .. } => Role::ConfigManager,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingAction {
+    pub id: U64,
+    pub action: TimelockAction,
+    pub proposer: AccountId,
+    /// The action can be executed after this timestamp
+    #[serde(with = "u64_dec_format")]
+    pub executable_at: Timestamp,
+}
+
+impl RestakingBaseContract {
+    pub(crate) fn internal_schedule_action(&mut self, action: TimelockAction) {
+        self.assert_role(action.required_role());
+        let pending_action = PendingAction {
+            id: self.next_uuid().into(),
+            action,
+            proposer: env::predecessor_account_id(),
+            executable_at: env::block_timestamp() + seconds_to_nanoseconds(self.timelock_delay),
+        };
+        self.pending_actions
+            .insert(&pending_action.id.0, &pending_action);
+        Event::ScheduleAction {
+            pending_action: &pending_action,
+        }
+        .emit();
+    }
+
+    pub(crate) fn internal_take_pending_action_or_panic(&mut self, id: U64) -> PendingAction {
+        self.pending_actions
+            .remove(&id.0)
+            .expect(format!("Failed to get pending action by {}", id.0).as_str())
+    }
+
+    /// Returns false if the target of action has gone during the timelock delay,
+    /// e.g. the pending withdrawal has been withdrawn, then the action is dropped without change.
+    pub(crate) fn internal_execute_action(&mut self, action: TimelockAction) -> bool {
+        match action {
+            TimelockAction::SetWithdrawalBeneficiary {
+                account_id,
+                withdraw_certificate,
+                new_beneficiary,
+            } => {
+                let pending_withdrawal = self
+                    .accounts
+                    .get(&account_id)
+                    .and_then(|account| account.pending_withdrawals.get(&withdraw_certificate));
+                if pending_withdrawal.is_none() {
+                    return false;
+                }
+                self.internal_use_account(&account_id, |account| {
+                    let mut pending_withdrawal = account
+                        .pending_withdrawals
+                        .get(&withdraw_certificate)
+                        .unwrap();
+                    pending_withdrawal.beneficiary = new_beneficiary.clone();
+                    account
+                        .pending_withdrawals
+                        .insert(&withdraw_certificate, &pending_withdrawal);
+                });
+            }
+            TimelockAction::SetSlashGuarantee {
+                new_slash_guarantee,
+            } => {
+                self.slash_guarantee = new_slash_guarantee.into();
+            }
+            TimelockAction::SetStakerUnbondingUnlockTimeAsCurrentTime { staker_id } => {
+                if self.stakers.get(&staker_id).is_none() {
+                    return false;
+                }
+                self.internal_use_staker_or_panic(&staker_id, |staker| {
+                    staker.unbonding_unlock_time = env::block_timestamp();
+                });
+            }
+            TimelockAction::SetTimelockDelay { delay_seconds } => {
+                self.timelock_delay = delay_seconds;
+                Event::SetTimelockDelay {
+                    timelock_delay: &delay_seconds,
+                }
+                .emit();
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_interface::owner::OwnerAction;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, ONE_YOCTO};
+
+    fn set_context(predecessor_account_id: AccountId, block_timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(predecessor_account_id)
+            .attached_deposit(ONE_YOCTO)
+            .block_timestamp(block_timestamp)
+            .build());
+    }
+
+    #[test]
+    fn test_execute_action_after_pending_withdrawal_gone() {
+        set_context(accounts(0), 0);
+        let mut contract = RestakingBaseContract::new(accounts(0), 0.into(), accounts(1), 0.into());
+        let staker_id = accounts(2);
+        let mut account = Account::new(staker_id.clone());
+        account.pending_withdrawals.insert(
+            &1.into(),
+            &PendingWithdrawal::new(
+                1.into(),
+                "pool.near".parse().unwrap(),
+                100,
+                1,
+                0,
+                staker_id.clone(),
+                false,
+                0.into(),
+            ),
+        );
+        contract.internal_save_account(&staker_id, &account);
+        contract.set_withdrawal_beneficiary(staker_id.clone(), 1.into(), accounts(3));
+        let pending_action = contract.pending_actions.values().next().unwrap();
+
+        // The pending withdrawal is withdrawn during the timelock delay.
+        contract.internal_use_account(&staker_id, |account| {
+            account.pending_withdrawals.remove(&1.into());
+        });
+
+        set_context(accounts(4), pending_action.executable_at);
+        contract.execute_pending_action(pending_action.id);
+        assert!(contract.pending_actions.is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use std::io::{Error, ErrorKind, Result, Write};
+
+use near_sdk::IntoStorageKey;
+
+use crate::*;
+
+/// The header written before every versioned value. Values saved before versioning are never
+/// started with it, because they are started with a length of id, map or a small uuid.
+const VERSIONED_HEADER: [u8; 4] = [u8::MAX; 4];
+
+/// The entity saved in state in any of its versions, it's upgraded to the current version when read.
+/// The legacy version saved before versioning is untagged, the other versions are tagged
+/// with `VERSIONED_HEADER` and version number.
+pub trait Versioned: BorshSerialize + BorshDeserialize + Into<Self::Current> {
+    type Current: BorshSerialize;
+
+    const CURRENT_VERSION: u8;
+
+    fn serialize_current(current: &Self::Current) -> Vec<u8> {
+        let mut buf = vec![];
+        write_version(&mut buf, Self::CURRENT_VERSION).unwrap();
+        BorshSerialize::serialize(current, &mut buf).unwrap();
+        buf
+    }
+}
+
+/// Read the version of value, it's None if the value is saved before versioning.
+pub(crate) fn read_version(buf: &mut &[u8]) -> Result<Option<u8>> {
+    if !buf.starts_with(&VERSIONED_HEADER) {
+        return Ok(None);
+    }
+    *buf = &buf[VERSIONED_HEADER.len()..];
+    <u8 as BorshDeserialize>::deserialize(buf).map(Some)
+}
+
+pub(crate) fn write_version<W: Write>(writer: &mut W, version: u8) -> Result<()> {
+    writer.write_all(&VERSIONED_HEADER)?;
+    BorshSerialize::serialize(&version, writer)
+}
+
+pub(crate) fn unknown_version_error(version: u8) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Unknown version({}) of state.", version),
+    )
+}
+
+/// The lookup map whose values are saved in any version and read in current version.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct VersionedLookupMap<K, V> {
+    map: LookupMap<K, V>,
+}
+
+impl<K, V> VersionedLookupMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize,
+    V: Versioned,
+{
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        Self {
+            map: LookupMap::new(prefix),
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn get(&self, key: &K) -> Option<V::Current> {
+        self.map.get(key).map(Into::into)
+    }
+
+    pub fn insert(&mut self, key: &K, value: &V::Current) -> Option<V::Current> {
+        self.map
+            .insert_raw(&key.try_to_vec().unwrap(), &V::serialize_current(value))
+            .map(|raw| V::try_from_slice(&raw).unwrap().into())
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V::Current> {
+        self.map.remove(key).map(Into::into)
+    }
+}
+
+/// The unordered map whose values are saved in any version and read in current version.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct VersionedUnorderedMap<K, V> {
+    map: UnorderedMap<K, V>,
+}
+
+impl<K, V> VersionedUnorderedMap<K, V>
+where
+    K: BorshSerialize + BorshDeserialize,
+    V: Versioned,
+{
+    pub fn new<S: IntoStorageKey>(prefix: S) -> Self {
+        Self {
+            map: UnorderedMap::new(prefix),
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn get(&self, key: &K) -> Option<V::Current> {
+        self.map.get(key).map(Into::into)
+    }
+
+    pub fn insert(&mut self, key: &K, value: &V::Current) -> Option<V::Current> {
+        self.map
+            .insert_raw(&key.try_to_vec().unwrap(), &V::serialize_current(value))
+            .map(|raw| V::try_from_slice(&raw).unwrap().into())
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V::Current> {
+        self.map.remove(key).map(Into::into)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.map.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = V::Current> + '_ {
+        self.map.values().map(Into::into)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (K, V::Current)> + '_ {
+        self.map.iter().map(|(key, value)| (key, value.into()))
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::DEFAULT_REGISTER_STORAGE_FEE;
+    use crate::models::account::AccountV0;
+    use crate::models::pending_withdrawal::PendingWithdrawalV0;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+    use std::collections::HashMap;
+
+    /// Save the borsh encoded value of legacy version, as it's saved before versioning.
+    fn insert_v0<K: BorshSerialize, V: BorshSerialize>(
+        map: &mut LookupMap<K, impl Versioned>,
+        key: &K,
+        value: &V,
+    ) {
+        map.insert_raw(&key.try_to_vec().unwrap(), &value.try_to_vec().unwrap());
+    }
+
+    fn pending_withdrawal_v0(pool_id: &PoolId, beneficiary: &AccountId) -> PendingWithdrawalV0 {
+        PendingWithdrawalV0 {
+            withdrawal_certificate: 1.into(),
+            pool_id: pool_id.clone(),
+            amount: 100,
+            unlock_epoch: 10,
+            unlock_time: 20,
+            beneficiary: beneficiary.clone(),
+            allow_other_withdraw: true,
+            unstake_batch_id: Some(2.into()),
+        }
+    }
+
+    #[test]
+    fn test_read_v0_values_in_lookup_map() {
+        testing_env!(VMContextBuilder::new().build());
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+        let staker_id = accounts(1);
+
+        let mut stakers: VersionedLookupMap<AccountId, VersionedStaker> =
+            VersionedLookupMap::new(b"s".to_vec());
+        insert_v0(
+            &mut stakers.map,
+            &staker_id,
+            &StakerV0 {
+                staker_id: staker_id.clone(),
+                select_staking_pool: Some(pool_id.clone()),
+                shares: 100,
+                bonding_consumer_chains: UnorderedMap::new(b"b".to_vec()),
+                max_bonding_unlock_period: 30,
+                unbonding_unlock_time: 40,
+                unbonding_consumer_chains: UnorderedMap::new(b"u".to_vec()),
+            },
+        );
+        let staker = stakers.get(&staker_id).unwrap();
+        assert_eq!(staker.select_staking_pool, Some(pool_id.clone()));
+        assert_eq!(staker.shares, 100);
+        assert_eq!(staker.max_bonding_unlock_period, 30);
+        assert_eq!(staker.unbonding_unlock_time, 40);
+        assert!(staker.global_operator.is_none());
+
+        let mut pending_withdrawals: VersionedUnorderedMap<
+            WithdrawalCertificate,
+            VersionedPendingWithdrawal,
+        > = VersionedUnorderedMap::new(b"p".to_vec());
+        let pending_withdrawal = pending_withdrawal_v0(&pool_id, &staker_id);
+        pending_withdrawals.map.insert_raw(
+            &pending_withdrawal
+                .withdrawal_certificate
+                .try_to_vec()
+                .unwrap(),
+            &pending_withdrawal.try_to_vec().unwrap(),
+        );
+        let mut accounts_map: VersionedLookupMap<AccountId, VersionedAccount> =
+            VersionedLookupMap::new(b"a".to_vec());
+        insert_v0(
+            &mut accounts_map.map,
+            &staker_id,
+            &AccountV0 {
+                legacy_shares: HashMap::from([(pool_id.clone(), 50)]),
+                pending_withdrawals,
+            },
+        );
+        let mut account = accounts_map.get(&staker_id).unwrap();
+        assert_eq!(account.legacy_shares.get(&pool_id), Some(&50));
+        assert_eq!(account.storage_balance, DEFAULT_REGISTER_STORAGE_FEE);
+        let pending_withdrawal = account.pending_withdrawals.get(&1.into()).unwrap();
+        assert_eq!(pending_withdrawal.amount, 100);
+        assert_eq!(pending_withdrawal.unlock_epoch, 10);
+        assert_eq!(pending_withdrawal.unstake_batch_id, Some(2.into()));
+
+        // The value is saved in current version after it's upgraded.
+        account.storage_balance += 1;
+        accounts_map.insert(&staker_id, &account);
+        assert!(matches!(
+            accounts_map.map.get(&staker_id).unwrap(),
+            VersionedAccount::V1(_)
+        ));
+        assert_eq!(
+            accounts_map.get(&staker_id).unwrap().storage_balance,
+            DEFAULT_REGISTER_STORAGE_FEE + 1
+        );
+    }
+
+    #[test]
+    fn test_read_v0_values_in_unordered_map() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .block_index(5)
+            .build());
+        let pool_id: PoolId = "pool.near".parse().unwrap();
+
+        let mut submitted_unstake_batches = UnorderedMap::new(b"b".to_vec());
+        submitted_unstake_batches.insert(
+            &1.into(),
+            &SubmittedUnstakeBatch {
+                unstake_batch_id: 1.into(),
+                submit_unstake_epoch: 3,
+                total_unstake_amount: 30,
+                claimed_amount: 0,
+                is_withdrawn: false,
+            },
+        );
+        let mut staking_pools: VersionedUnorderedMap<PoolId, VersionedStakingPool> =
+            VersionedUnorderedMap::new(b"s".to_vec());
+        staking_pools.map.insert_raw(
+            &pool_id.try_to_vec().unwrap(),
+            &StakingPoolV0 {
+                pool_id: pool_id.clone(),
+                total_share_balance: 100,
+                total_staked_balance: 110,
+                stakers: UnorderedSet::new(b"t".to_vec()),
+                locked: true,
+                unlock_epoch: 7,
+                last_unstake_epoch: 3,
+                last_unstake_batch_id: Some(1.into()),
+                current_unstake_batch_id: 2.into(),
+                batched_unstake_amount: 20,
+                submitted_unstake_batches,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        let staking_pools = staking_pools.values().collect::<Vec<StakingPool>>();
+        assert_eq!(staking_pools.len(), 1);
+        let staking_pool = &staking_pools[0];
+        assert_eq!(staking_pool.total_staked_balance, 110);
+        assert_eq!(staking_pool.batched_unstake_amount, 20);
+        assert_eq!(staking_pool.in_flight_unstake_amount, 30);
+        assert!(!staking_pool.paused && !staking_pool.deprecated);
+        // The lock of legacy staking pool is stale immediately, so it can be cleared by anyone.
+        let lock = staking_pool.lock.as_ref().unwrap();
+        assert!(matches!(lock.operation, StakingPoolOperation::Reconcile));
+        assert_eq!(lock.expire_block_height, 5);
+
+        let consumer_chain_id = "chain".to_string();
+        let mut consumer_chains: VersionedUnorderedMap<ConsumerChainId, VersionedConsumerChain> =
+            VersionedUnorderedMap::new(b"c".to_vec());
+        consumer_chains.map.insert_raw(
+            &consumer_chain_id.try_to_vec().unwrap(),
+            &ConsumerChainV0 {
+                consumer_chain_id: consumer_chain_id.clone(),
+                unbonding_period: 60,
+                website: "https://chain.org".to_string(),
+                governance: accounts(1),
+                bonding_stakers: UnorderedSet::new(b"d".to_vec()),
+                treasury: accounts(2),
+                status: ConsumerChainStatus::Active,
+                pos_account_id: accounts(3),
+                blacklist: UnorderedSet::new(b"e".to_vec()),
+                register_fee: 1000,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        let (key, consumer_chain) = consumer_chains.iter().next().unwrap();
+        assert_eq!(key, consumer_chain_id);
+        assert_eq!(consumer_chain.unbonding_period, 60);
+        assert_eq!(consumer_chain.governance, accounts(1));
+        assert_eq!(consumer_chain.register_fee, 1000);
+        assert!(consumer_chain.pending_governance.is_none());
+        assert!(consumer_chain.bonded_shares.is_empty());
+        assert_eq!(consumer_chain.pending_slash_count, 0);
+    }
+}
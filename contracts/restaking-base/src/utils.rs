@@ -65,6 +65,27 @@ pub mod u128_dec_format {
     }
 }
 
+/// Parse the version like `1.1.2` into numbers, the missing parts are treated as 0.
+pub fn parse_version(version: &str) -> [u64; 3] {
+    let mut numbers = [0; 3];
+    for (i, part) in version.split('.').enumerate() {
+        assert!(i < 3, "Failed to parse version({}).", version);
+        numbers[i] = part
+            .parse()
+            .unwrap_or_else(|_| panic!("Failed to parse version({}).", version));
+    }
+    numbers
+}
+
+pub fn assert_not_downgrade(previous_version: &str, version: &str) {
+    assert!(
+        parse_version(version) >= parse_version(previous_version),
+        "Can't downgrade from version({}) to version({}).",
+        previous_version,
+        version
+    );
+}
+
 // validate rule refer to https://github.com/ChainAgnostic/CAIPs/blob/master/CAIPs/caip-2.md
 pub fn validate_chain_id(chain_id: &String) {
     let chain_id_regex = Regex::new(r"^[-a-z0-9]{3,8}:[-_a-zA-Z0-9]{1,32}$").unwrap();
@@ -140,4 +161,19 @@ mod tests {
     fn test_validate_uncorrect_chain_id_7() {
         validate_chain_id(&"namespacereference12345".to_string());
     }
+
+    #[test]
+    fn test_not_downgrade() {
+        assert_eq!(parse_version("1.1.2"), [1, 1, 2]);
+        assert_eq!(parse_version("2"), [2, 0, 0]);
+        assert_not_downgrade("1.1.2", "1.1.2");
+        assert_not_downgrade("1.1.2", "1.2.0");
+        assert_not_downgrade("1.9.9", "1.10.0");
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't downgrade")]
+    fn test_downgrade() {
+        assert_not_downgrade("1.10.0", "1.9.9");
+    }
 }
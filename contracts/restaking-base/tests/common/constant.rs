@@ -6,8 +6,12 @@ pub const STAKING_POOL_WHITELIST_WASM_BYTES: &[u8] =
     include_bytes!("../../../../res/whitelist.wasm");
 pub const MOCK_CONSUMER_CHAIN_POS_WASM_BYTES: &[u8] =
     include_bytes!("../../../../res/mock_consumer_chain_pos.wasm");
+/// The mock staking pool is read when deploying, so that other tests don't depend on it.
+pub const MOCK_STAKING_POOL_WASM_PATH: &str = "../../res/mock_staking_pool.wasm";
 pub const RESTAKING_BASE_WASM_BYTES: &[u8] =
     include_bytes!("../../../../res/restaking_base_contract.wasm");
 
 pub const CC_REGISTER_FEE: Balance = parse_near!("10 near");
 pub const SLASH_GUARANTEE: Balance = parse_near!("1 near");
+/// The number of blocks in an epoch of sandbox.
+pub const SANDBOX_EPOCH_LENGTH: u64 = 500;
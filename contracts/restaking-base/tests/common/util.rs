@@ -13,6 +13,18 @@ pub async fn register_account(worker: &Worker<Sandbox>, name: &str) -> Account {
         .unwrap()
 }
 
+/// The block timestamp advances by the block production delay for each fast forwarded block,
+/// which is less than one second in sandbox, so twice the blocks are fast forwarded.
+pub async fn fast_forward_seconds(worker: &Worker<Sandbox>, seconds: u64) -> anyhow::Result<()> {
+    worker.fast_forward(2 * seconds).await?;
+    anyhow::Ok(())
+}
+
+pub async fn fast_forward_epochs(worker: &Worker<Sandbox>, epochs: u64) -> anyhow::Result<()> {
+    worker.fast_forward(epochs * SANDBOX_EPOCH_LENGTH).await?;
+    anyhow::Ok(())
+}
+
 pub fn assert_result_success(result: &ExecutionFinalResult, msg: &str) {
     assert!(
         result.is_success(),
@@ -0,0 +1,38 @@
+use crate::common::*;
+
+pub struct MockStakingPoolContract {
+    pub deploy_account: Account,
+}
+
+impl MockStakingPoolContract {
+    pub async fn deploy(deploy_account: Account) -> MockStakingPoolContract {
+        let wasm = std::fs::read(MOCK_STAKING_POOL_WASM_PATH)
+            .expect("Failed to read mock staking pool wasm, it's built by build.sh.");
+        let result = deploy_account.deploy(&wasm).await.unwrap().details;
+
+        assert_result_success(&result, "Failed to deploy MockStakingPoolContract");
+        let result = deploy_account
+            .call(deploy_account.id(), "new")
+            .args_json(json!({}))
+            .transact()
+            .await
+            .unwrap();
+
+        assert_result_success(&result, "Failed to call MockStakingPoolContract new");
+        MockStakingPoolContract { deploy_account }
+    }
+
+    pub async fn lose_staked_balance(
+        &self,
+        signer: &Account,
+        account_id: AccountId,
+        amount: U128,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "lose_staked_balance")
+            .args_json(json!({ "account_id": account_id, "amount": amount }))
+            .transact()
+            .await
+            .unwrap()
+    }
+}
@@ -1,5 +1,6 @@
 pub mod cc_pos;
 pub mod mock_consumer_chain_pos;
+pub mod mock_staking_pool;
 pub mod nep141;
 pub mod restaking_base;
 pub mod staking_pool;
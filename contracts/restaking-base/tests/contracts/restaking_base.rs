@@ -1,4 +1,16 @@
+use near_sdk::json_types::U64;
 use near_sdk::ONE_YOCTO;
+use restaking_base_contract::migrate::StateMigrationInfo;
+use restaking_base_contract::models::liquidity_pool::LiquidityPoolInfo;
+use restaking_base_contract::models::pause::{PauseCategory, PauseMatrix};
+use restaking_base_contract::models::pending_withdrawal::PendingWithdrawal;
+use restaking_base_contract::models::protocol_config::ProtocolConfig;
+use restaking_base_contract::models::protocol_fee::{ProtocolFee, ProtocolFeeInfo};
+use restaking_base_contract::models::protocol_stats::ProtocolStats;
+use restaking_base_contract::models::role::Role;
+use restaking_base_contract::models::staker::{StakerInfo, StakerOverview};
+use restaking_base_contract::models::staking_pool::KeeperWork;
+use restaking_base_contract::models::timelock::PendingAction;
 
 use crate::common::*;
 use crate::contracts::storgae_management::*;
@@ -152,6 +164,38 @@ impl RestakingBaseContract {
             .call(&self.deploy_account.id(), "bond")
             .args_json(json!({"consumer_chain_id": consumer_chain_id, "key": key}))
             .gas(parse_gas!("150 Tgas") as u64)
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn bond_by_operator(
+        &self,
+        operator: &Account,
+        consumer_chain_id: ConsumerChainId,
+        key: String,
+        staker_id: AccountId,
+    ) -> ExecutionFinalResult {
+        operator
+            .call(&self.deploy_account.id(), "bond")
+            .args_json(
+                json!({"consumer_chain_id": consumer_chain_id, "key": key, "staker_id": staker_id}),
+            )
+            .gas(parse_gas!("150 Tgas") as u64)
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn authorize_operator(
+        &self,
+        signer: &Account,
+        operator_id: AccountId,
+        consumer_chain_id: Option<ConsumerChainId>,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "authorize_operator")
+            .args_json(json!({"operator_id": operator_id, "consumer_chain_id": consumer_chain_id}))
             .deposit(ONE_YOCTO)
             .transact()
             .await
@@ -237,6 +281,55 @@ impl RestakingBaseContract {
             .unwrap()
     }
 
+    pub async fn get_consumer_chains(
+        &self,
+        signer: &Account,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<ConsumerChainInfo> {
+        signer
+            .view(&self.deploy_account.id(), "get_consumer_chains")
+            .args_json(json!({ "from_index": from_index, "limit": limit }))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_staker_bonding_consumer_chains(
+        &self,
+        signer: &Account,
+        staker_id: AccountId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<ConsumerChainInfo> {
+        signer
+            .view(
+                &self.deploy_account.id(),
+                "get_staker_bonding_consumer_chains",
+            )
+            .args_json(json!({ "staker_id": staker_id, "from_index": from_index, "limit": limit }))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_protocol_stats(
+        &self,
+        signer: &Account,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> ProtocolStats {
+        signer
+            .view(&self.deploy_account.id(), "get_protocol_stats")
+            .args_json(json!({ "from_index": from_index, "limit": limit }))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
     pub async fn get_slash_guarantee(&self, signer: &Account) -> U128 {
         signer
             .view(&self.deploy_account.id(), "get_slash_guarantee")
@@ -255,6 +348,47 @@ impl RestakingBaseContract {
             .unwrap()
     }
 
+    pub async fn get_keeper_reward(&self, signer: &Account) -> U128 {
+        signer
+            .view(&self.deploy_account.id(), "get_keeper_reward")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_keeper_fund(&self, signer: &Account) -> U128 {
+        signer
+            .view(&self.deploy_account.id(), "get_keeper_fund")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_pending_keeper_work(&self, signer: &Account) -> Vec<KeeperWork> {
+        signer
+            .view(&self.deploy_account.id(), "get_pending_keeper_work")
+            .args_json(json!({}))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn deposit_keeper_fund(
+        &self,
+        signer: &Account,
+        amount: Balance,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "deposit_keeper_fund")
+            .deposit(amount)
+            .transact()
+            .await
+            .unwrap()
+    }
+
     pub async fn get_owner(&self, signer: &Account) -> AccountId {
         signer
             .view(&self.deploy_account.id(), "get_owner")
@@ -264,6 +398,126 @@ impl RestakingBaseContract {
             .unwrap()
     }
 
+    pub async fn get_pending_owner(&self, signer: &Account) -> Option<AccountId> {
+        signer
+            .view(&self.deploy_account.id(), "get_pending_owner")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_staker(&self, signer: &Account, staker_id: AccountId) -> Option<StakerInfo> {
+        signer
+            .view(&self.deploy_account.id(), "get_staker")
+            .args_json(json!({ "staker_id": staker_id }))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_staker_overview(
+        &self,
+        signer: &Account,
+        staker_id: AccountId,
+        from_index: Option<u32>,
+        limit: Option<u32>,
+    ) -> Option<StakerOverview> {
+        signer
+            .view(&self.deploy_account.id(), "get_staker_overview")
+            .args_json(json!({ "staker_id": staker_id, "from_index": from_index, "limit": limit }))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn reconcile_pool(
+        &self,
+        signer: &Account,
+        pool_id: AccountId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "reconcile_pool")
+            .args_json(json!({ "pool_id": pool_id }))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn check_pool_whitelisted(
+        &self,
+        signer: &Account,
+        pool_id: AccountId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "check_pool_whitelisted")
+            .args_json(json!({ "pool_id": pool_id }))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn resume_staking_pool(
+        &self,
+        signer: &Account,
+        pool_id: AccountId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "resume_staking_pool")
+            .args_json(json!({ "pool_id": pool_id }))
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_pending_withdrawals(
+        &self,
+        signer: &Account,
+        account_id: AccountId,
+    ) -> Vec<PendingWithdrawal> {
+        signer
+            .view(&self.deploy_account.id(), "get_pending_withdrawals")
+            .args_json(json!({ "account_id": account_id }))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_state_migration_info(&self, signer: &Account) -> StateMigrationInfo {
+        signer
+            .view(&self.deploy_account.id(), "get_state_migration_info")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn upgrade(&self, signer: &Account, code: &[u8]) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "upgrade")
+            .deposit(ONE_YOCTO)
+            .args_borsh(code.to_vec())
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn migrate_state(&self, signer: &Account, limit: u32) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "migrate_state")
+            .args_json(json!({ "limit": limit }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
     // #endregion
 
     // #region StakerAction
@@ -277,6 +531,22 @@ impl RestakingBaseContract {
             .await
             .unwrap()
     }
+    pub async fn stake(
+        &self,
+        signer: &Account,
+        pool_id: AccountId,
+        staker_id: Option<AccountId>,
+        stake_amount: u128,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "stake")
+            .args_json(json!({ "pool_id": pool_id, "staker_id": staker_id }))
+            .deposit(stake_amount)
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
     pub async fn ping(&self, signer: &Account, pool_id: Option<PoolId>) -> ExecutionFinalResult {
         signer
             .call(&self.deploy_account.id(), "ping")
@@ -306,7 +576,6 @@ impl RestakingBaseContract {
         signer
             .call(&self.deploy_account.id(), "decrease_stake")
             .args_json(json!({ "decrease_amount": decrease_amount }))
-            .deposit(ONE_YOCTO)
             .gas(parse_gas!("200 Tgas") as u64)
             .transact()
             .await
@@ -315,26 +584,153 @@ impl RestakingBaseContract {
     pub async fn unstake(&self, signer: &Account) -> ExecutionFinalResult {
         signer
             .call(&self.deploy_account.id(), "unstake")
-            .deposit(ONE_YOCTO)
             .gas(parse_gas!("200 Tgas") as u64)
             .transact()
             .await
             .unwrap()
     }
+    pub async fn submit_unstake_batch(
+        &self,
+        signer: &Account,
+        pool_id: AccountId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "submit_unstake_batch")
+            .args_json(json!({ "pool_id": pool_id }))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
+    pub async fn withdraw_unstake_batch(
+        &self,
+        signer: &Account,
+        pool_id: AccountId,
+        unstake_batch_id: UnstakeBatchId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "withdraw_unstake_batch")
+            .args_json(json!({ "pool_id": pool_id, "unstake_batch_id": unstake_batch_id }))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
+    pub async fn decrease_stake_to(
+        &self,
+        signer: &Account,
+        decrease_amount: U128,
+        beneficiary: AccountId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "decrease_stake")
+            .args_json(json!({ "decrease_amount": decrease_amount, "beneficiary": beneficiary }))
+            .gas(parse_gas!("200 Tgas") as u64)
+            .transact()
+            .await
+            .unwrap()
+    }
+    pub async fn transfer_position(
+        &self,
+        signer: &Account,
+        new_staker_id: AccountId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "transfer_position")
+            .args_json(json!({ "new_staker_id": new_staker_id }))
+            .deposit(ONE_YOCTO)
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
     pub async fn withdraw_all(
         &self,
         signer: &Account,
         account_id: AccountId,
-        pool_id: PoolId,
+        limit: u32,
     ) -> ExecutionFinalResult {
         signer
             .call(&self.deploy_account.id(), "withdraw_all")
+            .args_json(json!({ "account_id": account_id, "limit": limit }))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    // #endregion
+
+    // #region LiquidityPoolAction
+    pub async fn add_liquidity(&self, signer: &Account, amount: Balance) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "add_liquidity")
+            .deposit(amount)
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn remove_liquidity(&self, signer: &Account, shares: U128) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "remove_liquidity")
+            .args_json(json!({ "shares": shares }))
             .deposit(ONE_YOCTO)
             .transact()
             .await
             .unwrap()
     }
 
+    pub async fn instant_withdraw(
+        &self,
+        signer: &Account,
+        id: WithdrawalCertificate,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "instant_withdraw")
+            .args_json(json!({ "id": id }))
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn settle_liquidity_pool(
+        &self,
+        signer: &Account,
+        limit: u32,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "settle_liquidity_pool")
+            .args_json(json!({ "limit": limit }))
+            .max_gas()
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_liquidity_pool(&self, signer: &Account) -> LiquidityPoolInfo {
+        signer
+            .view(&self.deploy_account.id(), "get_liquidity_pool")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn set_liquidity_pool_fee(
+        &self,
+        signer: &Account,
+        fee_basis_points: u32,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "set_liquidity_pool_fee")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "fee_basis_points": fee_basis_points }))
+            .transact()
+            .await
+            .unwrap()
+    }
     // #endregion
 
     // #region Storage Management
@@ -401,6 +797,7 @@ impl RestakingBaseContract {
     ) -> Option<StorageBalance> {
         signer
             .view(self.get_deploy_account().id(), "storage_balance_of")
+            .args_json(json!({ "account_id": account_id }))
             .await
             .unwrap()
             .json()
@@ -423,6 +820,15 @@ impl RestakingBaseContract {
             .unwrap()
     }
 
+    pub async fn accept_ownership(&self, signer: &Account) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "accept_ownership")
+            .deposit(ONE_YOCTO)
+            .transact()
+            .await
+            .unwrap()
+    }
+
     pub async fn set_cc_register_fee(
         &self,
         signer: &Account,
@@ -451,5 +857,176 @@ impl RestakingBaseContract {
             .unwrap()
     }
 
+    pub async fn set_timelock_delay(
+        &self,
+        signer: &Account,
+        delay_seconds: DurationOfSeconds,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "set_timelock_delay")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "delay_seconds": delay_seconds }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_timelock_delay(&self, signer: &Account) -> DurationOfSeconds {
+        signer
+            .view(&self.deploy_account.id(), "get_timelock_delay")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn update_config(
+        &self,
+        signer: &Account,
+        config: &ProtocolConfig,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "update_config")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "config": config }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn set_protocol_fee(
+        &self,
+        signer: &Account,
+        protocol_fee: &ProtocolFee,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "set_protocol_fee")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "protocol_fee": protocol_fee }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn withdraw_protocol_fee(
+        &self,
+        signer: &Account,
+        amount: Option<U128>,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "withdraw_protocol_fee")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "amount": amount }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_protocol_fee_info(&self, signer: &Account) -> ProtocolFeeInfo {
+        signer
+            .view(&self.deploy_account.id(), "get_protocol_fee_info")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn get_config(&self, signer: &Account) -> ProtocolConfig {
+        signer
+            .view(&self.deploy_account.id(), "get_config")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn execute_pending_action(&self, signer: &Account, id: U64) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "execute_pending_action")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "id": id }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_pending_actions(&self, signer: &Account) -> Vec<PendingAction> {
+        signer
+            .view(&self.deploy_account.id(), "get_pending_actions")
+            .args_json(json!({}))
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
+    pub async fn set_keeper_reward(
+        &self,
+        signer: &Account,
+        new_keeper_reward: U128,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "set_keeper_reward")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "new_keeper_reward": new_keeper_reward }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn grant_role(
+        &self,
+        signer: &Account,
+        account_id: AccountId,
+        role: Role,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "grant_role")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "account_id": account_id, "role": role }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn revoke_role(
+        &self,
+        signer: &Account,
+        account_id: AccountId,
+        role: Role,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "revoke_role")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "account_id": account_id, "role": role }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn set_paused(
+        &self,
+        signer: &Account,
+        category: PauseCategory,
+        paused: bool,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(self.get_deploy_account().id(), "set_paused")
+            .deposit(ONE_YOCTO)
+            .args_json(json!({ "category": category, "paused": paused }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_pause_matrix(&self, signer: &Account) -> PauseMatrix {
+        signer
+            .view(&self.deploy_account.id(), "get_pause_matrix")
+            .await
+            .unwrap()
+            .json()
+            .unwrap()
+    }
+
     // #endregion
 }
@@ -43,6 +43,21 @@ impl WhitelistContract {
             .unwrap()
     }
 
+    pub async fn remove_staking_pool(
+        &self,
+        signer: &Account,
+        staking_pool_account_id: AccountId,
+    ) -> ExecutionFinalResult {
+        signer
+            .call(&self.deploy_account.id(), "remove_staking_pool")
+            .args_json(json!({
+                "staking_pool_account_id": staking_pool_account_id
+            }))
+            .transact()
+            .await
+            .unwrap()
+    }
+
     pub async fn is_whitelisted(
         &self,
         signer: &Account,
@@ -0,0 +1,110 @@
+mod common;
+mod contracts;
+
+use anyhow::Ok;
+use common::*;
+use restaking_base_contract::constants::DEFAULT_NUM_EPOCHS_TO_UNLOCK;
+
+#[tokio::test]
+async fn test_instant_withdraw_and_settle() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    let provider = register_account(&worker, "provider").await;
+    env.restaking_base_contract
+        .set_liquidity_pool_fee(&env.restaking_base_owner, 100)
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .storage_deposit(&provider, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .add_liquidity(&provider, parse_near!("10 near"))
+        .await
+        .into_result()?;
+
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .unstake(&env.staker1)
+        .await
+        .into_result()?;
+    let pending_withdrawal = env
+        .restaking_base_contract
+        .get_pending_withdrawals(&env.staker1, env.staker1.id().clone())
+        .await
+        .pop()
+        .unwrap();
+    let fee = pending_withdrawal.amount / 100;
+
+    let result = env
+        .restaking_base_contract
+        .instant_withdraw(&env.staker1, pending_withdrawal.withdrawal_certificate)
+        .await;
+    assert_result_success(&result, "Failed to instant_withdraw.");
+    let pay_amount: U128 = result.json()?;
+    assert_eq!(pay_amount.0, pending_withdrawal.amount - fee);
+    let liquidity_pool = env
+        .restaking_base_contract
+        .get_liquidity_pool(&provider)
+        .await;
+    assert_eq!(liquidity_pool.pending_withdrawals_count, 1);
+    assert_eq!(
+        liquidity_pool.pending_withdrawal_amount.0,
+        pending_withdrawal.amount
+    );
+
+    // The pending withdrawal is not settled until its unstake batch is withdrawn.
+    env.restaking_base_contract
+        .submit_unstake_batch(&provider, pool_id.clone())
+        .await
+        .into_result()?;
+    let settled_amount: U128 = env
+        .restaking_base_contract
+        .settle_liquidity_pool(&provider, 10)
+        .await
+        .json()?;
+    assert_eq!(settled_amount.0, 0);
+
+    fast_forward_epochs(&worker, DEFAULT_NUM_EPOCHS_TO_UNLOCK + 1).await?;
+    env.restaking_base_contract
+        .withdraw_unstake_batch(
+            &provider,
+            pool_id.clone(),
+            pending_withdrawal.unstake_batch_id.unwrap(),
+        )
+        .await
+        .into_result()?;
+    let settled_amount: U128 = env
+        .restaking_base_contract
+        .settle_liquidity_pool(&provider, 10)
+        .await
+        .json()?;
+    assert_eq!(settled_amount.0, pending_withdrawal.amount);
+    let liquidity_pool = env
+        .restaking_base_contract
+        .get_liquidity_pool(&provider)
+        .await;
+    assert_eq!(liquidity_pool.pending_withdrawals_count, 0);
+    assert_eq!(
+        liquidity_pool.available_balance.0,
+        parse_near!("10 near") + fee
+    );
+
+    // The provider takes the fee back with liquidity.
+    let removed_amount: U128 = env
+        .restaking_base_contract
+        .remove_liquidity(&provider, liquidity_pool.total_share_balance)
+        .await
+        .json()?;
+    assert_eq!(removed_amount.0, parse_near!("10 near") + fee);
+
+    Ok(())
+}
@@ -3,6 +3,12 @@ mod contracts;
 
 use anyhow::Ok;
 use common::*;
+use restaking_base_contract::constants::{
+    DEFAULT_TIMELOCK_DELAY_SECONDS, MIN_TIMELOCK_DELAY_SECONDS,
+};
+use restaking_base_contract::models::pause::PauseCategory;
+use restaking_base_contract::models::protocol_fee::ProtocolFee;
+use restaking_base_contract::models::role::Role;
 
 #[tokio::test]
 async fn test_owner_actions() -> anyhow::Result<()> {
@@ -10,6 +16,20 @@ async fn test_owner_actions() -> anyhow::Result<()> {
     let env = setup_common_test_env(&worker).await?;
 
     let signer = &env.restaking_base_owner;
+    let state_migration_info = env
+        .restaking_base_contract
+        .get_state_migration_info(signer)
+        .await;
+    assert_eq!(
+        state_migration_info.schema_version,
+        state_migration_info.current_schema_version
+    );
+    assert!(env
+        .restaking_base_contract
+        .migrate_state(signer, 10)
+        .await
+        .is_failure());
+
     env.restaking_base_contract
         .set_cc_register_fee(signer, parse_near!("15 near").into())
         .await
@@ -25,6 +45,33 @@ async fn test_owner_actions() -> anyhow::Result<()> {
         .set_slash_guarantee(signer, parse_near!("15 near").into())
         .await
         .into_result()?;
+    assert!(env
+        .restaking_base_contract
+        .set_timelock_delay(signer, 0)
+        .await
+        .is_failure());
+    env.restaking_base_contract
+        .set_timelock_delay(signer, MIN_TIMELOCK_DELAY_SECONDS)
+        .await
+        .into_result()?;
+    let pending_actions = env
+        .restaking_base_contract
+        .get_pending_actions(signer)
+        .await;
+    assert_eq!(pending_actions.len(), 2);
+    assert!(env
+        .restaking_base_contract
+        .execute_pending_action(signer, pending_actions[0].id)
+        .await
+        .is_failure());
+
+    fast_forward_seconds(&worker, DEFAULT_TIMELOCK_DELAY_SECONDS).await?;
+    for pending_action in pending_actions {
+        env.restaking_base_contract
+            .execute_pending_action(signer, pending_action.id)
+            .await
+            .into_result()?;
+    }
     assert_eq!(
         parse_near!("15 near"),
         env.restaking_base_contract
@@ -32,14 +79,162 @@ async fn test_owner_actions() -> anyhow::Result<()> {
             .await
             .0
     );
+    assert_eq!(
+        MIN_TIMELOCK_DELAY_SECONDS,
+        env.restaking_base_contract.get_timelock_delay(signer).await
+    );
+
+    env.restaking_base_contract
+        .set_keeper_reward(signer, parse_near!("0.1 near").into())
+        .await
+        .into_result()?;
+    assert_eq!(
+        parse_near!("0.1 near"),
+        env.restaking_base_contract
+            .get_keeper_reward(signer)
+            .await
+            .0
+    );
+
+    let mut config = env.restaking_base_contract.get_config(signer).await;
+    config.num_epochs_to_unlock = 3;
+    assert!(env
+        .restaking_base_contract
+        .update_config(signer, &config)
+        .await
+        .is_failure());
+    config.num_epochs_to_unlock = 5;
+    config.gas.ping_callback = 1;
+    assert!(env
+        .restaking_base_contract
+        .update_config(signer, &config)
+        .await
+        .is_failure());
+    config.gas.ping_callback = 10;
+    config.gas.migrate = 300;
+    assert!(env
+        .restaking_base_contract
+        .update_config(signer, &config)
+        .await
+        .is_failure());
+    config.gas.migrate = 100;
+    config.num_epochs_to_unlock = 5;
+    config.gas.ping = 40;
+    env.restaking_base_contract
+        .update_config(signer, &config)
+        .await
+        .into_result()?;
+    let config = env.restaking_base_contract.get_config(signer).await;
+    assert_eq!(config.num_epochs_to_unlock, 5);
+    assert_eq!(config.gas.ping, 40);
+
+    assert!(env
+        .restaking_base_contract
+        .set_protocol_fee(
+            signer,
+            &ProtocolFee {
+                register_fee_basis_points: 5_001,
+                reward_fee_basis_points: 0,
+            },
+        )
+        .await
+        .is_failure());
+    env.restaking_base_contract
+        .set_protocol_fee(
+            signer,
+            &ProtocolFee {
+                register_fee_basis_points: 1_000,
+                reward_fee_basis_points: 500,
+            },
+        )
+        .await
+        .into_result()?;
+    let protocol_fee_info = env
+        .restaking_base_contract
+        .get_protocol_fee_info(signer)
+        .await;
+    assert_eq!(
+        protocol_fee_info.protocol_fee.register_fee_basis_points,
+        1_000
+    );
+    assert_eq!(protocol_fee_info.protocol_fee.reward_fee_basis_points, 500);
+    assert_eq!(
+        protocol_fee_info.treasury_account.to_string(),
+        signer.id().to_string()
+    );
+    assert_eq!(protocol_fee_info.accrued_fee.0, 0);
+
+    // The fee is cut from the register fee which is 15 near now.
+    env.restaking_base_contract
+        .register_consumer_chain(
+            &env.cc_gov,
+            ConsumerChainRegisterParam {
+                consumer_chain_id: "fee:chain".to_string(),
+                cc_pos_account: near_sdk::AccountId::new_unchecked(
+                    env.cc_pos_contract.deploy_account.id().to_string(),
+                ),
+                unbonding_period: 86400,
+                website: "website".to_string(),
+                treasury: near_sdk::AccountId::new_unchecked(env.cc_treasury.id().to_string()),
+            },
+            parse_near!("15 near"),
+        )
+        .await
+        .into_result()?;
+    let accrued_fee = env
+        .restaking_base_contract
+        .get_protocol_fee_info(signer)
+        .await
+        .accrued_fee
+        .0;
+    assert!(accrued_fee > 0 && accrued_fee < parse_near!("1.5 near"));
+
+    assert!(env
+        .restaking_base_contract
+        .withdraw_protocol_fee(&env.cc_gov, None)
+        .await
+        .is_failure());
+    let treasury_balance = signer.view_account().await?.balance;
+    env.restaking_base_contract
+        .withdraw_protocol_fee(signer, None)
+        .await
+        .into_result()?;
+    assert!(signer.view_account().await?.balance > treasury_balance);
+    assert_eq!(
+        env.restaking_base_contract
+            .get_protocol_fee_info(signer)
+            .await
+            .accrued_fee
+            .0,
+        0
+    );
+
+    let new_owner = register_account(&worker, "new_owner").await;
+    env.restaking_base_contract
+        .set_new_owner(signer, new_owner.id().clone())
+        .await
+        .into_result()?;
+    assert_eq!(
+        signer.id().to_string(),
+        env.restaking_base_contract
+            .get_owner(signer)
+            .await
+            .to_string()
+    );
+    assert_eq!(
+        Some(new_owner.id().to_string()),
+        env.restaking_base_contract
+            .get_pending_owner(signer)
+            .await
+            .map(|owner| owner.to_string())
+    );
 
-    let new_owner = AccountId::from_str("new_owner").unwrap();
     env.restaking_base_contract
-        .set_new_owner(signer, new_owner.clone())
+        .accept_ownership(&new_owner)
         .await
         .into_result()?;
     assert_eq!(
-        new_owner.to_string(),
+        new_owner.id().to_string(),
         env.restaking_base_contract
             .get_owner(signer)
             .await
@@ -48,3 +243,122 @@ async fn test_owner_actions() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_role_actions() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let fee_manager = register_account(&worker, "fee_manager").await;
+
+    assert!(env
+        .restaking_base_contract
+        .set_keeper_reward(&fee_manager, parse_near!("0.1 near").into())
+        .await
+        .is_failure());
+    // Only owner can grant roles.
+    assert!(env
+        .restaking_base_contract
+        .grant_role(&fee_manager, fee_manager.id().clone(), Role::FeeManager)
+        .await
+        .is_failure());
+
+    env.restaking_base_contract
+        .grant_role(
+            &env.restaking_base_owner,
+            fee_manager.id().clone(),
+            Role::FeeManager,
+        )
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .set_keeper_reward(&fee_manager, parse_near!("0.1 near").into())
+        .await
+        .into_result()?;
+    assert_eq!(
+        parse_near!("0.1 near"),
+        env.restaking_base_contract
+            .get_keeper_reward(&fee_manager)
+            .await
+            .0
+    );
+    // The role doesn't grant the actions of other roles.
+    assert!(env
+        .restaking_base_contract
+        .set_paused(&fee_manager, PauseCategory::Stake, true)
+        .await
+        .is_failure());
+
+    env.restaking_base_contract
+        .revoke_role(
+            &env.restaking_base_owner,
+            fee_manager.id().clone(),
+            Role::FeeManager,
+        )
+        .await
+        .into_result()?;
+    assert!(env
+        .restaking_base_contract
+        .set_keeper_reward(&fee_manager, parse_near!("0.2 near").into())
+        .await
+        .is_failure());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let signer = &env.restaking_base_owner;
+    env.restaking_base_contract
+        .set_cc_register_fee(signer, parse_near!("15 near").into())
+        .await
+        .into_result()?;
+
+    // The upgrade requires one yocto and the upgrader role.
+    assert!(signer
+        .call(env.restaking_base_contract.deploy_account.id(), "upgrade")
+        .args_borsh(RESTAKING_BASE_WASM_BYTES.to_vec())
+        .max_gas()
+        .transact()
+        .await?
+        .is_failure());
+    let upgrader = register_account(&worker, "upgrader").await;
+    assert!(env
+        .restaking_base_contract
+        .upgrade(&upgrader, RESTAKING_BASE_WASM_BYTES)
+        .await
+        .is_failure());
+
+    env.restaking_base_contract
+        .grant_role(signer, upgrader.id().clone(), Role::Upgrader)
+        .await
+        .into_result()?;
+    let result = env
+        .restaking_base_contract
+        .upgrade(&upgrader, RESTAKING_BASE_WASM_BYTES)
+        .await;
+    assert_result_success(&result, "Failed to upgrade");
+    assert!(result
+        .logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"upgrade\"")));
+
+    // The state is migrated by the new code.
+    let state_migration_info = env
+        .restaking_base_contract
+        .get_state_migration_info(signer)
+        .await;
+    assert_eq!(
+        state_migration_info.schema_version,
+        state_migration_info.current_schema_version
+    );
+    assert_eq!(
+        parse_near!("15 near"),
+        env.restaking_base_contract
+            .get_cc_register_fee(signer)
+            .await
+            .0
+    );
+    Ok(())
+}
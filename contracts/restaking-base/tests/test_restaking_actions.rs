@@ -18,6 +18,129 @@ async fn test_register_consumer_chain() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_get_consumer_chains_pagination() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    env.restaking_base_contract
+        .register_consumer_chain(
+            &env.cc_gov,
+            ConsumerChainRegisterParam {
+                consumer_chain_id: "test:test1".to_string(),
+                cc_pos_account: near_sdk::AccountId::new_unchecked(
+                    env.cc_pos_contract.deploy_account.id().to_string(),
+                ),
+                unbonding_period: 86400 * 7,
+                website: "website".to_string(),
+                treasury: near_sdk::AccountId::new_unchecked(env.cc_treasury.id().to_string()),
+            },
+            CC_REGISTER_FEE,
+        )
+        .await
+        .into_result()?;
+
+    let consumer_chain_ids = |consumer_chains: Vec<ConsumerChainInfo>| {
+        consumer_chains
+            .into_iter()
+            .map(|consumer_chain| consumer_chain.consumer_chain_id)
+            .collect::<Vec<_>>()
+    };
+    let all = consumer_chain_ids(
+        env.restaking_base_contract
+            .get_consumer_chains(&env.staker1, None, None)
+            .await,
+    );
+    assert_eq!(all.len(), 2);
+    assert_eq!(
+        consumer_chain_ids(
+            env.restaking_base_contract
+                .get_consumer_chains(&env.staker1, Some(1), Some(1))
+                .await
+        ),
+        all[1..].to_vec()
+    );
+    assert_eq!(
+        consumer_chain_ids(
+            env.restaking_base_contract
+                .get_consumer_chains(&env.staker1, Some(0), Some(100))
+                .await
+        ),
+        all
+    );
+    assert!(env
+        .restaking_base_contract
+        .get_consumer_chains(&env.staker1, Some(2), None)
+        .await
+        .is_empty());
+    assert!(env
+        .restaking_base_contract
+        .get_consumer_chains(&env.staker1, None, Some(0))
+        .await
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_protocol_stats_pagination() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(
+            &env.staker1,
+            env.staking_pool_contract.deploy_account.id().clone(),
+            None,
+            parse_near!("2 near"),
+        )
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .register_consumer_chain(
+            &env.cc_gov,
+            ConsumerChainRegisterParam {
+                consumer_chain_id: "test:test1".to_string(),
+                cc_pos_account: near_sdk::AccountId::new_unchecked(
+                    env.cc_pos_contract.deploy_account.id().to_string(),
+                ),
+                unbonding_period: 86400 * 7,
+                website: "website".to_string(),
+                treasury: near_sdk::AccountId::new_unchecked(env.cc_treasury.id().to_string()),
+            },
+            CC_REGISTER_FEE,
+        )
+        .await
+        .into_result()?;
+
+    let all = env
+        .restaking_base_contract
+        .get_protocol_stats(&env.staker1, None, None)
+        .await;
+    assert!(!all.has_more);
+    assert_eq!(all.stake_per_pool.len(), 1);
+
+    let first_page = env
+        .restaking_base_contract
+        .get_protocol_stats(&env.staker1, Some(0), Some(1))
+        .await;
+    assert!(first_page.has_more);
+    assert_eq!(first_page.total_staked_balance, all.total_staked_balance);
+    assert_eq!(first_page.tvl, all.tvl);
+
+    let second_page = env
+        .restaking_base_contract
+        .get_protocol_stats(&env.staker1, Some(1), Some(1))
+        .await;
+    assert!(!second_page.has_more);
+    assert!(second_page.stake_per_pool.is_empty());
+    assert_eq!(second_page.tvl.0, 0);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_update_consumer_chain_info() -> anyhow::Result<()> {
     let worker = workspaces::sandbox().await?;
@@ -60,6 +183,47 @@ async fn test_bond() -> anyhow::Result<()> {
         .bond(&env.staker1, env.test_chain_id.clone(), "key".to_string())
         .await
         .into_result()?;
+
+    let bonding_consumer_chains = env
+        .restaking_base_contract
+        .get_staker_bonding_consumer_chains(&env.staker1, env.staker1.id().clone(), None, None)
+        .await;
+    assert_eq!(bonding_consumer_chains.len(), 1);
+    assert_eq!(
+        bonding_consumer_chains[0].consumer_chain_id,
+        env.test_chain_id
+    );
+    assert!(env
+        .restaking_base_contract
+        .get_staker_bonding_consumer_chains(&env.staker1, env.staker1.id().clone(), Some(1), None)
+        .await
+        .is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bond_by_operator() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    setup_staker_select_pool(&env).await?;
+    let operator = register_account(&worker, "operator").await;
+    env.restaking_base_contract
+        .authorize_operator(
+            &env.staker1,
+            operator.id().clone(),
+            Some(env.test_chain_id.clone()),
+        )
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .bond_by_operator(
+            &operator,
+            env.test_chain_id.clone(),
+            "key".to_string(),
+            env.staker1.id().clone(),
+        )
+        .await
+        .into_result()?;
     Ok(())
 }
 
@@ -92,10 +256,27 @@ async fn test_unbond() -> anyhow::Result<()> {
         .bond(&env.staker1, env.test_chain_id.clone(), "key".to_string())
         .await
         .into_result()?;
+    let storage_balance_before_unbond = env
+        .restaking_base_contract
+        .storage_balance_of(&env.staker1, env.staker1.id().clone())
+        .await
+        .unwrap();
     env.restaking_base_contract
         .unbond(&env.staker1, env.test_chain_id.clone())
         .await
         .into_result()?;
+
+    // The storage released by unbond is refunded to staker.
+    let storage_balance_after_unbond = env
+        .restaking_base_contract
+        .storage_balance_of(&env.staker1, env.staker1.id().clone())
+        .await
+        .unwrap();
+    assert!(storage_balance_after_unbond.total.0 < storage_balance_before_unbond.total.0);
+    assert_eq!(
+        storage_balance_after_unbond.available,
+        storage_balance_before_unbond.available
+    );
     Ok(())
 }
 
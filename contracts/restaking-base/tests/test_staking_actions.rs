@@ -3,6 +3,11 @@ mod contracts;
 
 use anyhow::Ok;
 use common::*;
+use restaking_base_contract::constants::DEFAULT_NUM_EPOCHS_TO_UNLOCK;
+use restaking_base_contract::models::pause::PauseCategory;
+use restaking_base_contract::models::staking_pool::PoolReconciliation;
+
+use crate::contracts::mock_staking_pool::MockStakingPoolContract;
 
 #[tokio::test]
 async fn test_select_pool() -> anyhow::Result<()> {
@@ -133,3 +138,608 @@ async fn test_unstake() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_stake_for_other_staker() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    let pool1_id = env.staking_pool1_contract.deploy_account.id().clone();
+    let funder = register_account(&worker, "funder").await;
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+
+    // The funder can't choose the staking pool for staker.
+    assert!(env
+        .restaking_base_contract
+        .stake(
+            &funder,
+            pool_id.clone(),
+            Some(env.staker1.id().clone()),
+            parse_near!("1 near")
+        )
+        .await
+        .is_failure());
+
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("1 near"))
+        .await
+        .into_result()?;
+    assert!(env
+        .restaking_base_contract
+        .stake(
+            &funder,
+            pool1_id,
+            Some(env.staker1.id().clone()),
+            parse_near!("1 near")
+        )
+        .await
+        .is_failure());
+    env.restaking_base_contract
+        .stake(
+            &funder,
+            pool_id.clone(),
+            Some(env.staker1.id().clone()),
+            parse_near!("1 near"),
+        )
+        .await
+        .into_result()?;
+
+    let staked_balance = env
+        .staking_pool_contract
+        .get_account_staked_balance(
+            &env.restaking_base_contract.deploy_account,
+            env.restaking_base_contract.deploy_account.id().clone(),
+        )
+        .await
+        .0;
+    assert_eq!(staked_balance, parse_near!("2 near"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_position() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    let staker2 = register_account(&worker, "staker2").await;
+    let third_party = register_account(&worker, "third_party").await;
+    for staker in [&env.staker1, &staker2] {
+        env.restaking_base_contract
+            .storage_deposit(staker, None, None, parse_near!("0.1 near"))
+            .await
+            .into_result()?;
+    }
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("3 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .bond(&env.staker1, env.test_chain_id.clone(), "key".to_string())
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .decrease_stake_to(
+            &env.staker1,
+            parse_near!("1 near").into(),
+            third_party.id().clone(),
+        )
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .decrease_stake(&env.staker1, parse_near!("1 near").into())
+        .await
+        .into_result()?;
+    let shares = env
+        .restaking_base_contract
+        .get_staker(&env.staker1, env.staker1.id().clone())
+        .await
+        .unwrap()
+        .shares;
+
+    let result = env
+        .restaking_base_contract
+        .transfer_position(&env.staker1, staker2.id().clone())
+        .await;
+    assert_result_success(&result, "Failed to transfer position.");
+    assert!(result.json::<bool>()?);
+
+    assert!(env
+        .restaking_base_contract
+        .get_staker(&env.staker1, env.staker1.id().clone())
+        .await
+        .is_none());
+    let new_staker = env
+        .restaking_base_contract
+        .get_staker(&staker2, staker2.id().clone())
+        .await
+        .unwrap();
+    assert_eq!(new_staker.shares, shares);
+    assert_eq!(
+        new_staker
+            .select_staking_pool
+            .map(|pool_id| pool_id.to_string()),
+        Some(pool_id.to_string())
+    );
+
+    // The pending withdrawals are moved, only the ones paid to old staker are retargeted.
+    assert!(env
+        .restaking_base_contract
+        .get_pending_withdrawals(&env.staker1, env.staker1.id().clone())
+        .await
+        .is_empty());
+    let mut beneficiaries = env
+        .restaking_base_contract
+        .get_pending_withdrawals(&staker2, staker2.id().clone())
+        .await
+        .into_iter()
+        .map(|pending_withdrawal| pending_withdrawal.beneficiary.to_string())
+        .collect::<Vec<_>>();
+    beneficiaries.sort();
+    let mut expected_beneficiaries = vec![staker2.id().to_string(), third_party.id().to_string()];
+    expected_beneficiaries.sort();
+    assert_eq!(beneficiaries, expected_beneficiaries);
+
+    // An account without a position can't transfer one.
+    assert!(env
+        .restaking_base_contract
+        .transfer_position(&third_party, env.staker1.id().clone())
+        .await
+        .is_failure());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_staker_overview() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("5 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .bond(&env.staker1, env.test_chain_id.clone(), "key".to_string())
+        .await
+        .into_result()?;
+    for _ in 0..3 {
+        env.restaking_base_contract
+            .decrease_stake(&env.staker1, parse_near!("1 near").into())
+            .await
+            .into_result()?;
+    }
+    env.restaking_base_contract
+        .blackout(
+            &env.cc_pos_contract.deploy_account,
+            env.test_chain_id.clone(),
+            env.staker1.id().clone(),
+        )
+        .await
+        .into_result()?;
+
+    let overview = env
+        .restaking_base_contract
+        .get_staker_overview(&env.staker1, env.staker1.id().clone(), None, Some(2))
+        .await
+        .unwrap();
+    assert_eq!(overview.pending_withdrawals.len(), 2);
+    assert!(overview.has_more_pending_withdrawals);
+    assert_eq!(
+        overview.blacklist_status,
+        vec![(env.test_chain_id.clone(), true)]
+    );
+
+    let overview = env
+        .restaking_base_contract
+        .get_staker_overview(&env.staker1, env.staker1.id().clone(), Some(2), Some(2))
+        .await
+        .unwrap();
+    assert_eq!(overview.pending_withdrawals.len(), 1);
+    assert!(!overview.has_more_pending_withdrawals);
+
+    let overview = env
+        .restaking_base_contract
+        .get_staker_overview(&env.staker1, env.staker1.id().clone(), Some(3), None)
+        .await
+        .unwrap();
+    assert!(overview.pending_withdrawals.is_empty());
+    assert!(!overview.has_more_pending_withdrawals);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_withdraw_all() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .unstake(&env.staker1)
+        .await
+        .into_result()?;
+    let pending_withdrawals = env
+        .restaking_base_contract
+        .get_pending_withdrawals(&env.staker1, env.staker1.id().clone())
+        .await;
+    assert_eq!(pending_withdrawals.len(), 1);
+    let unstake_batch_id = pending_withdrawals[0].unstake_batch_id.unwrap();
+
+    env.restaking_base_contract
+        .submit_unstake_batch(&env.staker1, pool_id.clone())
+        .await
+        .into_result()?;
+    fast_forward_epochs(&worker, DEFAULT_NUM_EPOCHS_TO_UNLOCK + 1).await?;
+    env.restaking_base_contract
+        .withdraw_unstake_batch(&env.staker1, pool_id.clone(), unstake_batch_id)
+        .await
+        .into_result()?;
+
+    let balance_before = env.staker1.view_account().await?.balance;
+    let result = env
+        .restaking_base_contract
+        .withdraw_all(&env.staker1, env.staker1.id().clone(), 10)
+        .await;
+    assert_result_success(&result, "Failed to withdraw_all.");
+    let withdrawn_amount: U128 = result.json()?;
+    assert_eq!(withdrawn_amount.0, pending_withdrawals[0].amount);
+    assert!(env
+        .restaking_base_contract
+        .get_pending_withdrawals(&env.staker1, env.staker1.id().clone())
+        .await
+        .is_empty());
+    assert!(env.staker1.view_account().await?.balance > balance_before);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_keeper_reward() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    let keeper = register_account(&worker, "keeper").await;
+    let keeper_reward = parse_near!("0.1 near");
+    env.restaking_base_contract
+        .set_keeper_reward(&env.restaking_base_owner, keeper_reward.into())
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .deposit_keeper_fund(&keeper, parse_near!("1 near"))
+        .await
+        .into_result()?;
+    assert!(env
+        .restaking_base_contract
+        .get_pending_keeper_work(&keeper)
+        .await
+        .is_empty());
+
+    // The tiny unstake batch can be submitted, but the keeper is not rewarded.
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .unstake(&env.staker1)
+        .await
+        .into_result()?;
+    let keeper_works = env
+        .restaking_base_contract
+        .get_pending_keeper_work(&keeper)
+        .await;
+    assert_eq!(keeper_works.len(), 1);
+    assert_eq!(keeper_works[0].pool_id.to_string(), pool_id.to_string());
+    assert!(keeper_works[0].is_unstake_batch_submittable);
+    env.restaking_base_contract
+        .submit_unstake_batch(&keeper, pool_id.clone())
+        .await
+        .into_result()?;
+    assert_eq!(
+        env.restaking_base_contract.get_keeper_fund(&keeper).await.0,
+        parse_near!("1 near")
+    );
+
+    // The batch merged in the same epoch is large enough to be rewarded.
+    let staker2 = register_account(&worker, "staker2").await;
+    env.restaking_base_contract
+        .storage_deposit(&staker2, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&staker2, pool_id.clone(), None, parse_near!("20 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .unstake(&staker2)
+        .await
+        .into_result()?;
+    let keeper_balance = keeper.view_account().await?.balance;
+    env.restaking_base_contract
+        .submit_unstake_batch(&keeper, pool_id.clone())
+        .await
+        .into_result()?;
+    assert_eq!(
+        env.restaking_base_contract.get_keeper_fund(&keeper).await.0,
+        parse_near!("1 near") - keeper_reward
+    );
+    assert!(keeper.view_account().await?.balance > keeper_balance);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage_unregister() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    let storage_balance = env
+        .restaking_base_contract
+        .storage_balance_of(&env.staker1, env.staker1.id().clone())
+        .await
+        .expect("The staker1 should be registered.");
+    assert_eq!(storage_balance.total.0, parse_near!("0.02 near"));
+
+    env.restaking_base_contract
+        .storage_unregister(&env.staker1, None)
+        .await
+        .into_result()?;
+    assert!(env
+        .restaking_base_contract
+        .storage_balance_of(&env.staker1, env.staker1.id().clone())
+        .await
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pause_category() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+
+    env.restaking_base_contract
+        .set_paused(&env.restaking_base_owner, PauseCategory::Stake, true)
+        .await
+        .into_result()?;
+    let pause_matrix = env
+        .restaking_base_contract
+        .get_pause_matrix(&env.staker1)
+        .await;
+    assert!(pause_matrix.stake);
+    assert!(!pause_matrix.unstake);
+    assert!(env
+        .restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("2 near"))
+        .await
+        .is_failure());
+    assert!(env
+        .restaking_base_contract
+        .add_liquidity(&env.staker1, parse_near!("1 near"))
+        .await
+        .is_failure());
+
+    env.restaking_base_contract
+        .set_paused(&env.restaking_base_owner, PauseCategory::Stake, false)
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("2 near"))
+        .await
+        .into_result()?;
+
+    // Pausing unstake doesn't block the other categories.
+    env.restaking_base_contract
+        .set_paused(&env.restaking_base_owner, PauseCategory::Unstake, true)
+        .await
+        .into_result()?;
+    assert!(env
+        .restaking_base_contract
+        .decrease_stake(&env.staker1, parse_near!("1 near").into())
+        .await
+        .is_failure());
+    assert!(env
+        .restaking_base_contract
+        .unstake(&env.staker1)
+        .await
+        .is_failure());
+    env.restaking_base_contract
+        .bond(&env.staker1, env.test_chain_id.clone(), "key".to_string())
+        .await
+        .into_result()?;
+
+    env.restaking_base_contract
+        .set_paused(&env.restaking_base_owner, PauseCategory::Unstake, false)
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .decrease_stake(&env.staker1, parse_near!("1 near").into())
+        .await
+        .into_result()?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reconcile_pool_with_shortfall() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let mock_staking_pool =
+        MockStakingPoolContract::deploy(register_account(&worker, "mock_staking_pool").await).await;
+    let pool_id = mock_staking_pool.deploy_account.id().clone();
+    let restaking_base_id = env.restaking_base_contract.deploy_account.id().clone();
+    env.whitelist_contract
+        .add_staking_pool(&env.foundation_account, pool_id.clone())
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("10 near"))
+        .await
+        .into_result()?;
+
+    // The pool isn't paused by a shortfall below the default threshold.
+    mock_staking_pool
+        .lose_staked_balance(
+            &env.staker1,
+            restaking_base_id.clone(),
+            parse_near!("0.001 near").into(),
+        )
+        .await
+        .into_result()?;
+    let pool_reconciliation: Option<PoolReconciliation> = env
+        .restaking_base_contract
+        .reconcile_pool(&env.staker1, pool_id.clone())
+        .await
+        .json()?;
+    let pool_reconciliation = pool_reconciliation.unwrap();
+    assert_eq!(
+        pool_reconciliation.total_shortfall.0,
+        parse_near!("0.001 near")
+    );
+    assert!(!pool_reconciliation.paused);
+
+    mock_staking_pool
+        .lose_staked_balance(
+            &env.staker1,
+            restaking_base_id.clone(),
+            parse_near!("2 near").into(),
+        )
+        .await
+        .into_result()?;
+    let pool_reconciliation: Option<PoolReconciliation> = env
+        .restaking_base_contract
+        .reconcile_pool(&env.staker1, pool_id.clone())
+        .await
+        .json()?;
+    let pool_reconciliation = pool_reconciliation.unwrap();
+    assert_eq!(
+        pool_reconciliation.staked_shortfall.0,
+        parse_near!("2 near")
+    );
+    assert_eq!(pool_reconciliation.total_shortfall.0, parse_near!("2 near"));
+    assert!(pool_reconciliation.paused);
+    assert!(env
+        .restaking_base_contract
+        .increase_stake(&env.staker1, parse_near!("1 near"))
+        .await
+        .is_failure());
+
+    // Only pool operator can resume the paused pool.
+    assert!(env
+        .restaking_base_contract
+        .resume_staking_pool(&env.staker1, pool_id.clone())
+        .await
+        .is_failure());
+    env.restaking_base_contract
+        .resume_staking_pool(&env.restaking_base_owner, pool_id.clone())
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .increase_stake(&env.staker1, parse_near!("1 near"))
+        .await
+        .into_result()?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_exit_deprecated_pool() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox().await?;
+    let env = setup_common_test_env(&worker).await?;
+    let pool_id = env.staking_pool_contract.deploy_account.id().clone();
+    let pool1_id = env.staking_pool1_contract.deploy_account.id().clone();
+    env.restaking_base_contract
+        .storage_deposit(&env.staker1, None, None, parse_near!("0.1 near"))
+        .await
+        .into_result()?;
+    env.restaking_base_contract
+        .stake(&env.staker1, pool_id.clone(), None, parse_near!("2 near"))
+        .await
+        .into_result()?;
+
+    env.whitelist_contract
+        .remove_staking_pool(&env.foundation_account, pool_id.clone())
+        .await
+        .into_result()?;
+    let result = env
+        .restaking_base_contract
+        .check_pool_whitelisted(&env.staker1, pool_id.clone())
+        .await;
+    assert_result_success(&result, "Failed to check pool whitelisted.");
+    assert!(!result.json::<bool>()?);
+    assert!(env
+        .restaking_base_contract
+        .increase_stake(&env.staker1, parse_near!("1 near"))
+        .await
+        .is_failure());
+
+    // The staker exits the deprecated pool and stakes into another one.
+    env.restaking_base_contract
+        .unstake(&env.staker1)
+        .await
+        .into_result()?;
+    let keeper_work = env
+        .restaking_base_contract
+        .get_pending_keeper_work(&env.staker1)
+        .await;
+    assert!(keeper_work
+        .iter()
+        .any(|work| work.pool_id.to_string() == pool_id.to_string()
+            && work.is_unstake_batch_submittable));
+    env.restaking_base_contract
+        .stake(&env.staker1, pool1_id.clone(), None, parse_near!("1 near"))
+        .await
+        .into_result()?;
+    assert_eq!(
+        env.restaking_base_contract
+            .get_staker(&env.staker1, env.staker1.id().clone())
+            .await
+            .unwrap()
+            .select_staking_pool
+            .map(|pool_id| pool_id.to_string()),
+        Some(pool1_id.to_string())
+    );
+
+    // The pool isn't deprecated after it's whitelisted again.
+    env.whitelist_contract
+        .add_staking_pool(&env.foundation_account, pool_id.clone())
+        .await
+        .into_result()?;
+    let result = env
+        .restaking_base_contract
+        .check_pool_whitelisted(&env.staker1, pool_id.clone())
+        .await;
+    assert!(result.json::<bool>()?);
+
+    Ok(())
+}